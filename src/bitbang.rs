@@ -0,0 +1,291 @@
+//! Software (bit-banged) SPI display interface.
+//!
+//! The controller's modest data rates make bit-banging SPI on GPIO pins viable for small panels,
+//! which is useful on boards where the hardware SPI peripherals are already in use.
+
+use crate::{
+    busy::{Busy, BusyTiming},
+    error::Ssd1680Error,
+    interface::DisplayInterface,
+    reset::{Reset, ResetTiming},
+    trace::{NoTracer, TracedDelay, Tracer},
+};
+use embedded_hal::digital::{OutputPin, PinState};
+use embedded_hal_async::delay::DelayNs;
+
+/// Error returned by `BitBangInterface`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitBangError {
+    /// Setting one of the GPIO pins failed.
+    Pin,
+    /// Reading from the controller is not supported over bit-banged SPI.
+    ReadUnsupported,
+}
+
+/// A display interface that bit-bangs SPI mode 0 (MSB first) on GPIO pins.
+///
+/// Pins are toggled as fast as the HAL allows, which is well below the controller's maximum
+/// write clock on typical microcontrollers. Reading from the controller is not supported.
+///
+/// ### Example
+///
+/// ```ignore
+/// let interface = ssd1680::bitbang::BitBangInterface::new(sck, mosi, cs, busy, dc, reset, delay);
+/// let display = ssd1680::Display::new(interface, config);
+/// ```
+pub struct BitBangInterface<SCK, MOSI, CS, BUSY, DC, RESET, DELAY, TRACER = NoTracer> {
+    /// Serial clock (output)
+    sck: SCK,
+    /// Serial data to the controller (output)
+    mosi: MOSI,
+    /// Active low chip select (output)
+    cs: CS,
+    /// Active low busy pin (input)
+    busy: BUSY,
+    /// Data/Command Control Pin (High for data, Low for command) (output)
+    dc: DC,
+    /// Pin for resetting the controller (output)
+    reset: RESET,
+    /// Delay provider for reset pulses and busy polling
+    delay: DELAY,
+    /// Timing of the hardware reset sequence
+    reset_timing: ResetTiming,
+    /// Timeout and polling interval for the BUSY pin
+    busy_timing: BusyTiming,
+    /// Receives the command stream for debugging
+    tracer: TRACER,
+}
+
+impl<SCK, MOSI, CS, BUSY, DC, RESET, DELAY> BitBangInterface<SCK, MOSI, CS, BUSY, DC, RESET, DELAY>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+    DELAY: DelayNs,
+{
+    /// Create a new bit-banged interface from GPIO pins and a delay provider.
+    pub fn new(
+        sck: SCK,
+        mosi: MOSI,
+        cs: CS,
+        busy: BUSY,
+        dc: DC,
+        reset: RESET,
+        delay: DELAY,
+    ) -> Self {
+        Self {
+            sck,
+            mosi,
+            cs,
+            busy,
+            dc,
+            reset,
+            delay,
+            reset_timing: ResetTiming::default(),
+            busy_timing: BusyTiming::default(),
+            tracer: NoTracer,
+        }
+    }
+}
+
+impl<SCK, MOSI, CS, BUSY, DC, RESET, DELAY, TRACER>
+    BitBangInterface<SCK, MOSI, CS, BUSY, DC, RESET, DELAY, TRACER>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+    DELAY: DelayNs,
+    TRACER: Tracer,
+{
+    /// Set a tracer to receive the command stream sent to the controller.
+    ///
+    /// The tracer is also called while waiting for BUSY, see `Tracer::on_busy_poll`, e.g. to feed
+    /// a watchdog during a refresh.
+    pub fn tracer<T: Tracer>(
+        self,
+        tracer: T,
+    ) -> BitBangInterface<SCK, MOSI, CS, BUSY, DC, RESET, DELAY, T> {
+        BitBangInterface {
+            sck: self.sck,
+            mosi: self.mosi,
+            cs: self.cs,
+            busy: self.busy,
+            dc: self.dc,
+            reset: self.reset,
+            delay: self.delay,
+            reset_timing: self.reset_timing,
+            busy_timing: self.busy_timing,
+            tracer,
+        }
+    }
+
+    /// Set the timing of the hardware reset sequence.
+    pub fn reset_timing(self, reset_timing: ResetTiming) -> Self {
+        Self {
+            reset_timing,
+            ..self
+        }
+    }
+
+    /// Set the timeout and polling interval used when waiting for the BUSY pin.
+    pub fn busy_timing(self, busy_timing: BusyTiming) -> Self {
+        Self {
+            busy_timing,
+            ..self
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), BitBangError> {
+        self.select(|this| this.write_bytes(data))
+    }
+
+    /// Run `f` with chip select asserted.
+    fn select(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), BitBangError>,
+    ) -> Result<(), BitBangError> {
+        self.sck.set_low().map_err(|_| BitBangError::Pin)?;
+        self.cs.set_low().map_err(|_| BitBangError::Pin)?;
+        let result = f(self);
+        self.cs.set_high().map_err(|_| BitBangError::Pin)?;
+        result
+    }
+
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BitBangError> {
+        data.iter().try_for_each(|byte| self.write_byte(*byte))
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), BitBangError> {
+        for bit in (0..8).rev() {
+            let state = PinState::from(byte & (1 << bit) != 0);
+            self.mosi.set_state(state).map_err(|_| BitBangError::Pin)?;
+            // Data is sampled on the rising edge
+            self.sck.set_high().map_err(|_| BitBangError::Pin)?;
+            self.sck.set_low().map_err(|_| BitBangError::Pin)?;
+        }
+        Ok(())
+    }
+}
+
+impl<SCK, MOSI, CS, BUSY, DC, RESET, DELAY, TRACER> DisplayInterface
+    for BitBangInterface<SCK, MOSI, CS, BUSY, DC, RESET, DELAY, TRACER>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+    DELAY: DelayNs,
+    TRACER: Tracer,
+{
+    type Error = BitBangError;
+
+    async fn reset(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.reset.pulse(&mut self.delay, self.reset_timing).await
+    }
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.tracer.on_command(command);
+        self.busy.command_sent(command);
+        self.dc.set_low().map_err(|_| BitBangError::Pin)?;
+        self.write(&[command])?;
+        self.dc.set_high().map_err(|_| BitBangError::Pin)?;
+        Ok(())
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.tracer.on_data(data.len());
+        self.busy.data_sent(data);
+        self.dc.set_high().map_err(|_| BitBangError::Pin)?;
+        self.write(data)?;
+        Ok(())
+    }
+
+    async fn send_command_with_data(
+        &mut self,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.tracer.on_command(command);
+        self.tracer.on_data(data.len());
+        self.busy.command_sent(command);
+        self.busy.data_sent(data);
+        self.select(|this| {
+            this.dc.set_low().map_err(|_| BitBangError::Pin)?;
+            this.write_byte(command)?;
+            this.dc.set_high().map_err(|_| BitBangError::Pin)?;
+            this.write_bytes(data)
+        })?;
+        Ok(())
+    }
+
+    async fn read_data(&mut self, _data: &mut [u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        Err(BitBangError::ReadUnsupported.into())
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        let mut delay = TracedDelay {
+            delay: &mut self.delay,
+            tracer: &mut self.tracer,
+            waited_ns: 0,
+        };
+        let waited_ms = self.busy.wait_ready(&mut delay, self.busy_timing).await?;
+        self.tracer.on_busy(waited_ms);
+        Ok(())
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<Self::Error>> {
+        self.busy.is_busy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        busy::FixedDelay,
+        command::{Command, UpdateSequence},
+        test_util::{Delay, Pin},
+    };
+
+    /// Counts the sleeps while waiting for BUSY.
+    #[derive(Default)]
+    struct Polls(usize);
+
+    impl Tracer for Polls {
+        fn on_busy_poll(&mut self, _waited_ms: u32) {
+            self.0 += 1;
+        }
+    }
+
+    #[futures_test::test]
+    async fn busy_waits_are_traced() {
+        let busy = FixedDelay::new().sequence_ms(25);
+        let mut interface = BitBangInterface::new(Pin, Pin, Pin, busy, Pin, Pin, Delay::default())
+            .busy_timing(BusyTiming {
+                timeout_ms: 100,
+                poll_ms: 10,
+            })
+            .tracer(Polls::default());
+        Command::UpdateDisplayOption2(UpdateSequence::POWER_ON)
+            .execute(&mut interface)
+            .await
+            .unwrap();
+        Command::UpdateDisplay
+            .execute(&mut interface)
+            .await
+            .unwrap();
+        interface.busy_wait().await.unwrap();
+        assert_eq!(interface.delay.waited_ns, 25_000_000);
+        // Sleeps of 10, 10 and 5 ms
+        assert_eq!(interface.tracer.0, 3);
+    }
+}
@@ -0,0 +1,675 @@
+//! Blocking driver for projects without an async executor.
+//!
+//! The blocking types wrap their async counterparts, so the two APIs share one implementation.
+//! The interface is built on the blocking `embedded_hal` SPI and delay traits through the
+//! [Blocking] adapter, so its futures complete without ever suspending, and the displays run
+//! each operation to completion before returning. The displays only accept such interfaces, see
+//! [BlockingInterface].
+//!
+//! Methods that don't talk to the controller, such as `rows` or `state`, are forwarded to the
+//! async display, and the blocking `GraphicDisplay` is an embedded-graphics `DrawTarget` itself.
+//! The async display can be reached with `into_inner` when needed.
+//!
+//! ### Example
+//!
+//! ```ignore
+//! use ssd1680::blocking::{Display, Interface};
+//!
+//! let interface = Interface::new_blocking(spi_device, busy, dc, reset, delay);
+//! let mut display = Display::new(interface, config);
+//! display.reset()?;
+//! display.update(&frame)?;
+//! display.deep_sleep()?;
+//! ```
+
+#[cfg(feature = "graphics-core")]
+use crate::graphics::{self, Refresh};
+use crate::{
+    busy::{Busy, FixedDelay},
+    command::{BorderWaveform, Command, DeepSleepMode, LutLoadMode},
+    config::{Config, SourceVoltages, TemperaturePolicy},
+    display::{
+        self, BitOrder, Color, ControllerState, FrameLayout, Otp, Pattern, PowerRails, RamPlane,
+        Region, Rotation,
+    },
+    error::Ssd1680Error,
+    interface::DisplayInterface,
+    reset::{NoReset, Reset},
+    stats::Stats,
+};
+#[cfg(feature = "graphics-core")]
+use core::convert::Infallible;
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+#[cfg(feature = "graphics-core")]
+use embedded_graphics_core::{
+    image::GetPixel, pixelcolor::BinaryColor, prelude::*, primitives::Rectangle,
+};
+use embedded_hal::{
+    delay,
+    digital::{InputPin, OutputPin},
+    spi,
+};
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+/// Run a future of a [BlockingInterface] to completion, which it reaches the first time it is
+/// polled.
+fn run<F: Future>(future: F) -> F::Output {
+    let mut context = Context::from_waker(Waker::noop());
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// An interface whose futures complete without suspending, as the blocking displays require.
+///
+/// Implemented for the blocking [Interface] when BUSY is an `InputPin` or a `FixedDelay` and the
+/// reset is an `OutputPin` or `NoReset`, which wait with the blocking delay only, and for the
+/// interfaces of the `mock` and `simulator` features. The trait is sealed: an interface that
+/// awaits an interrupt, such as one with a `WaitPin`, would never complete without an executor.
+pub trait BlockingInterface: DisplayInterface + sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {}
+
+    /// A BUSY strategy that waits with the delay provider only.
+    pub trait PolledBusy {}
+
+    /// A hardware reset that waits with the delay provider only.
+    pub trait PolledReset {}
+}
+
+impl<P> sealed::PolledBusy for P where P: InputPin {}
+impl sealed::PolledBusy for FixedDelay {}
+impl<P> sealed::PolledReset for P where P: OutputPin {}
+impl sealed::PolledReset for NoReset {}
+
+impl<SPI, BUSY, DC, RESET, DELAY, TRACER> sealed::Sealed
+    for crate::Interface<Blocking<SPI>, BUSY, DC, RESET, Blocking<DELAY>, TRACER>
+where
+    BUSY: sealed::PolledBusy,
+    RESET: sealed::PolledReset,
+{
+}
+
+impl<SPI, BUSY, DC, RESET, DELAY, TRACER> BlockingInterface
+    for crate::Interface<Blocking<SPI>, BUSY, DC, RESET, Blocking<DELAY>, TRACER>
+where
+    Self: DisplayInterface,
+    BUSY: sealed::PolledBusy,
+    RESET: sealed::PolledReset,
+{
+}
+
+#[cfg(feature = "mock")]
+impl<const N: usize> sealed::Sealed for crate::mock::MockInterface<'_, N> {}
+#[cfg(feature = "mock")]
+impl<const N: usize> BlockingInterface for crate::mock::MockInterface<'_, N> {}
+
+#[cfg(feature = "simulator")]
+impl sealed::Sealed for crate::simulator::SimulatorInterface {}
+#[cfg(feature = "simulator")]
+impl BlockingInterface for crate::simulator::SimulatorInterface {}
+
+/// Adapts a blocking `embedded_hal` SPI device or delay provider to the async traits.
+pub struct Blocking<T>(pub T);
+
+impl<T> spi::ErrorType for Blocking<T>
+where
+    T: spi::ErrorType,
+{
+    type Error = T::Error;
+}
+
+impl<T> SpiDevice<u8> for Blocking<T>
+where
+    T: spi::SpiDevice<u8>,
+{
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        self.0.transaction(operations)
+    }
+}
+
+impl<T> DelayNs for Blocking<T>
+where
+    T: delay::DelayNs,
+{
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns)
+    }
+}
+
+/// The hardware interface to a display, using blocking SPI and delays.
+pub type Interface<SPI, BUSY, DC, RESET, DELAY> =
+    crate::Interface<Blocking<SPI>, BUSY, DC, RESET, Blocking<DELAY>>;
+
+impl<SPI, BUSY, DC, RESET, DELAY> Interface<SPI, BUSY, DC, RESET, DELAY>
+where
+    SPI: spi::SpiDevice<u8>,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+    DELAY: delay::DelayNs,
+{
+    /// Create a new Interface from blocking embedded hal traits.
+    pub fn new_blocking(spi: SPI, busy: BUSY, dc: DC, reset: RESET, delay: DELAY) -> Self {
+        crate::Interface::new(Blocking(spi), busy, dc, reset, Blocking(delay))
+    }
+}
+
+/// Define blocking methods that run the async method of the same name to completion.
+macro_rules! blocking_methods {
+    ($(
+        $(#[$meta:meta])*
+        fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$meta])*
+            pub fn $name(&mut self $(, $arg: $ty)*) -> Result<$ret, Ssd1680Error<I::Error>> {
+                run(self.0.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+/// Define methods that call the method of the same name of the async display.
+macro_rules! forward_methods {
+    (&self; $(
+        $(#[$meta:meta])*
+        fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$meta])*
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                self.0.$name($($arg),*)
+            }
+        )*
+    };
+    (&mut self; $(
+        $(#[$meta:meta])*
+        fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$meta])*
+            pub fn $name(&mut self $(, $arg: $ty)*) -> $ret {
+                self.0.$name($($arg),*)
+            }
+        )*
+    };
+}
+
+/// Define the methods of `display::Display` that are shared by `Display` and `GraphicDisplay`.
+///
+/// `update`, `partial_update` and `partial_update_regions` are left out, as `GraphicDisplay`
+/// defines its own.
+macro_rules! display_methods {
+    () => {
+        blocking_methods! {
+            /// Perform a hardware reset followed by software reset.
+            fn reset(&mut self) -> ();
+            /// Wake the controller from deep sleep, keeping the contents of the RAM.
+            fn wake(&mut self) -> ();
+            /// Show a boot splash image as quickly as possible.
+            fn splash(&mut self, black: &[u8]) -> ();
+            /// Override the temperature used to select the waveform.
+            fn override_temperature(&mut self, celsius: i8) -> ();
+            /// Update the display with a frame rendered independently of the display.
+            #[cfg(feature = "graphics-core")]
+            fn present(
+                &mut self,
+                frame: &crate::frame::Frame<impl AsMut<[u8]> + AsRef<[u8]>>
+            ) -> ();
+            /// Update the display with a B/W frame produced by an iterator.
+            fn update_from_iter(&mut self, black: impl Iterator<Item = u8>) -> ();
+            /// Update the display with a B/W frame rendered one row at a time.
+            fn update_rows(&mut self, render: impl FnMut(u16, &mut [u8])) -> ();
+            /// Update the display with a B/W frame from an iterator of packed rows.
+            fn update_from_rows(&mut self, rows: impl Iterator<Item = impl AsRef<[u8]>>) -> ();
+            /// Perform a full refresh of the current RAM contents.
+            fn refresh(&mut self) -> ();
+            /// Start a full refresh of the current RAM contents.
+            fn start_refresh(&mut self) -> ();
+            /// Wait for a refresh to complete.
+            fn wait_refresh(&mut self) -> ();
+            /// Write a whole frame to one of the RAM planes.
+            fn write_ram(&mut self, plane: RamPlane, data: &[u8]) -> ();
+            /// Write a whole frame to one of the RAM planes from an iterator.
+            fn write_ram_from_iter(
+                &mut self,
+                plane: RamPlane,
+                data: impl Iterator<Item = u8>
+            ) -> ();
+            /// Write a whole frame to the red RAM.
+            fn write_red_ram(&mut self, data: &[u8]) -> ();
+            /// Read a whole frame from one of the RAM planes.
+            fn read_ram(&mut self, plane: RamPlane, data: &mut [u8]) -> ();
+            /// Read a whole frame from the B/W RAM.
+            fn read_black_ram(&mut self, data: &mut [u8]) -> ();
+            /// Fill one of the RAM planes with `value`.
+            fn fill_ram(&mut self, plane: RamPlane, value: u8) -> ();
+            /// Fill the red RAM with `value`.
+            fn fill_red_ram(&mut self, value: u8) -> ();
+            /// Fill both RAM planes so the next refresh shows `color`.
+            fn clear_ram(&mut self, color: Color) -> ();
+            /// Update a region of the display, supplying the previous contents of the region.
+            fn partial_update_with_previous(
+                &mut self,
+                image: &[u8],
+                previous: &[u8],
+                start_x_px: u16,
+                start_y_px: u16,
+                width_px: u16,
+                height_px: u16
+            ) -> ();
+            /// Write `image` to a window of one of the RAM planes.
+            fn write_window(
+                &mut self,
+                plane: RamPlane,
+                image: &[u8],
+                start_x_px: u16,
+                start_y_px: u16,
+                width_px: u16,
+                height_px: u16
+            ) -> ();
+            /// Read the panel temperature in °C.
+            fn read_temperature(&mut self) -> i16;
+            /// Use an external temperature sensor.
+            fn use_external_temperature_sensor(&mut self) -> ();
+            /// Use the internal temperature sensor.
+            fn use_internal_temperature_sensor(&mut self) -> ();
+            /// Read the display options and user ID from OTP.
+            fn read_otp(&mut self) -> Otp;
+            /// Program VCOM into OTP.
+            #[cfg(feature = "otp-program")]
+            fn program_vcom_otp(&mut self, vcom: u8) -> ();
+            /// Program the display option and user ID into OTP.
+            #[cfg(feature = "otp-program")]
+            fn program_display_option_otp(
+                &mut self,
+                display_option: &[u8; 10],
+                user_id: &[u8; 10]
+            ) -> ();
+            /// Check the booster and VCI supply levels.
+            fn check_power_rails(&mut self) -> PowerRails;
+            /// Calculate the CRC of the OTP content.
+            fn read_crc(&mut self) -> u16;
+            /// Check the CRC of the OTP content.
+            fn verify_crc(&mut self, expected: u16) -> bool;
+            /// Measure the VCOM voltage of the panel.
+            fn sense_vcom(&mut self, duration: u8) -> u8;
+            /// Calibrate VCOM for the panel.
+            fn calibrate_vcom(&mut self, duration: u8) -> u8;
+            /// Clear ghosting by flashing the panel.
+            fn deghost(&mut self, cycles: u8) -> ();
+            /// Fill the B/W RAM with a test pattern and perform a full refresh.
+            fn draw_test_pattern(&mut self, pattern: Pattern) -> ();
+            /// Reload the waveform LUT from OTP.
+            fn reload_otp_waveform(&mut self, mode: LutLoadMode) -> ();
+            /// Enter deep sleep mode.
+            fn deep_sleep(&mut self) -> ();
+            /// Enter deep sleep with the given mode.
+            fn deep_sleep_mode(&mut self, mode: DeepSleepMode) -> ();
+            /// Disable the analog block and clock signal.
+            fn power_off(&mut self) -> ();
+            /// Enable the clock signal and analog block.
+            fn power_on(&mut self) -> ();
+            /// Send a command to the controller.
+            fn command(&mut self, command: Command) -> ();
+            /// Send a raw command byte and its data.
+            fn write_raw(&mut self, command: u8, data: &[u8]) -> ();
+            /// Send a raw command byte and read its response.
+            fn read_raw(&mut self, command: u8, data: &mut [u8]) -> ();
+            /// Change the border waveform.
+            fn set_border(&mut self, border: BorderWaveform) -> ();
+            /// Invert the black/white RAM when displaying it.
+            fn set_inverted(&mut self, inverted: bool) -> ();
+        }
+
+        forward_methods! { &self;
+            /// Access the interface, e.g. to inspect a mock in tests.
+            fn interface() -> &I;
+            /// Returns the state of the controller, as tracked by the driver.
+            fn state() -> ControllerState;
+            /// Returns whether the B/W RAM holds the last frame written.
+            fn ram_retained() -> bool;
+            /// Returns the border waveform in use.
+            fn border() -> BorderWaveform;
+            /// Returns whether the black/white RAM is inverted when displayed.
+            fn is_inverted() -> bool;
+            /// Returns the source driving voltages in use.
+            fn source_voltages() -> Option<SourceVoltages>;
+            /// Returns the number of partial updates since the last full refresh.
+            fn partial_updates_since_full_refresh() -> u16;
+            /// Returns the statistics collected since the display was created.
+            fn stats() -> Stats;
+            /// Returns the size of a whole frame in bytes.
+            fn frame_size() -> usize;
+            /// Returns the number of rows of the panel.
+            fn rows() -> u16;
+            /// Returns the number of columns of the panel.
+            fn cols() -> u8;
+            /// Returns the number of columns of the panel in bytes.
+            fn cols_as_bytes() -> u8;
+            /// Returns the layout of frame buffers.
+            fn frame_layout() -> FrameLayout;
+            /// Returns the bit order of frame buffers.
+            fn bit_order() -> BitOrder;
+            /// Returns the rotation of the display.
+            fn rotation() -> Rotation;
+            /// Returns the offset added to drawing coordinates.
+            fn drawing_offset() -> (i16, i16);
+        }
+
+        forward_methods! { &mut self;
+            /// Sample the BUSY signal once, without waiting.
+            fn is_busy() -> Result<bool, Ssd1680Error<I::Error>>;
+            /// Returns whether a refresh has completed, without waiting.
+            fn poll_refresh() -> Result<bool, Ssd1680Error<I::Error>>;
+            /// Set the amount the panel has been used.
+            fn set_panel_usage(usage: u32) -> ();
+            /// Set the policy adjusting refreshes according to the panel temperature.
+            fn set_temperature_policy(policy: &'a dyn TemperaturePolicy) -> ();
+            /// Reset the statistics.
+            fn reset_stats() -> ();
+        }
+
+        /// Create an empty frame for this display, to render independently of it.
+        #[cfg(feature = "graphics-core")]
+        pub fn new_frame<F>(&self, buffer: F) -> crate::frame::Frame<F>
+        where
+            F: AsMut<[u8]> + AsRef<[u8]>,
+        {
+            self.0.new_frame(buffer)
+        }
+    };
+}
+
+/// A blocking display. See `display::Display` for documentation of the methods.
+pub struct Display<'a, I>(display::Display<'a, I>)
+where
+    I: BlockingInterface;
+
+impl<'a, I> Display<'a, I>
+where
+    I: BlockingInterface,
+{
+    /// Create a new display instance from a DisplayInterface and Config.
+    pub fn new(interface: I, config: Config<'a>) -> Self {
+        Self(display::Display::new(interface, config))
+    }
+
+    /// Unwrap the async display.
+    pub fn into_inner(self) -> display::Display<'a, I> {
+        self.0
+    }
+
+    /// Release the interface and configuration.
+    pub fn release(self) -> (I, Config<'a>) {
+        self.0.release()
+    }
+
+    blocking_methods! {
+        /// Update the display with the supplied B/W buffer.
+        fn update(&mut self, black: &[u8]) -> ();
+        /// Update a region of the display.
+        fn partial_update(
+            &mut self,
+            image: &[u8],
+            start_x_px: u16,
+            start_y_px: u16,
+            width_px: u16,
+            height_px: u16
+        ) -> ();
+        /// Update several regions of the display with a single refresh.
+        fn partial_update_regions(&mut self, regions: &[(&[u8], Region)]) -> ();
+    }
+
+    display_methods!();
+}
+
+#[cfg(feature = "graphics-core")]
+/// A blocking graphics display. See `graphics::GraphicDisplay` for documentation of the
+/// methods.
+pub struct GraphicDisplay<'a, I, B = &'a mut [u8]>(graphics::GraphicDisplay<'a, I, B>)
+where
+    I: BlockingInterface;
+
+#[cfg(feature = "graphics-core")]
+impl<'a, I, B> GraphicDisplay<'a, I, B>
+where
+    I: BlockingInterface,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Promote a blocking `Display` to a `GraphicDisplay`.
+    pub fn new(display: Display<'a, I>, black_buffer: B, work_buffer: B) -> Self {
+        Self(graphics::GraphicDisplay::new(
+            display.0,
+            black_buffer,
+            work_buffer,
+        ))
+    }
+
+    /// Promote a blocking `Display` to a `GraphicDisplay` that performs differential partial
+    /// updates.
+    pub fn with_previous_frame(
+        display: Display<'a, I>,
+        black_buffer: B,
+        work_buffer: B,
+        previous_buffer: B,
+    ) -> Self {
+        Self(graphics::GraphicDisplay::with_previous_frame(
+            display.0,
+            black_buffer,
+            work_buffer,
+            previous_buffer,
+        ))
+    }
+
+    /// Unwrap the async graphics display.
+    pub fn into_inner(self) -> graphics::GraphicDisplay<'a, I, B> {
+        self.0
+    }
+
+    /// Release the display and the buffers.
+    pub fn release(self) -> (Display<'a, I>, B, B, Option<B>) {
+        let (display, black_buffer, work_buffer, previous_buffer) = self.0.release();
+        (Display(display), black_buffer, work_buffer, previous_buffer)
+    }
+
+    blocking_methods! {
+        /// Update the display by writing the buffer to the controller.
+        fn update(&mut self) -> ();
+        /// Update a region of the display from the buffer.
+        fn partial_update(
+            &mut self,
+            start_x_px: u16,
+            start_y_px: u16,
+            width_px: u16,
+            height_px: u16
+        ) -> ();
+        /// Update the part of the display covered by a rectangle in drawing coordinates.
+        fn partial_update_rect(
+            &mut self,
+            rect: embedded_graphics_core::primitives::Rectangle
+        ) -> ();
+        /// Update several regions of the display from the buffer with a single refresh.
+        fn partial_update_regions(&mut self, regions: &[Region]) -> ();
+        /// Scroll the contents of a rectangle in drawing coordinates vertically and partially
+        /// update it.
+        fn scroll_vertical(&mut self, rect: Rectangle, pixels: i32) -> ();
+        /// Update the part of the display that changed since the previous update.
+        fn update_diff(&mut self) -> bool;
+        /// Present the frame drawn since the last update, choosing the refresh automatically.
+        fn swap_and_update(&mut self) -> Refresh;
+        /// Update the region drawn to since the last flush.
+        fn flush_dirty(&mut self) -> bool;
+    }
+
+    display_methods!();
+
+    forward_methods! { &self;
+        /// Returns the clip rectangle, in rotated coordinates.
+        fn clip() -> Rectangle;
+        /// Returns the color of the pixel at `x`, `y` in the buffer, in rotated coordinates.
+        fn get_pixel(x: u32, y: u32) -> Option<BinaryColor>;
+        /// Returns a copy of the buffer as an image.
+        #[cfg(feature = "std")]
+        fn snapshot() -> crate::snapshot::Snapshot;
+    }
+
+    forward_methods! { &mut self;
+        /// Set the display rotation.
+        fn set_rotation(rotation: Rotation) -> ();
+        /// Restrict subsequent drawing to `clip`, in rotated coordinates.
+        fn set_clip(clip: Rectangle) -> ();
+        /// Allow drawing to the whole display again.
+        fn clear_clip() -> ();
+        /// Invert the pixels inside `rect`, in drawing coordinates.
+        fn invert_region(rect: Rectangle) -> ();
+        /// Returns the byte-aligned region drawn to since the last call, and resets the tracking.
+        fn take_dirty_region() -> Option<Region>;
+        /// Clear the buffer, filling it with a single color.
+        fn clear(color: BinaryColor) -> ();
+        /// Copy a packed 1 bit per pixel image into the buffer at `x`, `y`, in rotated coordinates.
+        fn blit(
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+            data: &[u8],
+            stride: usize
+        ) -> Result<(), Ssd1680Error<I::Error>>;
+        /// Copy a binary PBM (P4) image into the buffer at `x`, `y`, in rotated coordinates.
+        #[cfg(feature = "image-formats")]
+        fn blit_pbm(x: u32, y: u32, pbm: &[u8]) -> Result<(), crate::image::ImageError>;
+        /// Copy an XBM image into the buffer at `x`, `y`, in rotated coordinates.
+        #[cfg(feature = "image-formats")]
+        fn blit_xbm(x: u32, y: u32, xbm: &str) -> Result<(), crate::image::ImageError>;
+    }
+}
+
+#[cfg(feature = "graphics-core")]
+impl<'a, I, const N: usize> GraphicDisplay<'a, I, [u8; N]>
+where
+    I: BlockingInterface,
+{
+    /// Promote a blocking `Display` to a `GraphicDisplay` with buffers of its own.
+    ///
+    /// Returns `InvalidArgument` if `N` is not the `frame_size` of the display.
+    pub fn new_owned(display: Display<'a, I>) -> Result<Self, Ssd1680Error<I::Error>> {
+        graphics::GraphicDisplay::new_owned(display.0).map(Self)
+    }
+
+    /// Promote a blocking `Display` to a `GraphicDisplay` with buffers of its own that performs
+    /// differential partial updates.
+    ///
+    /// Returns `InvalidArgument` if `N` is not the `frame_size` of the display.
+    pub fn with_previous_frame_owned(
+        display: Display<'a, I>,
+    ) -> Result<Self, Ssd1680Error<I::Error>> {
+        graphics::GraphicDisplay::with_previous_frame_owned(display.0).map(Self)
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "graphics-core"))]
+impl<'a, I> GraphicDisplay<'a, I, alloc::boxed::Box<[u8]>>
+where
+    I: BlockingInterface,
+{
+    /// Promote a blocking `Display` to a `GraphicDisplay` with buffers allocated from the heap.
+    pub fn new_boxed(display: Display<'a, I>) -> Self {
+        Self(graphics::GraphicDisplay::new_boxed(display.0))
+    }
+
+    /// Promote a blocking `Display` to a `GraphicDisplay` with buffers allocated from the heap
+    /// that performs differential partial updates.
+    pub fn with_previous_frame_boxed(display: Display<'a, I>) -> Self {
+        Self(graphics::GraphicDisplay::with_previous_frame_boxed(
+            display.0,
+        ))
+    }
+}
+
+#[cfg(feature = "graphics-core")]
+impl<I, B> DrawTarget for GraphicDisplay<'_, I, B>
+where
+    I: BlockingInterface,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Infallible>
+    where
+        Iter: IntoIterator<Item = Pixel<BinaryColor>>,
+    {
+        self.0.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: BinaryColor) -> Result<(), Infallible> {
+        self.0.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: BinaryColor) -> Result<(), Infallible> {
+        DrawTarget::clear(&mut self.0, color)
+    }
+}
+
+#[cfg(feature = "graphics-core")]
+impl<I, B> GetPixel for GraphicDisplay<'_, I, B>
+where
+    I: BlockingInterface,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    type Color = BinaryColor;
+
+    fn pixel(&self, point: Point) -> Option<BinaryColor> {
+        self.0.pixel(point)
+    }
+}
+
+#[cfg(feature = "graphics-core")]
+impl<I, B> OriginDimensions for GraphicDisplay<'_, I, B>
+where
+    I: BlockingInterface,
+{
+    fn size(&self) -> Size {
+        self.0.size()
+    }
+}
+
+#[cfg(all(test, feature = "mock", feature = "graphics"))]
+mod tests {
+    use super::*;
+    use crate::{graphics::BLACK, mock::MockInterface, test_util::builder};
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+
+    #[test]
+    fn blocking_displays_run_operations_to_completion() {
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(MockInterface::<256>::new(), config);
+        display.reset().unwrap();
+        assert_eq!(display.state(), ControllerState::Ready);
+        assert_eq!((display.rows(), display.cols()), (8, 8));
+
+        let mut display: GraphicDisplay<'_, _, [u8; 8]> =
+            GraphicDisplay::new_owned(display).unwrap();
+        Rectangle::new(Point::zero(), Size::new(8, 1))
+            .into_styled(PrimitiveStyle::with_fill(BLACK))
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(display.get_pixel(0, 0), Some(BLACK));
+        display.update().unwrap();
+        display.deep_sleep().unwrap();
+        assert_eq!(display.state(), ControllerState::Asleep);
+    }
+}
@@ -0,0 +1,336 @@
+//! Ways of waiting for the controller's BUSY signal.
+//!
+//! Any `InputPin` can be used as the BUSY pin of an interface, in which case it is polled, every
+//! 10 ms by default. Wrapping a pin that also implements `embedded_hal_async::digital::Wait` in
+//! [WaitPin] awaits the falling edge instead, letting the MCU sleep through a refresh. The
+//! timeout and polling interval are set with [BusyTiming]. Boards that don't connect BUSY at all
+//! can use [FixedDelay], which waits typical durations for each operation instead.
+
+use crate::{
+    command::{Operation, OperationTracker},
+    error::Ssd1680Error,
+};
+use core::{
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+};
+use embedded_hal::digital::InputPin;
+use embedded_hal_async::{delay::DelayNs, digital::Wait};
+
+/// How long to wait for the BUSY signal, and how often to poll it.
+///
+/// Full refreshes at low temperatures can take longer than the default 5 s timeout, while fast
+/// partial updates finish quickly enough that a shorter polling interval noticeably reduces
+/// latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BusyTiming {
+    /// Time after which waiting gives up with `Ssd1680Error::BusyTimeout`
+    pub timeout_ms: u32,
+    /// Interval between samples of a polled BUSY pin
+    pub poll_ms: u32,
+}
+
+impl Default for BusyTiming {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 5_000,
+            poll_ms: 10,
+        }
+    }
+}
+
+/// A source of the controller's BUSY signal.
+pub trait Busy {
+    /// Sample the BUSY signal once, without waiting.
+    fn is_busy<E>(&mut self) -> Result<bool, Ssd1680Error<E>>;
+
+    /// Wait for the controller to indicate it is not busy, using `delay` for the timeout.
+    ///
+    /// Returns the approximate time waited in milliseconds, if it is known.
+    fn wait_ready<E>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timing: BusyTiming,
+    ) -> impl Future<Output = Result<Option<u32>, Ssd1680Error<E>>>;
+
+    /// Called by the interface with each command sent to the controller.
+    fn command_sent(&mut self, _command: u8) {}
+
+    /// Called by the interface with the data sent for the last command.
+    fn data_sent(&mut self, _data: &[u8]) {}
+}
+
+impl<P> Busy for P
+where
+    P: InputPin,
+{
+    fn is_busy<E>(&mut self) -> Result<bool, Ssd1680Error<E>> {
+        self.is_high().map_err(|_| Ssd1680Error::BusyPinError)
+    }
+
+    async fn wait_ready<E>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timing: BusyTiming,
+    ) -> Result<Option<u32>, Ssd1680Error<E>> {
+        let poll_ms = timing.poll_ms.max(1);
+        let mut waited_ms = 0;
+        while self.is_busy()? {
+            if waited_ms >= timing.timeout_ms {
+                return Err(Ssd1680Error::BusyTimeout { waited_ms });
+            }
+            delay.delay_ms(poll_ms).await;
+            waited_ms = waited_ms.saturating_add(poll_ms);
+        }
+        Ok(Some(waited_ms))
+    }
+}
+
+/// A BUSY pin that is waited on with `embedded_hal_async::digital::Wait`.
+///
+/// Rather than polling, waiting awaits the pin going low, typically with an edge interrupt. The
+/// timeout still applies, and is slept in steps of `BusyTiming::poll_ms` so that
+/// `Tracer::on_busy_poll` is called while waiting. Raise `poll_ms` to wake less often.
+pub struct WaitPin<P>(pub P);
+
+impl<P> Busy for WaitPin<P>
+where
+    P: InputPin + Wait,
+{
+    fn is_busy<E>(&mut self) -> Result<bool, Ssd1680Error<E>> {
+        self.0.is_high().map_err(|_| Ssd1680Error::BusyPinError)
+    }
+
+    async fn wait_ready<E>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timing: BusyTiming,
+    ) -> Result<Option<u32>, Ssd1680Error<E>> {
+        let mut ready = pin!(self.0.wait_for_low());
+        let mut timeout = pin!(sleep_in_steps(delay, timing.timeout_ms, timing.poll_ms));
+        poll_fn(|cx| {
+            if let Poll::Ready(result) = ready.as_mut().poll(cx) {
+                return Poll::Ready(result.map(|_| None).map_err(|_| Ssd1680Error::BusyPinError));
+            }
+            if timeout.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Err(Ssd1680Error::BusyTimeout {
+                    waited_ms: timing.timeout_ms,
+                }));
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// Sleep for `ms` in steps of at most `step_ms`, so that a traced delay reports each step.
+async fn sleep_in_steps(delay: &mut impl DelayNs, ms: u32, step_ms: u32) {
+    let step_ms = step_ms.max(1);
+    let mut slept_ms = 0;
+    while slept_ms < ms {
+        let sleep_ms = step_ms.min(ms - slept_ms);
+        delay.delay_ms(sleep_ms).await;
+        slept_ms += sleep_ms;
+    }
+}
+
+/// Waits fixed durations in place of a BUSY pin, for boards that don't connect it.
+///
+/// The duration depends on the operation started by the last command: the update sequence sent
+/// with `UpdateDisplayOption2` distinguishes full refreshes, partial refreshes, and other
+/// sequences such as powering on or loading the LUT. The defaults are typical for the panels this
+/// driver supports, but refresh times grow at low temperatures, so check the data sheet of the
+/// panel.
+///
+/// The controller can't be sampled, so `is_busy` always reports that it is not busy and
+/// `Display::poll_refresh` can't be used. Only `BusyTiming::poll_ms` is used, to sleep in steps
+/// so that `Tracer::on_busy_poll` is called while waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedDelay {
+    full_refresh_ms: u32,
+    partial_refresh_ms: u32,
+    sequence_ms: u32,
+    reset_ms: u32,
+    operations: OperationTracker,
+    /// The operation started by the last command, not yet waited for
+    pending: Option<Operation>,
+}
+
+impl Default for FixedDelay {
+    fn default() -> Self {
+        Self {
+            full_refresh_ms: 4_000,
+            partial_refresh_ms: 1_000,
+            sequence_ms: 200,
+            reset_ms: 10,
+            operations: OperationTracker::new(),
+            pending: None,
+        }
+    }
+}
+
+impl FixedDelay {
+    /// Create a new FixedDelay with the default durations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time to wait for a full refresh.
+    pub fn full_refresh_ms(self, full_refresh_ms: u32) -> Self {
+        Self {
+            full_refresh_ms,
+            ..self
+        }
+    }
+
+    /// Set the time to wait for a partial refresh.
+    pub fn partial_refresh_ms(self, partial_refresh_ms: u32) -> Self {
+        Self {
+            partial_refresh_ms,
+            ..self
+        }
+    }
+
+    /// Set the time to wait for update sequences that don't refresh the panel, such as loading
+    /// the LUT.
+    pub fn sequence_ms(self, sequence_ms: u32) -> Self {
+        Self {
+            sequence_ms,
+            ..self
+        }
+    }
+
+    /// Set the time to wait after a software reset.
+    pub fn reset_ms(self, reset_ms: u32) -> Self {
+        Self { reset_ms, ..self }
+    }
+
+    fn duration_ms(&self, operation: Operation) -> u32 {
+        match operation {
+            Operation::SoftReset => self.reset_ms,
+            Operation::FullRefresh => self.full_refresh_ms,
+            Operation::PartialRefresh => self.partial_refresh_ms,
+            Operation::Sequence => self.sequence_ms,
+        }
+    }
+}
+
+impl Busy for FixedDelay {
+    fn is_busy<E>(&mut self) -> Result<bool, Ssd1680Error<E>> {
+        Ok(false)
+    }
+
+    async fn wait_ready<E>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timing: BusyTiming,
+    ) -> Result<Option<u32>, Ssd1680Error<E>> {
+        // Only wait once for each operation
+        let duration_ms = self
+            .pending
+            .take()
+            .map_or(0, |operation| self.duration_ms(operation));
+        sleep_in_steps(delay, duration_ms, timing.poll_ms).await;
+        Ok(Some(duration_ms))
+    }
+
+    fn command_sent(&mut self, command: u8) {
+        self.pending = self.operations.command_sent(command);
+    }
+
+    fn data_sent(&mut self, data: &[u8]) {
+        self.operations.data_sent(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::UpdateSequence, test_util::Delay};
+
+    /// Returns the time `busy` waits after `command`, sent with `data`.
+    async fn waited_ms(busy: &mut FixedDelay, command: u8, data: &[u8]) -> u64 {
+        busy.command_sent(command);
+        busy.data_sent(data);
+        let mut delay = Delay::default();
+        busy.wait_ready::<()>(&mut delay, BusyTiming::default())
+            .await
+            .unwrap();
+        delay.waited_ns / 1_000_000
+    }
+
+    #[futures_test::test]
+    async fn fixed_delays_follow_the_operation_started() {
+        let mut busy = FixedDelay::new()
+            .full_refresh_ms(3_000)
+            .partial_refresh_ms(500)
+            .sequence_ms(100)
+            .reset_ms(20);
+        assert_eq!(waited_ms(&mut busy, 0x12, &[]).await, 20);
+
+        let full = UpdateSequence::DISPLAY_MODE_1.bits();
+        assert_eq!(waited_ms(&mut busy, 0x22, &[full]).await, 0);
+        assert_eq!(waited_ms(&mut busy, 0x20, &[]).await, 3_000);
+        // The operation is only waited for once
+        assert_eq!(waited_ms(&mut busy, 0x24, &[0xFF]).await, 0);
+        // The sequence is kept until it is replaced
+        assert_eq!(waited_ms(&mut busy, 0x20, &[]).await, 3_000);
+
+        let partial = UpdateSequence::DISPLAY_MODE_2.bits();
+        busy.command_sent(0x22);
+        busy.data_sent(&[partial]);
+        assert_eq!(waited_ms(&mut busy, 0x20, &[]).await, 500);
+
+        let load_lut = UpdateSequence::LOAD_LUT_MODE_1.bits();
+        busy.command_sent(0x22);
+        busy.data_sent(&[load_lut]);
+        assert_eq!(waited_ms(&mut busy, 0x20, &[]).await, 100);
+
+        // An invalid sequence doesn't drive the display
+        busy.command_sent(0x22);
+        busy.data_sent(&[0x04]);
+        assert_eq!(waited_ms(&mut busy, 0x20, &[]).await, 100);
+    }
+
+    #[futures_test::test]
+    async fn fixed_delays_are_cancelled_by_later_commands() {
+        let mut busy = FixedDelay::new();
+        busy.command_sent(0x12);
+        assert_eq!(waited_ms(&mut busy, 0x4E, &[0x00]).await, 0);
+        assert!(!busy.is_busy::<()>().unwrap());
+    }
+
+    #[futures_test::test]
+    async fn long_timeouts_do_not_overflow() {
+        /// A BUSY pin that never goes low.
+        struct Stuck;
+
+        impl embedded_hal::digital::ErrorType for Stuck {
+            type Error = core::convert::Infallible;
+        }
+
+        impl InputPin for Stuck {
+            fn is_high(&mut self) -> Result<bool, Self::Error> {
+                Ok(true)
+            }
+
+            fn is_low(&mut self) -> Result<bool, Self::Error> {
+                Ok(false)
+            }
+        }
+
+        let timing = BusyTiming {
+            timeout_ms: u32::MAX,
+            poll_ms: u32::MAX - 1,
+        };
+        let result = Stuck.wait_ready::<()>(&mut Delay::default(), timing).await;
+        assert_eq!(
+            result,
+            Err(Ssd1680Error::BusyTimeout {
+                waited_ms: u32::MAX
+            })
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! An SPI device built from an exclusively owned SPI bus and chip select pin.
+//!
+//! Many HALs only provide an `SpiBus`. [ChipSelectDevice] turns one into an `SpiDevice`, toggling
+//! the chip select pin around each transaction. [ChipSelectBus], constructed through
+//! `Interface::from_bus`, can also keep the pin asserted while the interface sends a command and
+//! its data, toggling DC in between.
+
+use crate::interface::Transport;
+use core::fmt::Debug;
+use embedded_hal::{
+    digital::OutputPin,
+    spi::{self, ErrorKind, ErrorType, Operation},
+};
+use embedded_hal_async::{
+    delay::DelayNs,
+    spi::{SpiBus, SpiDevice},
+};
+
+/// An error from a [ChipSelectDevice].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChipSelectError<BUS, CS> {
+    /// The SPI bus failed.
+    Spi(BUS),
+    /// Setting the chip select pin failed.
+    ChipSelect(CS),
+}
+
+impl<BUS, CS> spi::Error for ChipSelectError<BUS, CS>
+where
+    BUS: spi::Error,
+    CS: Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ChipSelectError::Spi(error) => error.kind(),
+            ChipSelectError::ChipSelect(_) => ErrorKind::ChipSelectFault,
+        }
+    }
+}
+
+/// An `SpiDevice` with exclusive use of an SPI bus, selected by an active low chip select pin.
+pub struct ChipSelectDevice<BUS, CS, DELAY> {
+    bus: BUS,
+    cs: CS,
+    delay: DELAY,
+    /// Delay between asserting CS and the first clock edge
+    setup_ns: u32,
+    /// Delay between the last clock edge and releasing CS
+    hold_ns: u32,
+}
+
+impl<BUS, CS, DELAY> ChipSelectDevice<BUS, CS, DELAY>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Create a new device from a bus, chip select pin and delay provider.
+    ///
+    /// The chip select pin is released immediately. No setup or hold delay is applied unless
+    /// configured with [cs_timing](#method.cs_timing).
+    pub fn new(bus: BUS, mut cs: CS, delay: DELAY) -> Result<Self, CS::Error> {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            setup_ns: 0,
+            hold_ns: 0,
+        })
+    }
+
+    /// Set the delays after asserting and before releasing the chip select pin, in nanoseconds.
+    pub fn cs_timing(self, setup_ns: u32, hold_ns: u32) -> Self {
+        Self {
+            setup_ns,
+            hold_ns,
+            ..self
+        }
+    }
+
+    /// Release the bus, chip select pin and delay provider.
+    pub fn release(self) -> (BUS, CS, DELAY) {
+        (self.bus, self.cs, self.delay)
+    }
+
+    async fn run(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), BUS::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(buf) => self.bus.read(buf).await?,
+                Operation::Write(buf) => self.bus.write(buf).await?,
+                Operation::Transfer(read, write) => self.bus.transfer(read, write).await?,
+                Operation::TransferInPlace(buf) => self.bus.transfer_in_place(buf).await?,
+                Operation::DelayNs(ns) => {
+                    self.bus.flush().await?;
+                    self.delay.delay_ns(*ns).await;
+                }
+            }
+        }
+        self.bus.flush().await
+    }
+}
+
+impl<BUS, CS, DELAY> ErrorType for ChipSelectDevice<BUS, CS, DELAY>
+where
+    BUS: ErrorType,
+    CS: OutputPin,
+{
+    type Error = ChipSelectError<BUS::Error, CS::Error>;
+}
+
+impl<BUS, CS, DELAY> SpiDevice<u8> for ChipSelectDevice<BUS, CS, DELAY>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        self.cs.set_low().map_err(ChipSelectError::ChipSelect)?;
+        if self.setup_ns > 0 {
+            self.delay.delay_ns(self.setup_ns).await;
+        }
+
+        // Release CS even if the bus failed, then report the first error
+        let result = self.run(operations).await;
+
+        if self.hold_ns > 0 {
+            self.delay.delay_ns(self.hold_ns).await;
+        }
+        let cs_result = self.cs.set_high();
+
+        result.map_err(ChipSelectError::Spi)?;
+        cs_result.map_err(ChipSelectError::ChipSelect)
+    }
+}
+
+/// An exclusively owned SPI bus and chip select pin, used by `Interface::from_bus`.
+///
+/// Each transfer is framed by the chip select pin as with [ChipSelectDevice], except while the
+/// interface keeps the controller selected to send a command and its data in one assertion.
+pub struct ChipSelectBus<BUS, CS, DELAY> {
+    device: ChipSelectDevice<BUS, CS, DELAY>,
+    selected: bool,
+}
+
+impl<BUS, CS, DELAY> ChipSelectBus<BUS, CS, DELAY>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Create a new bus from a bus, chip select pin and delay provider.
+    ///
+    /// The chip select pin is released immediately.
+    pub fn new(bus: BUS, cs: CS, delay: DELAY) -> Result<Self, CS::Error> {
+        Ok(Self {
+            device: ChipSelectDevice::new(bus, cs, delay)?,
+            selected: false,
+        })
+    }
+
+    /// Set the delays after asserting and before releasing the chip select pin, in nanoseconds.
+    pub fn cs_timing(self, setup_ns: u32, hold_ns: u32) -> Self {
+        Self {
+            device: self.device.cs_timing(setup_ns, hold_ns),
+            ..self
+        }
+    }
+
+    /// Release the bus, chip select pin and delay provider.
+    pub fn release(self) -> (BUS, CS, DELAY) {
+        self.device.release()
+    }
+}
+
+impl<BUS, CS, DELAY> Transport for ChipSelectBus<BUS, CS, DELAY>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+    DELAY: DelayNs,
+{
+    type Error = ChipSelectError<BUS::Error, CS::Error>;
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        if self.selected {
+            self.device
+                .bus
+                .write(data)
+                .await
+                .map_err(ChipSelectError::Spi)
+        } else {
+            SpiDevice::write(&mut self.device, data).await
+        }
+    }
+
+    async fn read(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        if self.selected {
+            self.device
+                .bus
+                .read(data)
+                .await
+                .map_err(ChipSelectError::Spi)
+        } else {
+            SpiDevice::read(&mut self.device, data).await
+        }
+    }
+
+    async fn select(&mut self) -> Result<(), Self::Error> {
+        let device = &mut self.device;
+        device.cs.set_low().map_err(ChipSelectError::ChipSelect)?;
+        self.selected = true;
+        if device.setup_ns > 0 {
+            device.delay.delay_ns(device.setup_ns).await;
+        }
+        Ok(())
+    }
+
+    async fn deselect(&mut self) -> Result<(), Self::Error> {
+        let device = &mut self.device;
+        self.selected = false;
+        // Release CS even if the bus failed, then report the first error
+        let result = device.bus.flush().await;
+        if device.hold_ns > 0 {
+            device.delay.delay_ns(device.hold_ns).await;
+        }
+        let cs_result = device.cs.set_high();
+
+        result.map_err(ChipSelectError::Spi)?;
+        cs_result.map_err(ChipSelectError::ChipSelect)
+    }
+}
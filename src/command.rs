@@ -1,7 +1,9 @@
-use crate::interface::DisplayInterface;
+use crate::{display::RamPlane, error::Ssd1680Error, interface::DisplayInterface};
 
 const MAX_GATES: u16 = 296;
-const MAX_DUMMY_LINE_PERIOD: u8 = 127;
+pub(crate) const MAX_DUMMY_LINE_PERIOD: u8 = 127;
+pub(crate) const MAX_GATE_LINE_WIDTH: u8 = 0x0F;
+pub(crate) const MAX_VCOM_SENSE_DURATION: u8 = 0x0F;
 
 trait Contains<C>
 where
@@ -14,6 +16,7 @@ where
 /// will auto-increment the row and column addresses when image data is written using the
 /// `WriteImageData` command.
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum IncrementAxis {
     /// X direction
     Horizontal,
@@ -22,6 +25,7 @@ pub enum IncrementAxis {
 }
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DataEntryMode {
     DecrementXDecrementY,
     IncrementXDecrementY,
@@ -29,43 +33,357 @@ pub enum DataEntryMode {
     IncrementYIncrementX, // POR
 }
 
-#[derive(Clone, Copy)]
+/// The temperature sensor used by the controller to select the waveform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TemperatureSensor {
+    /// The sensor built into the controller
     Internal,
+    /// An LM75 compatible I2C sensor connected to the controller
     External,
 }
 
-#[derive(Clone, Copy)]
-#[allow(non_camel_case_types)]
-pub enum DisplayUpdateSequenceOption {
-    EnableClockSignal,
-    DisableClockSignal,
-    EnableClockSignal_EnableAnalog,
-    DisableAnalog_DisableClockSignal,
-    EnableClockSignal_LoadLutMode1_DisableClockSignal,
-    EnableClockSignal_LoadLutMode2_DisableClockSignal,
-    EnableClockSignal_LoadTemp_LoadLutMode1_DisableClockSignal,
-    EnableClockSignal_LoadTemp_LoadLutMode2_DisableClockSignal,
-    EnableClockSignal_EnableAnalog_DisplayMode1_DisableAnalog_DisableOscillator,
-    EnableClockSignal_EnableAnalog_DisplayMode2_DisableAnalog_DisableOscillator,
-    EnableClockSignal_LoadTemp_EnableAnalog_DisplayMode1_DisableAnalog_DisableOscillator,
-    EnableClockSignal_LoadTemp_EnableAnalog_DisplayMode2_DisableAnalog_DisableOscillator,
+/// Display update sequence run by the `UpdateDisplay` command (command 0x22).
+///
+/// Each flag enables one step of the sequence. Flags are combined with `|` or with the builder
+/// style methods, and the resulting sequence can be checked with `is_valid` before use:
+///
+/// ```
+/// use ssd1680::command::UpdateSequence;
+///
+/// let sequence = UpdateSequence::ENABLE_CLOCK
+///     | UpdateSequence::ENABLE_ANALOG
+///     | UpdateSequence::DISPLAY
+///     | UpdateSequence::DISABLE_ANALOG
+///     | UpdateSequence::DISABLE_CLOCK;
+/// assert_eq!(sequence, UpdateSequence::DISPLAY_MODE_1);
+/// assert!(sequence.is_valid());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UpdateSequence(u8);
+
+impl UpdateSequence {
+    /// Enable the clock signal.
+    pub const ENABLE_CLOCK: Self = Self(0x80);
+    /// Enable the analog block (booster and regulators).
+    pub const ENABLE_ANALOG: Self = Self(0x40);
+    /// Load the temperature value from the selected sensor.
+    pub const LOAD_TEMPERATURE: Self = Self(0x20);
+    /// Load the waveform LUT from OTP.
+    pub const LOAD_LUT: Self = Self(0x10);
+    /// Use Display Mode 2 instead of Display Mode 1 for `LOAD_LUT` and `DISPLAY`.
+    pub const MODE_2: Self = Self(0x08);
+    /// Drive the panel from the contents of RAM.
+    pub const DISPLAY: Self = Self(0x04);
+    /// Disable the analog block.
+    pub const DISABLE_ANALOG: Self = Self(0x02);
+    /// Disable the clock signal (oscillator).
+    pub const DISABLE_CLOCK: Self = Self(0x01);
+
+    /// Enable clock and analog (0xC0).
+    pub const POWER_ON: Self = Self(0xC0);
+    /// Disable analog and clock (0x03).
+    pub const POWER_OFF: Self = Self(0x03);
+    /// Load the Display Mode 1 LUT from OTP (0x91).
+    pub const LOAD_LUT_MODE_1: Self = Self(0x91);
+    /// Load the Display Mode 2 LUT from OTP (0x99).
+    pub const LOAD_LUT_MODE_2: Self = Self(0x99);
+    /// Load the temperature then the Display Mode 1 LUT from OTP (0xB1).
+    pub const LOAD_TEMPERATURE_LUT_MODE_1: Self = Self(0xB1);
+    /// Load the temperature then the Display Mode 2 LUT from OTP (0xB9).
+    pub const LOAD_TEMPERATURE_LUT_MODE_2: Self = Self(0xB9);
+    /// Full refresh using the LUT already loaded (0xC7).
+    pub const DISPLAY_MODE_1: Self = Self(0xC7);
+    /// Partial refresh using the LUT already loaded (0xCF).
+    pub const DISPLAY_MODE_2: Self = Self(0xCF);
+    /// Load temperature and LUT, then full refresh (0xF7).
+    pub const LOAD_AND_DISPLAY_MODE_1: Self = Self(0xF7);
+    /// Load temperature and LUT, then partial refresh (0xFF).
+    pub const LOAD_AND_DISPLAY_MODE_2: Self = Self(0xFF);
+
+    /// A sequence with no steps.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Create a sequence from a raw 0x22 parameter, returning `None` if it is not a valid
+    /// combination (see `is_valid`).
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        let sequence = Self(bits);
+        if sequence.is_valid() {
+            Some(sequence)
+        } else {
+            None
+        }
+    }
+
+    /// The raw 0x22 parameter.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the combination of both sequences.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns the sequence without the steps of `other`.
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Returns true if every step of `other` is also part of this sequence.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if the sequence is one the controller can run.
+    ///
+    /// Loading the temperature or LUT, enabling analog and driving the display all need the clock
+    /// enabled, driving the display needs the analog block enabled, and `MODE_2` only has meaning
+    /// together with `LOAD_LUT` or `DISPLAY`.
+    pub const fn is_valid(self) -> bool {
+        let needs_clock = Self::ENABLE_ANALOG
+            .union(Self::LOAD_TEMPERATURE)
+            .union(Self::LOAD_LUT)
+            .union(Self::DISPLAY);
+        if self.0 & needs_clock.0 != 0 && !self.contains(Self::ENABLE_CLOCK) {
+            return false;
+        }
+        if self.contains(Self::DISPLAY) && !self.contains(Self::ENABLE_ANALOG) {
+            return false;
+        }
+        if self.contains(Self::MODE_2) && self.0 & Self::LOAD_LUT.union(Self::DISPLAY).0 == 0 {
+            return false;
+        }
+        self.0 != 0
+    }
+
+    /// Add `ENABLE_CLOCK` to the sequence.
+    pub const fn enable_clock(self) -> Self {
+        self.union(Self::ENABLE_CLOCK)
+    }
+
+    /// Add `ENABLE_ANALOG` to the sequence.
+    pub const fn enable_analog(self) -> Self {
+        self.union(Self::ENABLE_ANALOG)
+    }
+
+    /// Add `LOAD_TEMPERATURE` to the sequence.
+    pub const fn load_temperature(self) -> Self {
+        self.union(Self::LOAD_TEMPERATURE)
+    }
+
+    /// Add `LOAD_LUT` to the sequence.
+    pub const fn load_lut(self) -> Self {
+        self.union(Self::LOAD_LUT)
+    }
+
+    /// Add `MODE_2` to the sequence.
+    pub const fn mode_2(self) -> Self {
+        self.union(Self::MODE_2)
+    }
+
+    /// Add `DISPLAY` to the sequence.
+    pub const fn display(self) -> Self {
+        self.union(Self::DISPLAY)
+    }
+
+    /// Add `DISABLE_ANALOG` to the sequence.
+    pub const fn disable_analog(self) -> Self {
+        self.union(Self::DISABLE_ANALOG)
+    }
+
+    /// Add `DISABLE_CLOCK` to the sequence.
+    pub const fn disable_clock(self) -> Self {
+        self.union(Self::DISABLE_CLOCK)
+    }
 }
 
+impl core::ops::BitOr for UpdateSequence {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl core::ops::BitOrAssign for UpdateSequence {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+// Commands that start an operation signalled on BUSY
+const SOFT_RESET: u8 = 0x12;
+const UPDATE_DISPLAY: u8 = 0x20;
+const UPDATE_DISPLAY_OPTION_2: u8 = 0x22;
+
+/// An operation of the controller signalled on BUSY, as told by `OperationTracker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operation {
+    SoftReset,
+    FullRefresh,
+    PartialRefresh,
+    /// An update sequence that doesn't drive the display, such as loading the LUT
+    Sequence,
+}
+
+/// Follows the commands sent to the controller to tell which operation each starts.
+///
+/// The update sequence sent with `UpdateDisplayOption2` (0x22) tells whether `UpdateDisplay`
+/// (0x20) refreshes the display, and whether fully or partially.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OperationTracker {
+    last_command: Option<u8>,
+    sequence: UpdateSequence,
+}
+
+impl OperationTracker {
+    pub(crate) const fn new() -> Self {
+        Self {
+            last_command: None,
+            sequence: UpdateSequence::empty(),
+        }
+    }
+
+    /// Returns the operation started by `command`, if any.
+    pub(crate) fn command_sent(&mut self, command: u8) -> Option<Operation> {
+        self.last_command = Some(command);
+        match command {
+            SOFT_RESET => Some(Operation::SoftReset),
+            UPDATE_DISPLAY if self.sequence.contains(UpdateSequence::DISPLAY) => {
+                if self.sequence.contains(UpdateSequence::MODE_2) {
+                    Some(Operation::PartialRefresh)
+                } else {
+                    Some(Operation::FullRefresh)
+                }
+            }
+            UPDATE_DISPLAY => Some(Operation::Sequence),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn data_sent(&mut self, data: &[u8]) {
+        if self.last_command == Some(UPDATE_DISPLAY_OPTION_2) {
+            if let Some(&bits) = data.first() {
+                self.sequence = UpdateSequence::from_bits(bits).unwrap_or(UpdateSequence::empty());
+            }
+        }
+    }
+}
+
+/// Selects which waveform is loaded from OTP into the LUT register.
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LutLoadMode {
+    /// Load the Display Mode 1 (full refresh) waveform.
+    Mode1,
+    /// Load the Display Mode 2 (partial refresh) waveform.
+    Mode2,
+    /// Read the temperature sensor, then load the matching Display Mode 1 waveform.
+    TemperatureMode1,
+    /// Read the temperature sensor, then load the matching Display Mode 2 waveform.
+    TemperatureMode2,
+}
+
+impl From<LutLoadMode> for UpdateSequence {
+    fn from(mode: LutLoadMode) -> Self {
+        match mode {
+            LutLoadMode::Mode1 => Self::LOAD_LUT_MODE_1,
+            LutLoadMode::Mode2 => Self::LOAD_LUT_MODE_2,
+            LutLoadMode::TemperatureMode1 => Self::LOAD_TEMPERATURE_LUT_MODE_1,
+            LutLoadMode::TemperatureMode2 => Self::LOAD_TEMPERATURE_LUT_MODE_2,
+        }
+    }
+}
+
+/// How a RAM plane is used when refreshing the display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum RamOption {
+    /// Display the RAM content as written
     Normal,
+    /// Ignore the RAM content, treating it as all 0
     Bypass,
+    /// Display the inverse of the RAM content
     Invert,
 }
 
-#[derive(Clone, Copy)]
+/// The range of source outputs driven by the controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SourceOption {
+    /// Drive all 176 sources
     SourceFromS0ToS175,
+    /// Drive the central 160 sources
     SourceFromS8ToS167,
 }
 
-#[derive(Clone, Copy)]
+/// Fixed voltage level for the border (VBD).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BorderLevel {
+    Vss,
+    Vsh1,
+    Vsl,
+    Vsh2,
+}
+
+/// Border waveform (VBD) selection, controlling the color of the bezel area of the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BorderWaveform {
+    /// Follow the LUT waveform for white (LUT1). POR for this driver.
+    White,
+    /// Follow the LUT waveform for black (LUT0).
+    Black,
+    /// Follow the LUT waveform for red (LUT2), on tri-color panels.
+    Red,
+    /// Follow the LUT waveform for the given LUT (0-3).
+    FollowLut(u8),
+    /// Hold the border at a fixed level.
+    FixedLevel(BorderLevel),
+    /// Hold the border at VCOM. This locks the border, preventing it flashing during partial
+    /// updates.
+    Vcom,
+    /// Leave the border floating (high impedance).
+    HiZ,
+}
+
+impl BorderWaveform {
+    /// The value of the border waveform register (command 0x3C).
+    pub fn register_value(self) -> u8 {
+        match self {
+            BorderWaveform::White => 0b0000_0101,
+            BorderWaveform::Black => 0b0000_0100,
+            BorderWaveform::Red => 0b0000_0110,
+            BorderWaveform::FollowLut(lut) => {
+                debug_assert!(lut < 4);
+                0b0000_0100 | (lut & 0b11)
+            }
+            BorderWaveform::FixedLevel(level) => {
+                let level = match level {
+                    BorderLevel::Vss => 0b00,
+                    BorderLevel::Vsh1 => 0b01,
+                    BorderLevel::Vsl => 0b10,
+                    BorderLevel::Vsh2 => 0b11,
+                };
+                0b0100_0000 | (level << 4)
+            }
+            BorderWaveform::Vcom => 0b1000_0000,
+            BorderWaveform::HiZ => 0b1100_0000,
+        }
+    }
+}
+
+/// Deep sleep modes, see `Display::deep_sleep_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DeepSleepMode {
     /// Not sleeping
     Normal,
@@ -75,8 +393,20 @@ pub enum DeepSleepMode {
     DiscardRAM,
 }
 
+/// OTP program modes, enabled by the `otp-program` feature.
+#[cfg(feature = "otp-program")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OtpProgramMode {
+    /// Normal operation
+    Normal,
+    /// Program the OTP with the internally generated programming voltage
+    InternalVoltage,
+}
+
 /// A command that can be issued to the controller.
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Command {
     /// Set the MUX of gate lines, scanning sequence and direction
     /// 0: MAX gate lines
@@ -104,19 +434,26 @@ pub enum Command {
     /// Perform a soft reset, and reset all parameters to their default values
     /// BUSY will be high when in progress.
     SoftReset,
-    // /// Start HV ready detection. Read result with `ReadStatusBit` command
-    // StartHVReadyDetection,
-    // /// Start VCI level detection
-    // /// 0: threshold
-    // /// Read result with `ReadStatusBit` command
-    // StartVCILevelDetection(u8),
+    /// Start HV ready detection. Read result with `ReadStatusBit` command
+    /// 0: Bits 6-4 cool down duration, 10ms x (value + 1). Bits 2-0 number of cool down loops.
+    /// BUSY will be high when in progress.
+    StartHVReadyDetection(u8),
+    /// Start VCI level detection
+    /// 0: threshold, 0x03 to 0x07 for 2.3V to 2.7V
+    /// Read result with `ReadStatusBit` command
+    /// BUSY will be high when in progress.
+    StartVCILevelDetection(u8),
     /// Specify internal or external temperature sensor
     TemperatureSensorSelection(TemperatureSensor),
     /// Write to the temperature sensor register
     WriteTemperatureSensor(u16),
-    /// Read from the temperature sensor register
-    ReadTemperatureSensor(u16),
+    /// Read from the temperature sensor register. The two byte value is read with `read`.
+    ReadTemperatureSensor,
     /// Write a command to the external temperature sensor
+    /// 0: Bits 7-6 select the bytes sent: 00 address and pointer, 01 adds parameter 1, 10 adds
+    ///    parameters 1 and 2, 11 address only. Bits 5-0 are the pointer.
+    /// 1: Parameter 1
+    /// 2: Parameter 2
     WriteExternalTemperatureSensor(u8, u8, u8),
     /// Activate display update sequence. BUSY will be high when in progress.
     UpdateDisplay,
@@ -126,36 +463,52 @@ pub enum Command {
     /// 2: Source option
     UpdateDisplayOption1(RamOption, RamOption, SourceOption),
     /// Set display update sequence options
-    UpdateDisplayOption2(DisplayUpdateSequenceOption),
-    // Read from RAM (not implemented)
-    // ReadData,
+    UpdateDisplayOption2(UpdateSequence),
+    /// Start writing to a RAM plane at the address counter. The data is sent with
+    /// `DisplayInterface::send_data` after executing the command, so a frame can be streamed a
+    /// chunk at a time. `BufCommand::WriteBlackData` and `WriteRedData` send a whole buffer.
+    WriteRam(RamPlane),
+    /// Read from the RAM plane selected with `ReadRamOption`, starting at the address counter.
+    /// The data is read with `read`, and the first byte read is a dummy.
+    ReadRam,
     /// Enter VCOM sensing and hold for duration defined by VCOMSenseDuration
     /// BUSY will be high when in progress.
     EnterVCOMSensing,
     /// Set VCOM sensing duration
+    /// 0: The hold time before the VCOM value is acquired, (value + 1) seconds. Max 15.
     VCOMSenseDuration(u8),
-    // /// Program VCOM register into OTP
-    // ProgramVCOMIntoOTP,
+    /// Program the VCOM register into OTP. BUSY will be high when in progress.
+    #[cfg(feature = "otp-program")]
+    ProgramVCOMIntoOTP,
     /// Write VCOM register from MCU interface
     WriteVCOM(u8),
-    // ReadDisplayOption,
-    // ReadUserId,
-    // StatusBitRead,
+    /// Read the display option registers. The eleven byte value is read with `read`.
+    ReadDisplayOption,
+    /// Read the user ID stored in OTP. The ten byte value is read with `read`.
+    ReadUserId,
+    /// Read the status bits. The one byte value is read with `read`.
+    ReadStatusBit,
     // ProgramWaveformSetting,
     // LoadWaveformSetting,
-    // CalculateCRC,
-    // ReadCRC,
-    // ProgramOTP,
-    // WriteDisplayOption,
-    // WriteUserId,
-    // OTPProgramMode,
+    /// Calculate the CRC of the OTP content. BUSY will be high when in progress.
+    CalculateCRC,
+    /// Read the result of `CalculateCRC`. The two byte value is read with `read`.
+    ReadCRC,
+    /// Program the display option and user ID registers into OTP. BUSY will be high when in
+    /// progress.
+    #[cfg(feature = "otp-program")]
+    ProgramOTP,
+    /// Set the OTP program mode
+    #[cfg(feature = "otp-program")]
+    OTPProgramMode(OtpProgramMode),
     /// Set the number of dummy line period in terms of gate line width (TGate)
     DummyLinePeriod(u8),
     /// Set the gate line width (TGate)
     GateLineWidth(u8),
     /// Select border waveform for VBD
-    BorderWaveform(u8),
-    // ReadRamOption,
+    BorderWaveform(BorderWaveform),
+    /// Select the RAM plane read by `ReadRam`
+    ReadRamOption(RamPlane),
     /// Set the start/end positions of the window address in the X direction
     /// 0: Start
     /// 1: End
@@ -165,8 +518,12 @@ pub enum Command {
     /// 1: End
     StartEndYPosition(u16, u16),
     /// Auto write red RAM for regular pattern
+    /// 0: Bit 7 first step value, bits 6:4 step height, bits 2:0 step width.
+    /// BUSY will be high when in progress.
     AutoWriteRedPattern(u8),
-    /// Auto write red RAM for regular pattern
+    /// Auto write black/white RAM for regular pattern
+    /// 0: Bit 7 first step value, bits 6:4 step height, bits 2:0 step width.
+    /// BUSY will be high when in progress.
     AutoWriteBlackPattern(u8),
     /// Set RAM X address
     XAddress(u8),
@@ -183,6 +540,7 @@ pub enum Command {
 /// Enumerates commands that can be sent to the controller that accept a slice argument buffer. This
 /// is separated from `Command` so that the lifetime parameter of the argument buffer slice does
 /// not pervade code which never invokes these two commands.
+#[derive(Clone, Copy)]
 pub enum BufCommand<'buf> {
     /// Write to black/white RAM
     /// 1 = White
@@ -192,8 +550,14 @@ pub enum BufCommand<'buf> {
     /// 1 = Red
     /// 0 = Use contents of black/white RAM
     WriteRedData(&'buf [u8]),
-    /// Write LUT register (70 bytes)
+    /// Write LUT register (153 bytes)
     WriteLUT(&'buf [u8]),
+    /// Write the display option register, programmed into OTP by `Command::ProgramOTP` (10 bytes)
+    #[cfg(feature = "otp-program")]
+    WriteDisplayOption(&'buf [u8]),
+    /// Write the user ID register, programmed into OTP by `Command::ProgramOTP` (10 bytes)
+    #[cfg(feature = "otp-program")]
+    WriteUserId(&'buf [u8]),
 }
 
 /// Populates data buffer (array) and returns a pair (tuple) with command and
@@ -232,7 +596,10 @@ macro_rules! pack {
 
 impl Command {
     /// Execute the command, transmitting any associated data as well.
-    pub async fn execute<I: DisplayInterface>(&self, interface: &mut I) -> Result<(), I::Error> {
+    pub async fn execute<I: DisplayInterface>(
+        &self,
+        interface: &mut I,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
         use self::Command::*;
 
         let mut buf = [0u8; 4];
@@ -275,6 +642,11 @@ impl Command {
                 pack!(buf, 0x11, [axis | mode])
             }
             SoftReset => pack!(buf, 0x12, []),
+            StartHVReadyDetection(detection) => pack!(buf, 0x14, [detection & 0x77]),
+            StartVCILevelDetection(threshold) => {
+                debug_assert!(Contains::contains(&(0x03..=0x07), threshold));
+                pack!(buf, 0x15, [threshold])
+            }
             TemperatureSensorSelection(temperature_sensor) => {
                 let sensor = match temperature_sensor {
                     TemperatureSensor::External => 0x48_u8,
@@ -287,21 +659,22 @@ impl Command {
                 let values = value.to_be_bytes();
                 pack!(buf, 0x1A, [values[0], values[1]])
             }
-            // ReadTemperatureSensor(u16) => {
-            // }
-            // WriteExternalTemperatureSensor(u8, u8, u8) => {
-            // }
+            ReadTemperatureSensor => pack!(buf, 0x1B, []),
+            WriteExternalTemperatureSensor(control, param1, param2) => {
+                pack!(buf, 0x1C, [control, param1, param2])
+            }
             UpdateDisplay => pack!(buf, 0x20, []),
             UpdateDisplayOption1(black_ram_option, red_ram_option, source_option) => {
+                // The red RAM option is in the high nibble, the black/white one in the low
                 let black = match black_ram_option {
                     RamOption::Normal => 0b0000_0000,
-                    RamOption::Bypass => 0b0100_0000,
-                    RamOption::Invert => 0b1000_0000,
+                    RamOption::Bypass => 0b0000_0100,
+                    RamOption::Invert => 0b0000_1000,
                 };
                 let red = match red_ram_option {
                     RamOption::Normal => 0b0000_0000,
-                    RamOption::Bypass => 0b0000_0100,
-                    RamOption::Invert => 0b0000_1000,
+                    RamOption::Bypass => 0b0100_0000,
+                    RamOption::Invert => 0b1000_0000,
                 };
                 let source = match source_option {
                     SourceOption::SourceFromS0ToS175 => 0b0000_0000,
@@ -309,44 +682,64 @@ impl Command {
                 };
                 pack!(buf, 0x21, [black | red, source])
             }
-            UpdateDisplayOption2(update_sequence_option) => {
-                let option = match update_sequence_option {
-                    DisplayUpdateSequenceOption::EnableClockSignal => 0x80_u8,
-                    DisplayUpdateSequenceOption::DisableClockSignal => 0x01_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_EnableAnalog => 0xC0_u8,
-                    DisplayUpdateSequenceOption::DisableAnalog_DisableClockSignal => 0x03_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_LoadLutMode1_DisableClockSignal => 0x91_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_LoadLutMode2_DisableClockSignal => 0x99_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_LoadTemp_LoadLutMode1_DisableClockSignal => 0xB1_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_LoadTemp_LoadLutMode2_DisableClockSignal => 0xB9_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_EnableAnalog_DisplayMode1_DisableAnalog_DisableOscillator => 0xC7_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_EnableAnalog_DisplayMode2_DisableAnalog_DisableOscillator => 0xCF_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_LoadTemp_EnableAnalog_DisplayMode1_DisableAnalog_DisableOscillator => 0xF7_u8,
-                    DisplayUpdateSequenceOption::EnableClockSignal_LoadTemp_EnableAnalog_DisplayMode2_DisableAnalog_DisableOscillator => 0xFF_u8,
-                };
-                pack!(buf, 0x22, [option])
+            UpdateDisplayOption2(sequence) => {
+                debug_assert!(sequence.is_valid());
+                pack!(buf, 0x22, [sequence.bits()])
+            }
+            WriteRam(plane) => match plane {
+                RamPlane::BlackWhite => pack!(buf, 0x24, []),
+                RamPlane::Red => pack!(buf, 0x26, []),
+            },
+            ReadRam => pack!(buf, 0x27, []),
+            EnterVCOMSensing => pack!(buf, 0x28, []),
+            VCOMSenseDuration(duration) => {
+                debug_assert!(duration <= MAX_VCOM_SENSE_DURATION);
+                pack!(buf, 0x29, [duration])
             }
-            // EnterVCOMSensing => {
-            // }
-            // VCOMSenseDuration(u8) => {
-            // }
+            #[cfg(feature = "otp-program")]
+            ProgramVCOMIntoOTP => pack!(buf, 0x2A, []),
             WriteVCOM(value) => pack!(buf, 0x2C, [value]),
+            ReadDisplayOption => pack!(buf, 0x2D, []),
+            ReadUserId => pack!(buf, 0x2E, []),
+            ReadStatusBit => pack!(buf, 0x2F, []),
+            CalculateCRC => pack!(buf, 0x34, []),
+            ReadCRC => pack!(buf, 0x35, []),
+            #[cfg(feature = "otp-program")]
+            ProgramOTP => pack!(buf, 0x36, []),
+            #[cfg(feature = "otp-program")]
+            OTPProgramMode(mode) => {
+                let mode = match mode {
+                    self::OtpProgramMode::Normal => 0b00,
+                    self::OtpProgramMode::InternalVoltage => 0b11,
+                };
+                pack!(buf, 0x39, [mode])
+            }
             DummyLinePeriod(period) => {
                 debug_assert!(Contains::contains(&(0..=MAX_DUMMY_LINE_PERIOD), period));
                 pack!(buf, 0x3A, [period])
             }
-            GateLineWidth(tgate) => pack!(buf, 0x3B, [tgate]),
-            BorderWaveform(border_waveform) => pack!(buf, 0x3C, [border_waveform]),
+            GateLineWidth(tgate) => {
+                debug_assert!(tgate <= MAX_GATE_LINE_WIDTH);
+                pack!(buf, 0x3B, [tgate])
+            }
+            BorderWaveform(border_waveform) => {
+                pack!(buf, 0x3C, [border_waveform.register_value()])
+            }
+            ReadRamOption(plane) => {
+                let plane = match plane {
+                    RamPlane::BlackWhite => 0x00,
+                    RamPlane::Red => 0x01,
+                };
+                pack!(buf, 0x41, [plane])
+            }
             StartEndXPosition(start, end) => pack!(buf, 0x44, [start, end]),
             StartEndYPosition(start, end) => {
                 let [start_upper, start_lower] = start.to_be_bytes();
                 let [end_upper, end_lower] = end.to_be_bytes();
                 pack!(buf, 0x45, [start_lower, start_upper, end_lower, end_upper])
             }
-            // AutoWriteRedPattern(u8) => {
-            // }
-            // AutoWriteBlackPattern(u8) => {
-            // }
+            AutoWriteRedPattern(pattern) => pack!(buf, 0x46, [pattern]),
+            AutoWriteBlackPattern(pattern) => pack!(buf, 0x47, [pattern]),
             XAddress(address) => pack!(buf, 0x4E, [address]),
             YAddress(address) => {
                 let [upper, lower] = address.to_be_bytes();
@@ -354,38 +747,78 @@ impl Command {
             }
             AnalogBlockControl(value) => pack!(buf, 0x74, [value]),
             DigitalBlockControl(value) => pack!(buf, 0x7E, [value]),
-            _ => unimplemented!(),
         };
 
-        interface.send_command(command).await?;
-        if data.is_empty() {
-            Ok(())
-        } else {
-            interface.send_data(data).await
+        match self {
+            // Not retried, as `BufCommand` RAM writes, since the data follows separately
+            WriteRam(_) => interface.send_command_with_data(command, data).await,
+            _ => send_with_retries(interface, command, data).await,
         }
     }
+
+    /// Execute a read command, such as `ReadStatusBit`, and read its response into `data`.
+    ///
+    /// This requires the SPI device to be able to read from the controller, see
+    /// `DisplayInterface::read_data`.
+    pub async fn read<I: DisplayInterface>(
+        &self,
+        interface: &mut I,
+        data: &mut [u8],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.execute(interface).await?;
+        interface.read_data(data).await
+    }
 }
 
 impl<'buf> BufCommand<'buf> {
     /// Execute the command, transmitting the associated buffer as well.
-    pub async fn execute<I: DisplayInterface>(&self, interface: &mut I) -> Result<(), I::Error> {
+    ///
+    /// RAM writes are not retried, as a failed write may have advanced the RAM address counter.
+    /// `Display` retries them along with the commands that set the address.
+    pub async fn execute<I: DisplayInterface>(
+        &self,
+        interface: &mut I,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
         use self::BufCommand::*;
 
         let (command, data) = match self {
             WriteBlackData(buffer) => (0x24, buffer),
             WriteRedData(buffer) => (0x26, buffer),
             WriteLUT(buffer) => (0x32, buffer),
+            #[cfg(feature = "otp-program")]
+            WriteDisplayOption(buffer) => (0x37, buffer),
+            #[cfg(feature = "otp-program")]
+            WriteUserId(buffer) => (0x38, buffer),
         };
 
-        interface.send_command(command).await?;
-        if data.is_empty() {
-            Ok(())
-        } else {
-            interface.send_data(data).await
+        match self {
+            WriteBlackData(_) | WriteRedData(_) => {
+                interface.send_command_with_data(command, data).await
+            }
+            _ => send_with_retries(interface, command, data).await,
         }
     }
 }
 
+/// Send a command with its data, resending both for as long as the interface's retry policy
+/// allows.
+async fn send_with_retries<I: DisplayInterface>(
+    interface: &mut I,
+    command: u8,
+    data: &[u8],
+) -> Result<(), Ssd1680Error<I::Error>> {
+    let mut attempt = 0;
+    loop {
+        let result = interface.send_command_with_data(command, data).await;
+        if !matches!(result, Err(Ssd1680Error::Interface(_)))
+            || !interface.retry_backoff(attempt).await
+        {
+            return result;
+        }
+        attempt = attempt.saturating_add(1);
+    }
+}
+
 impl<C> Contains<C> for core::ops::Range<C>
 where
     C: Copy + PartialOrd,
@@ -438,30 +871,41 @@ mod tests {
         ///
         /// Prefer calling `execute` on a [Command](../command/enum.Command.html) over calling this
         /// directly.
-        async fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<Self::Error>> {
             self.write(command);
             Ok(())
         }
 
         /// Send data for a command.
-        async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<Self::Error>> {
             for byte in data {
                 self.write(*byte)
             }
             Ok(())
         }
 
+        /// Read data returned by the previously sent command.
+        async fn read_data(&mut self, data: &mut [u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+            data.fill(0);
+            Ok(())
+        }
+
         /// Reset the controller.
-        async fn reset(&mut self) {
+        async fn reset(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
             self.data = [0; 256];
             self.offset = 0;
+            Ok(())
         }
 
         /// Wait for the controller to indicate it is not busy.
-        async fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
             // nop
             Ok(())
         }
+
+        fn is_busy(&mut self) -> Result<bool, Ssd1680Error<Self::Error>> {
+            Ok(false)
+        }
     }
 
     #[futures_test::test]
@@ -478,4 +922,118 @@ mod tests {
             &[0x01, lower, upper, scanning_seq_and_dir]
         );
     }
+
+    #[futures_test::test]
+    async fn test_update_sequence_execute() {
+        let mut interface = MockInterface::new();
+        let sequence = UpdateSequence::empty()
+            .enable_clock()
+            .load_temperature()
+            .load_lut()
+            .mode_2()
+            .disable_clock();
+
+        Command::UpdateDisplayOption2(sequence)
+            .execute(&mut interface)
+            .await
+            .unwrap();
+        assert_eq!(interface.data(), &[0x22, 0xB9]);
+    }
+
+    #[futures_test::test]
+    async fn test_ram_write_and_read_execute() {
+        let mut interface = MockInterface::new();
+
+        Command::WriteRam(RamPlane::Red)
+            .execute(&mut interface)
+            .await
+            .unwrap();
+        interface.send_data(&[0xAA]).await.unwrap();
+        let mut status = [0xFF];
+        Command::ReadStatusBit
+            .read(&mut interface, &mut status)
+            .await
+            .unwrap();
+        assert_eq!(interface.data(), &[0x26, 0xAA, 0x2F]);
+        assert_eq!(status, [0x00]);
+    }
+
+    #[test]
+    fn test_update_sequence_validation() {
+        assert!(UpdateSequence::DISPLAY_MODE_1.is_valid());
+        assert!(UpdateSequence::LOAD_AND_DISPLAY_MODE_2.is_valid());
+        assert!(UpdateSequence::POWER_OFF.is_valid());
+        assert_eq!(
+            UpdateSequence::from_bits(0x83),
+            Some(UpdateSequence::ENABLE_CLOCK | UpdateSequence::POWER_OFF)
+        );
+        // Display without the analog block enabled
+        assert_eq!(UpdateSequence::from_bits(0x84), None);
+        // Loading the LUT without the clock enabled
+        assert_eq!(UpdateSequence::from_bits(0x10), None);
+        // Mode 2 on its own
+        assert_eq!(UpdateSequence::from_bits(0x88), None);
+        assert_eq!(UpdateSequence::from_bits(0x00), None);
+    }
+
+    #[test]
+    fn test_border_waveform_register_value() {
+        assert_eq!(BorderWaveform::White.register_value(), 0x05);
+        assert_eq!(BorderWaveform::Vcom.register_value(), 0x80);
+        assert_eq!(BorderWaveform::HiZ.register_value(), 0xC0);
+        assert_eq!(
+            BorderWaveform::FixedLevel(BorderLevel::Vsl).register_value(),
+            0x60
+        );
+        assert_eq!(BorderWaveform::FollowLut(3).register_value(), 0x07);
+    }
+
+    #[futures_test::test]
+    async fn test_update_display_option1_execute() {
+        let mut interface = MockInterface::new();
+
+        Command::UpdateDisplayOption1(
+            RamOption::Invert,
+            RamOption::Bypass,
+            SourceOption::SourceFromS0ToS175,
+        )
+        .execute(&mut interface)
+        .await
+        .unwrap();
+        assert_eq!(interface.data(), &[0x21, 0x48, 0x00]);
+    }
+
+    #[futures_test::test]
+    async fn test_vcom_sense_duration_execute() {
+        let mut interface = MockInterface::new();
+
+        Command::VCOMSenseDuration(9)
+            .execute(&mut interface)
+            .await
+            .unwrap();
+        assert_eq!(interface.data(), &[0x29, 0x09]);
+    }
+
+    #[futures_test::test]
+    async fn test_read_ram_option_execute() {
+        let mut interface = MockInterface::new();
+
+        Command::ReadRamOption(RamPlane::Red)
+            .execute(&mut interface)
+            .await
+            .unwrap();
+        Command::ReadRam.execute(&mut interface).await.unwrap();
+        assert_eq!(interface.data(), &[0x41, 0x01, 0x27]);
+    }
+
+    #[futures_test::test]
+    async fn test_send_data_iter_chunks() {
+        let mut interface = MockInterface::new();
+        interface
+            .send_data_iter((0..=199).map(|i| i as u8))
+            .await
+            .unwrap();
+        let expected: [u8; 200] = core::array::from_fn(|i| i as u8);
+        assert_eq!(interface.data(), &expected);
+    }
 }
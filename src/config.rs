@@ -1,6 +1,9 @@
 use crate::{
-    command::{BufCommand, Command, DataEntryMode, IncrementAxis},
-    display::{self, Dimensions, Rotation},
+    command::{
+        BorderWaveform, DeepSleepMode, IncrementAxis, RamOption, SourceOption, TemperatureSensor,
+        UpdateSequence, MAX_DUMMY_LINE_PERIOD, MAX_GATE_LINE_WIDTH,
+    },
+    display::{self, BitOrder, Dimensions, FrameLayout, Rotation},
 };
 
 /// Builder for constructing a display Config.
@@ -22,113 +25,620 @@ use crate::{
 ///     .build()
 ///     .expect("invalid configuration");
 /// ```
+///
+/// All methods are `const`, so a `Config` can also be built at compile time, e.g. to keep it in a
+/// `static` rather than in stack-constrained initialisation code. An invalid configuration then
+/// fails to compile:
+///
+/// ```
+/// use ssd1680::{config::Config, Builder, Dimensions};
+///
+/// static CONFIG: Config<'static> = match Builder::new()
+///     .dimensions(Dimensions {
+///         rows: 250,
+///         cols: 128,
+///     })
+///     .vcom_voltage(-1600)
+///     .build()
+/// {
+///     Ok(config) => config,
+///     Err(_) => panic!("invalid configuration"),
+/// };
+/// ```
 pub struct Builder<'a> {
-    dummy_line_period: Command,
-    gate_line_width: Command,
-    write_vcom: Command,
-    write_lut: Option<BufCommand<'a>>,
-    data_entry_mode: Command,
+    dummy_line_period: Option<u8>,
+    gate_line_width: Option<u8>,
+    vcom: Option<u8>,
+    vcom_mv: Option<i16>,
+    gate_voltage_mv: Option<u16>,
+    source_voltages_mv: Option<(u16, u16, i16)>,
+    soft_start: Option<SoftStart>,
+    block_control: bool,
+    waveform_source: WaveformSource<'a>,
+    temperature_luts: &'a [TemperatureLut<'a>],
+    increment_axis: Option<IncrementAxis>,
     dimensions: Option<Dimensions>,
     rotation: Rotation,
+    drawing_offset: (i16, i16),
+    frame_layout: Option<FrameLayout>,
+    bit_order: BitOrder,
+    full_refresh_interval: Option<u16>,
+    aging_compensation: &'a [AgingStep],
+    partial_update_reset: bool,
+    border: BorderWaveform,
+    gate_scan_start: u16,
+    black_ram_option: RamOption,
+    red_ram_option: RamOption,
+    source_option: Option<SourceOption>,
+    init_profile: InitProfile,
+    full_refresh_sequence: UpdateSequence,
+    fast_refresh_sequence: UpdateSequence,
+    partial_refresh_sequence: UpdateSequence,
+    auto_deep_sleep: Option<DeepSleepMode>,
+    temperature_sensor: TemperatureSensor,
+    temperature_policy: Option<&'a (dyn TemperaturePolicy + Sync)>,
+}
+
+/// The waveform initialisation performed by `Display::reset`.
+///
+/// See `Builder::init_profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InitProfile {
+    /// Load the waveform for the temperature measured by the internal sensor.
+    Standard,
+    /// Load the waveform for a high temperature (100 °C), as in the GoodDisplay example code.
+    /// Refreshes are faster, but image quality is degraded on some panels.
+    Fast,
+    /// Load the waveform for the given temperature (in °C).
+    Custom(i8),
+}
+
+impl InitProfile {
+    /// Returns the temperature to load the waveform for, or `None` to measure it.
+    pub(crate) fn temperature(self) -> Option<i8> {
+        match self {
+            InitProfile::Standard => None,
+            InitProfile::Fast => Some(100),
+            InitProfile::Custom(temperature) => Some(temperature),
+        }
+    }
+}
+
+/// Adjustments to a refresh made by a `TemperaturePolicy`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshAdjustment {
+    /// Perform a full refresh, even if a partial refresh was requested.
+    pub force_full_refresh: bool,
+    /// Load the waveform for this temperature (in °C) instead of the measured one. Ignored with
+    /// `WaveformSource::Custom`.
+    pub waveform_temperature: Option<i8>,
+}
+
+/// A policy consulted before each refresh to adapt it to the panel temperature.
+///
+/// Panel vendors give guidance such as avoiding partial refreshes in the cold, or using a
+/// shorter waveform when hot. Implementing this trait expresses that guidance in one place, see
+/// `Builder::temperature_policy`, or `Display::set_temperature_policy` for a policy with interior
+/// mutability such as a `Cell`.
+///
+/// ### Example
+///
+/// ```
+/// use ssd1680::config::{RefreshAdjustment, TemperaturePolicy};
+///
+/// struct Outdoor;
+///
+/// impl TemperaturePolicy for Outdoor {
+///     fn adjust(&self, temperature: i16, _partial: bool) -> RefreshAdjustment {
+///         RefreshAdjustment {
+///             force_full_refresh: temperature < -5,
+///             waveform_temperature: (temperature > 40).then_some(50),
+///         }
+///     }
+/// }
+/// ```
+pub trait TemperaturePolicy {
+    /// Returns the adjustments for a refresh at `temperature` (in °C). `partial` is true if a
+    /// partial refresh was requested.
+    ///
+    /// The default makes no adjustments.
+    fn adjust(&self, temperature: i16, partial: bool) -> RefreshAdjustment {
+        let _ = (temperature, partial);
+        RefreshAdjustment::default()
+    }
+}
+
+/// Where the waveform used for refreshes comes from.
+///
+/// See `Builder::waveform_source`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WaveformSource<'a> {
+    /// Load the waveform programmed into OTP, selected by the panel temperature.
+    #[default]
+    Otp,
+    /// Write this lookup table (`LUT_SIZE` bytes) with command 0x32.
+    Custom(&'a [u8]),
+}
+
+/// A lookup table to use from a given panel temperature upwards.
+///
+/// See `Builder::temperature_luts`.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureLut<'a> {
+    /// The lowest temperature (in °C) this LUT is used for.
+    pub min_temperature: i8,
+    /// The lookup table (`LUT_SIZE` bytes), written with command 0x32.
+    pub lut: &'a [u8],
+}
+
+/// Source driving voltage register values (command 0x04).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SourceVoltages {
+    /// VSH1 register value.
+    pub vsh1: u8,
+    /// VSH2 register value.
+    pub vsh2: u8,
+    /// VSL register value.
+    pub vsl: u8,
+}
+
+impl SourceVoltages {
+    /// Convert source driving voltages in millivolts to register values.
+    ///
+    /// VSH1 and VSH2 must be between 2.4 V and 8.8 V in steps of 0.1 V, or between 9 V and 17 V
+    /// in steps of 0.2 V. VSL must be between -5 V and -17 V in steps of 0.5 V. Returns `None`
+    /// for other values.
+    ///
+    /// ```
+    /// use ssd1680::config::SourceVoltages;
+    ///
+    /// let voltages = SourceVoltages::from_millivolts(15_000, 5_000, -15_000).unwrap();
+    /// assert_eq!(voltages, SourceVoltages { vsh1: 0x41, vsh2: 0xA8, vsl: 0x32 });
+    /// assert_eq!(SourceVoltages::from_millivolts(15_100, 5_000, -15_000), None);
+    /// ```
+    pub const fn from_millivolts(vsh1_mv: u16, vsh2_mv: u16, vsl_mv: i16) -> Option<Self> {
+        match (
+            vsh_register(vsh1_mv),
+            vsh_register(vsh2_mv),
+            vsl_register(vsl_mv),
+        ) {
+            (Some(vsh1), Some(vsh2), Some(vsl)) => Some(SourceVoltages { vsh1, vsh2, vsl }),
+            _ => None,
+        }
+    }
+}
+
+/// One phase of the booster soft start (command 0x0C).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoftStartPhase {
+    /// Driving strength, from 1 (weakest) to 8.
+    pub strength: u8,
+    /// Minimum off time of GDR, register value from 0x4 to 0xF. Larger values are longer.
+    pub min_off_time: u8,
+    /// Duration of the phase in milliseconds: 10, 20, 30 or 40.
+    pub duration_ms: u8,
+}
+
+impl SoftStartPhase {
+    /// Returns the phase setting register value and the duration code, or `None` if invalid.
+    const fn register_values(self) -> Option<(u8, u8)> {
+        if self.strength < 1
+            || self.strength > 8
+            || self.min_off_time < 0x4
+            || self.min_off_time > 0xF
+        {
+            return None;
+        }
+        let duration = match self.duration_ms {
+            10 => 0b00,
+            20 => 0b01,
+            30 => 0b10,
+            40 => 0b11,
+            _ => return None,
+        };
+        // The field holds the strength minus one
+        Some((
+            0x80 | (self.strength - 1) << 4 | self.min_off_time,
+            duration,
+        ))
+    }
+}
+
+/// Booster soft start settings, see `Builder::soft_start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoftStart {
+    /// The first phase
+    pub phase1: SoftStartPhase,
+    /// The second phase
+    pub phase2: SoftStartPhase,
+    /// The third phase
+    pub phase3: SoftStartPhase,
+}
+
+impl SoftStart {
+    /// Returns the four parameters of command 0x0C, or `None` if a phase is invalid.
+    const fn register_values(self) -> Option<[u8; 4]> {
+        match (
+            self.phase1.register_values(),
+            self.phase2.register_values(),
+            self.phase3.register_values(),
+        ) {
+            (Some((phase1, duration1)), Some((phase2, duration2)), Some((phase3, duration3))) => {
+                Some([
+                    phase1,
+                    phase2,
+                    phase3,
+                    duration3 << 4 | duration2 << 2 | duration1,
+                ])
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the register value for `value` on a linear scale starting at `code` for `min`, or
+/// `None` if it is out of range or not on a step.
+const fn scale_register(
+    value: u16,
+    min: u16,
+    max: u16,
+    step: u16,
+    code: u8,
+    code_step: u8,
+) -> Option<u8> {
+    if value < min || value > max || !(value - min).is_multiple_of(step) {
+        return None;
+    }
+    let steps = (value - min) / step;
+    if steps > u8::MAX as u16 {
+        return None;
+    }
+    Some(code + steps as u8 * code_step)
+}
+
+/// Returns the VGH register value (command 0x03) for `mv`.
+const fn gate_voltage_register(mv: u16) -> Option<u8> {
+    scale_register(mv, 10_000, 20_000, 500, 0x03, 1)
+}
+
+/// VCOM register values (command 0x2C) from -0.2 V to -3 V in steps of 0.1 V, as tabulated in
+/// the datasheet. The steps between codes are uneven, so they can't be calculated.
+const VCOM_REGISTERS: [u8; 29] = [
+    0x08, 0x0B, 0x10, 0x14, 0x17, 0x1B, 0x1F, 0x23, 0x28, 0x2C, 0x2F, 0x32, 0x37, 0x3C, 0x40, 0x44,
+    0x48, 0x4B, 0x50, 0x55, 0x5A, 0x5F, 0x64, 0x69, 0x6E, 0x73, 0x78, 0x7C, 0x82,
+];
+
+/// Returns the VCOM register value (command 0x2C) for `mv`.
+const fn vcom_register(mv: i16) -> Option<u8> {
+    let mv = match negate(mv) {
+        Some(mv) => mv,
+        None => return None,
+    };
+    if mv < 200 || !mv.is_multiple_of(100) {
+        return None;
+    }
+    let index = (mv / 100 - 2) as usize;
+    if index < VCOM_REGISTERS.len() {
+        Some(VCOM_REGISTERS[index])
+    } else {
+        None
+    }
 }
 
-/// Error returned if Builder configuration is invalid.
+/// Returns the VSH1/VSH2 register value (command 0x04) for `mv`.
+const fn vsh_register(mv: u16) -> Option<u8> {
+    match scale_register(mv, 9_000, 17_000, 200, 0x23, 1) {
+        Some(register) => Some(register),
+        None => scale_register(mv, 2_400, 8_800, 100, 0x8E, 1),
+    }
+}
+
+/// Returns the VSL register value (command 0x04) for `mv`.
+const fn vsl_register(mv: i16) -> Option<u8> {
+    match negate(mv) {
+        Some(mv) => scale_register(mv, 5_000, 17_000, 500, 0x0A, 2),
+        None => None,
+    }
+}
+
+/// Returns true if `sequence` can be used for a refresh.
+const fn refreshes_display(sequence: UpdateSequence) -> bool {
+    sequence.is_valid() && sequence.contains(UpdateSequence::DISPLAY)
+}
+
+/// Returns `-mv`, or `None` if `mv` is positive.
+const fn negate(mv: i16) -> Option<u16> {
+    if mv > 0 {
+        None
+    } else {
+        Some(mv.unsigned_abs())
+    }
+}
+
+/// Source driving voltages to use from a given amount of panel usage onwards.
 ///
-/// Currently only returned if a configuration is built without dimensions.
-#[derive(Debug)]
-pub struct BuilderError {}
+/// See `Builder::aging_compensation`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AgingStep {
+    /// The lowest usage these voltages are used for.
+    pub min_usage: u32,
+    /// The source driving voltages.
+    pub voltages: SourceVoltages,
+}
+
+/// The length of a waveform lookup table, written with command 0x32.
+pub const LUT_SIZE: usize = 153;
+
+/// Error returned by `Builder::build` if the configuration is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BuilderError {
+    /// No dimensions were set.
+    MissingDimensions,
+    /// The rows exceed `MAX_GATE_OUTPUTS`.
+    RowsTooLarge,
+    /// The columns exceed `MAX_SOURCE_OUTPUTS`.
+    ColsTooLarge,
+    /// The columns are not a multiple of 8.
+    ColsNotByteAligned,
+    /// A lookup table is not `LUT_SIZE` bytes long.
+    LutWrongLength,
+    /// The temperature lookup tables are not sorted by ascending `min_temperature`.
+    TemperatureLutsUnsorted,
+    /// VCOM is outside the range supported by the controller.
+    VcomOutOfRange,
+    /// The gate driving voltage is outside the range supported by the controller.
+    GateVoltageOutOfRange,
+    /// A source driving voltage is outside the range supported by the controller.
+    SourceVoltageOutOfRange,
+    /// The dummy line period exceeds `MAX_DUMMY_LINE_PERIOD`.
+    DummyLinePeriodTooLarge,
+    /// The gate line width exceeds `MAX_GATE_LINE_WIDTH`.
+    GateLineWidthTooLarge,
+    /// A soft start setting is outside the range supported by the controller.
+    SoftStartOutOfRange,
+    /// A refresh update sequence is not valid or does not drive the display.
+    InvalidUpdateSequence,
+    /// The increment axis of the data entry mode doesn't match the frame layout.
+    ConflictingFrameLayout,
+}
 
 /// Display configuration.
 ///
 /// Passed to Display::new. Use `Builder` to construct a `Config`.
+#[derive(Clone)]
 pub struct Config<'a> {
-    pub(crate) _dummy_line_period: Command,
-    pub(crate) _gate_line_width: Command,
-    pub(crate) _write_vcom: Command,
-    pub(crate) _write_lut: Option<BufCommand<'a>>,
-    pub(crate) _data_entry_mode: Command,
+    pub(crate) dummy_line_period: Option<u8>,
+    pub(crate) gate_line_width: Option<u8>,
+    pub(crate) vcom: Option<u8>,
+    pub(crate) gate_voltage: Option<u8>,
+    pub(crate) source_voltages: Option<SourceVoltages>,
+    pub(crate) booster: Option<[u8; 4]>,
+    pub(crate) block_control: bool,
+    pub(crate) waveform_source: WaveformSource<'a>,
+    pub(crate) temperature_luts: &'a [TemperatureLut<'a>],
     pub(crate) dimensions: Dimensions,
     pub(crate) rotation: Rotation,
+    pub(crate) drawing_offset: (i16, i16),
+    pub(crate) frame_layout: FrameLayout,
+    pub(crate) bit_order: BitOrder,
+    pub(crate) full_refresh_interval: Option<u16>,
+    pub(crate) aging_compensation: &'a [AgingStep],
+    pub(crate) partial_update_reset: bool,
+    pub(crate) border: BorderWaveform,
+    pub(crate) gate_scan_start: u16,
+    pub(crate) black_ram_option: RamOption,
+    pub(crate) red_ram_option: RamOption,
+    pub(crate) source_option: SourceOption,
+    pub(crate) init_profile: InitProfile,
+    pub(crate) full_refresh_sequence: UpdateSequence,
+    pub(crate) fast_refresh_sequence: UpdateSequence,
+    pub(crate) partial_refresh_sequence: UpdateSequence,
+    pub(crate) auto_deep_sleep: Option<DeepSleepMode>,
+    pub(crate) temperature_sensor: TemperatureSensor,
+    pub(crate) temperature_policy: Option<&'a (dyn TemperaturePolicy + Sync)>,
 }
 
 impl<'a> Default for Builder<'a> {
     fn default() -> Self {
-        Builder {
-            dummy_line_period: Command::DummyLinePeriod(0x07),
-            gate_line_width: Command::GateLineWidth(0x04),
-            write_vcom: Command::WriteVCOM(0x3C),
-            write_lut: None,
-            data_entry_mode: Command::DataEntryMode(
-                DataEntryMode::IncrementYIncrementX,
-                IncrementAxis::Horizontal,
-            ),
-            dimensions: None,
-            rotation: Rotation::default(),
-        }
+        Self::new()
     }
 }
 
 impl<'a> Builder<'a> {
     /// Create a new Builder.
-    pub fn new() -> Self {
-        Self::default()
+    pub const fn new() -> Self {
+        Builder {
+            dummy_line_period: None,
+            gate_line_width: None,
+            vcom: None,
+            vcom_mv: None,
+            gate_voltage_mv: None,
+            source_voltages_mv: None,
+            soft_start: None,
+            block_control: false,
+            waveform_source: WaveformSource::Otp,
+            temperature_luts: &[],
+            increment_axis: None,
+            dimensions: None,
+            rotation: Rotation::Rotate0,
+            drawing_offset: (0, 0),
+            frame_layout: None,
+            bit_order: BitOrder::MsbFirst,
+            full_refresh_interval: None,
+            aging_compensation: &[],
+            partial_update_reset: true,
+            border: BorderWaveform::White,
+            gate_scan_start: 0,
+            black_ram_option: RamOption::Normal,
+            red_ram_option: RamOption::Normal,
+            source_option: None,
+            init_profile: InitProfile::Fast,
+            full_refresh_sequence: UpdateSequence::DISPLAY_MODE_1,
+            fast_refresh_sequence: UpdateSequence::DISPLAY_MODE_1.load_lut(),
+            partial_refresh_sequence: UpdateSequence::DISPLAY_MODE_2,
+            auto_deep_sleep: None,
+            temperature_sensor: TemperatureSensor::Internal,
+            temperature_policy: None,
+        }
     }
 
     /// Set the number of dummy line period in terms of gate line width (TGate).
     ///
-    /// Defaults to 0x07. Corresponds to command 0x3A.
-    pub fn dummy_line_period(self, dummy_line_period: u8) -> Self {
+    /// Together with the gate line width this sets the frame rate. At most 127, otherwise `build`
+    /// fails. Corresponds to command 0x3A, sent during initialisation. If not set the power on
+    /// value is used.
+    pub const fn dummy_line_period(self, dummy_line_period: u8) -> Self {
         Self {
-            dummy_line_period: Command::DummyLinePeriod(dummy_line_period),
+            dummy_line_period: Some(dummy_line_period),
             ..self
         }
     }
 
     /// Set the gate line width (TGate).
     ///
-    /// Defaults to 0x04. Corresponds to command 0x3B.
-    pub fn gate_line_width(self, gate_line_width: u8) -> Self {
+    /// At most 0x0F, otherwise `build` fails. Corresponds to command 0x3B, sent during
+    /// initialisation. If not set the power on value is used.
+    pub const fn gate_line_width(self, gate_line_width: u8) -> Self {
         Self {
-            gate_line_width: Command::GateLineWidth(gate_line_width),
+            gate_line_width: Some(gate_line_width),
             ..self
         }
     }
 
     /// Set VCOM register value.
     ///
-    /// Defaults to 0x3C. Corresponds to command 0x2C.
-    pub fn vcom(self, value: u8) -> Self {
+    /// Corresponds to command 0x2C. If not set the VCOM register is left at the value loaded from
+    /// OTP. The value returned by `Display::calibrate_vcom` can be persisted and restored here.
+    pub const fn vcom(self, value: u8) -> Self {
         Self {
-            write_vcom: Command::WriteVCOM(value),
+            vcom: Some(value),
+            vcom_mv: None,
             ..self
         }
     }
 
-    /// Set lookup table (70 bytes).
+    /// Set VCOM in millivolts, e.g. -1600 for the "VCOM -1.6V" printed on many panel flexes.
     ///
-    /// **Note:** The supplied slice must be exactly 70 bytes long.
+    /// VCOM must be between -3 V and -0.2 V in steps of 0.1 V, otherwise `build` fails. This sets
+    /// the same register as `vcom`, and the last of the two to be called takes effect.
+    pub const fn vcom_voltage(self, vcom_mv: i16) -> Self {
+        Self {
+            vcom: None,
+            vcom_mv: Some(vcom_mv),
+            ..self
+        }
+    }
+
+    /// Set the gate driving voltage VGH, in millivolts.
+    ///
+    /// VGH must be between 10 V and 20 V in steps of 0.5 V, otherwise `build` fails. Corresponds
+    /// to command 0x03. If not set the power on value of 20 V is used.
+    pub const fn gate_driving_voltage(self, vgh_mv: u16) -> Self {
+        Self {
+            gate_voltage_mv: Some(vgh_mv),
+            ..self
+        }
+    }
+
+    /// Set the source driving voltages, in millivolts.
+    ///
+    /// See `SourceVoltages::from_millivolts` for the supported values, `build` fails for others.
+    /// Corresponds to command 0x04. If not set the power on values of 15 V, 5 V and -15 V are
+    /// used. A table set with `aging_compensation` takes precedence.
+    pub const fn source_driving_voltage(self, vsh1_mv: u16, vsh2_mv: u16, vsl_mv: i16) -> Self {
+        Self {
+            source_voltages_mv: Some((vsh1_mv, vsh2_mv, vsl_mv)),
+            ..self
+        }
+    }
+
+    /// Set the booster soft start phases.
+    ///
+    /// Several vendor initialisation sequences require specific values. Each phase must be valid
+    /// as described on `SoftStartPhase`, otherwise `build` fails. Corresponds to command 0x0C. If
+    /// not set the power on values are used.
+    pub const fn soft_start(self, soft_start: SoftStart) -> Self {
+        Self {
+            soft_start: Some(soft_start),
+            ..self
+        }
+    }
+
+    /// Set whether the analog and digital block control registers are written during
+    /// initialisation.
+    ///
+    /// Some panels' reference code writes the values from the data sheet (0x54 with command 0x74
+    /// and 0x3B with command 0x7E) before driver output control. Defaults to false.
+    pub const fn block_control(self, block_control: bool) -> Self {
+        Self {
+            block_control,
+            ..self
+        }
+    }
+
+    /// Set lookup table (153 bytes).
+    ///
+    /// **Note:** The supplied slice must be exactly `LUT_SIZE` bytes long, otherwise `build`
+    /// returns `BuilderError::LutWrongLength`.
     ///
     /// There is no default for the lookup table. Corresponds to command 0x32. If not supplied then
-    /// the default in the controller is used. Apparently the display manufacturer will normally
-    /// supply the LUT values for a particular display batch.
-    pub fn lut(self, lut: &'a [u8]) -> Self {
+    /// the waveform in OTP is used. Apparently the display manufacturer will normally supply the
+    /// LUT values for a particular display batch. Equivalent to
+    /// `waveform_source(WaveformSource::Custom(lut))`.
+    pub const fn lut(self, lut: &'a [u8]) -> Self {
+        self.waveform_source(WaveformSource::Custom(lut))
+    }
+
+    /// Set where the waveform used for refreshes comes from.
+    ///
+    /// Defaults to `WaveformSource::Otp`. With `WaveformSource::Custom`, the lookup table is
+    /// written with command 0x32 at initialisation instead of loading one from OTP, and the steps
+    /// loading the temperature and LUT from OTP are removed from the refresh update sequences, so
+    /// that they don't replace it. The `InitProfile` then has no effect.
+    pub const fn waveform_source(self, waveform_source: WaveformSource<'a>) -> Self {
+        Self {
+            waveform_source,
+            ..self
+        }
+    }
+
+    /// Set lookup tables to select from by panel temperature.
+    ///
+    /// Entries must be sorted by ascending `min_temperature`. When set, the controller's
+    /// temperature sensor is read before each refresh and the entry with the highest
+    /// `min_temperature` not above the measured temperature is written to the LUT register. The
+    /// first entry is used for temperatures below all entries. Waveforms tuned for room
+    /// temperature tend to ghost badly near 0 °C, so this allows a slower table to be used in the
+    /// cold.
+    ///
+    /// There is no default, the LUT loaded from OTP is used as is. Reading the temperature
+    /// requires the interface to support `read_data`.
+    pub const fn temperature_luts(self, luts: &'a [TemperatureLut<'a>]) -> Self {
         Self {
-            write_lut: Some(BufCommand::WriteLUT(lut)),
+            temperature_luts: luts,
             ..self
         }
     }
 
-    /// Define data entry sequence.
+    /// Set the axis the RAM address counter advances along after each byte.
     ///
-    /// Defaults to DataEntryMode::IncrementAxis, IncrementAxis::Horizontal. Corresponds to command
-    /// 0x11.
-    pub fn data_entry_mode(
-        self,
-        data_entry_mode: DataEntryMode,
-        increment_axis: IncrementAxis,
-    ) -> Self {
+    /// Defaults to `IncrementAxis::Horizontal`. Corresponds to command 0x11, whose addresses
+    /// always increment, as frame buffers are packed for. The increment axis selects the frame
+    /// layout, `IncrementAxis::Vertical` being equivalent to
+    /// `frame_layout(FrameLayout::ColumnMajor)`, and `build` fails if a different `frame_layout`
+    /// is also set.
+    pub const fn data_entry_mode(self, increment_axis: IncrementAxis) -> Self {
         Self {
-            data_entry_mode: Command::DataEntryMode(data_entry_mode, increment_axis),
+            increment_axis: Some(increment_axis),
             ..self
         }
     }
@@ -136,47 +646,531 @@ impl<'a> Builder<'a> {
     /// Set the display dimensions.
     ///
     /// There is no default for this setting. The dimensions must be set for the builder to
-    /// successfully build a Config.
-    pub fn dimensions(self, dimensions: Dimensions) -> Self {
-        assert!(
-            dimensions.cols % 8 == 0,
-            "columns must be evenly divisible by 8"
-        );
-        assert!(
-            dimensions.rows <= display::MAX_GATE_OUTPUTS,
-            "rows must be less than MAX_GATE_OUTPUTS"
-        );
-        assert!(
-            dimensions.cols <= display::MAX_SOURCE_OUTPUTS,
-            "cols must be less than MAX_SOURCE_OUTPUTS"
-        );
-
+    /// successfully build a Config. The columns must be a multiple of 8, and the rows and columns
+    /// must not exceed `MAX_GATE_OUTPUTS` and `MAX_SOURCE_OUTPUTS`, which `build` checks.
+    pub const fn dimensions(self, dimensions: Dimensions) -> Self {
         Self {
             dimensions: Some(dimensions),
             ..self
         }
     }
 
+    /// Set the border waveform, controlling the color of the bezel area of the panel.
+    ///
+    /// Defaults to `BorderWaveform::White`. Corresponds to command 0x3C. Partial updates lock the
+    /// border at VCOM to prevent it flashing.
+    pub const fn border(self, border: BorderWaveform) -> Self {
+        Self { border, ..self }
+    }
+
     /// Set the display rotation.
     ///
     /// Defaults to no rotation (`Rotation::Rotate0`). Use this to translate between the physical
     /// rotation of the display and how the data is displayed on the display.
-    pub fn rotation(self, rotation: Rotation) -> Self {
+    pub const fn rotation(self, rotation: Rotation) -> Self {
         Self { rotation, ..self }
     }
 
+    /// Set an offset added to the coordinates of everything drawn with embedded-graphics.
+    ///
+    /// Defaults to no offset. The offset is in rotated coordinates and applied before clipping to
+    /// the display, so a UI designed for a slightly different resolution, or a panel with a dead
+    /// margin under the bezel, can be shifted without changing every draw call.
+    pub const fn drawing_offset(self, x: i16, y: i16) -> Self {
+        Self {
+            drawing_offset: (x, y),
+            ..self
+        }
+    }
+
+    /// Set the layout of frame buffers.
+    ///
+    /// Defaults to `FrameLayout::RowMajor`. The increment axis of the data entry mode (command
+    /// 0x11) is chosen to match, so column-major buffers produced by other rendering pipelines
+    /// can be written without transposition.
+    pub const fn frame_layout(self, frame_layout: FrameLayout) -> Self {
+        Self {
+            frame_layout: Some(frame_layout),
+            ..self
+        }
+    }
+
+    /// Set the order of the pixels within each byte of frame buffers.
+    ///
+    /// Defaults to `BitOrder::MsbFirst`, the order of the controller's RAM. With
+    /// `BitOrder::LsbFirst`, assets packed least significant bit first can be drawn, blitted and
+    /// passed to `update` without appearing mirrored within each byte.
+    pub const fn bit_order(self, bit_order: BitOrder) -> Self {
+        Self { bit_order, ..self }
+    }
+
+    /// Force a full refresh after this many consecutive partial updates.
+    ///
+    /// E-paper panels accumulate ghosting with each partial update. When set, the partial update
+    /// following `interval` consecutive partial updates is performed as a full (Display Mode 1)
+    /// refresh instead. Defaults to no limit.
+    pub const fn full_refresh_interval(self, interval: u16) -> Self {
+        Self {
+            full_refresh_interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Set whether partial updates pulse the hardware reset before writing.
+    ///
+    /// Defaults to true. The reset prevents the background color changing on some panels, but it
+    /// adds around 20 ms and re-latches the border, causing a visible blink on others. The
+    /// registers and waveform it clears are then restored as by `Display::wake`, which takes
+    /// longer again when the waveform is loaded from OTP. Panels that don't need the workaround
+    /// can disable it.
+    pub const fn partial_update_reset(self, partial_update_reset: bool) -> Self {
+        Self {
+            partial_update_reset,
+            ..self
+        }
+    }
+
+    /// Set a table of source driving voltages to apply as the panel ages.
+    ///
+    /// Multi-year deployments see contrast drift that can be partially compensated by adjusting
+    /// the source voltages. Entries must be sorted by ascending `min_usage`. At initialisation the
+    /// entry with the highest `min_usage` not above the usage set with `Display::set_panel_usage`
+    /// is written with command 0x04. The unit of usage is up to the application, e.g. days in
+    /// service or number of refreshes.
+    ///
+    /// There is no default, the source voltages are left at their power on values.
+    pub const fn aging_compensation(self, steps: &'a [AgingStep]) -> Self {
+        Self {
+            aging_compensation: steps,
+            ..self
+        }
+    }
+
+    /// Set the first gate line scanned, for panels whose glass does not start at gate 0.
+    ///
+    /// Defaults to 0. Corresponds to command 0x0F.
+    pub const fn gate_scan_start(self, gate_scan_start: u16) -> Self {
+        Self {
+            gate_scan_start,
+            ..self
+        }
+    }
+
+    /// Set how the black/white and red RAM are used when refreshing the display.
+    ///
+    /// Both default to `RamOption::Normal`. Corresponds to command 0x21. The black/white option is
+    /// further inverted at runtime by `Display::set_inverted`.
+    pub const fn ram_options(self, black_ram_option: RamOption, red_ram_option: RamOption) -> Self {
+        Self {
+            black_ram_option,
+            red_ram_option,
+            ..self
+        }
+    }
+
+    /// Set the range of source outputs driven.
+    ///
+    /// Defaults to `SourceOption::SourceFromS0ToS175` for panels wider than 160 columns and
+    /// `SourceOption::SourceFromS8ToS167` otherwise. Corresponds to command 0x21.
+    pub const fn source_option(self, source_option: SourceOption) -> Self {
+        Self {
+            source_option: Some(source_option),
+            ..self
+        }
+    }
+
+    /// Set the waveform initialisation performed by `Display::reset`.
+    ///
+    /// Defaults to `InitProfile::Fast`, matching the GoodDisplay example code.
+    pub const fn init_profile(self, init_profile: InitProfile) -> Self {
+        Self {
+            init_profile,
+            ..self
+        }
+    }
+
+    /// Set the update sequence (command 0x22) of full refreshes.
+    ///
+    /// Used by `refresh`, `update`, `deghost` and the partial updates forced to a full refresh.
+    /// Defaults to `UpdateSequence::DISPLAY_MODE_1` (0xC7), which uses the waveform loaded at
+    /// initialisation. Vendor scripts for some panels reload the waveform for each refresh with
+    /// `UpdateSequence::LOAD_AND_DISPLAY_MODE_1` (0xF7) instead. The sequence must be valid and
+    /// include `UpdateSequence::DISPLAY`, otherwise `build` fails.
+    pub const fn full_refresh_sequence(self, sequence: UpdateSequence) -> Self {
+        Self {
+            full_refresh_sequence: sequence,
+            ..self
+        }
+    }
+
+    /// Set the update sequence (command 0x22) of the fast refresh performed by `Display::splash`.
+    ///
+    /// Defaults to 0xD7, loading the LUT for the temperature written by `splash` before the
+    /// refresh. The sequence must be valid and include `UpdateSequence::DISPLAY`, otherwise `build`
+    /// fails.
+    pub const fn fast_refresh_sequence(self, sequence: UpdateSequence) -> Self {
+        Self {
+            fast_refresh_sequence: sequence,
+            ..self
+        }
+    }
+
+    /// Set the update sequence (command 0x22) of partial refreshes.
+    ///
+    /// Defaults to `UpdateSequence::DISPLAY_MODE_2` (0xCF). The sequence must be valid and include
+    /// `UpdateSequence::DISPLAY`, otherwise `build` fails.
+    pub const fn partial_refresh_sequence(self, sequence: UpdateSequence) -> Self {
+        Self {
+            partial_refresh_sequence: sequence,
+            ..self
+        }
+    }
+
+    /// Enter deep sleep automatically after each refresh.
+    ///
+    /// `update`, `refresh` and the partial updates wait for the refresh to complete, then enter
+    /// deep sleep with `mode`. The controller is woken again when the next operation needs it,
+    /// so the display never has to be woken by hand. Use `DeepSleepMode::PreserveRAM` if partial
+    /// updates are performed. Disabled by default.
+    pub const fn auto_deep_sleep(self, mode: DeepSleepMode) -> Self {
+        Self {
+            auto_deep_sleep: Some(mode),
+            ..self
+        }
+    }
+
+    /// Set the temperature sensor the controller uses to select the waveform.
+    ///
+    /// Boards that route an LM75 compatible sensor to the controller's I2C pins can select
+    /// `TemperatureSensor::External`, which is then used from the first `reset` on. The sensor can
+    /// still be changed later with `Display::use_external_temperature_sensor` and
+    /// `Display::use_internal_temperature_sensor`. Defaults to `TemperatureSensor::Internal`.
+    pub const fn temperature_sensor(self, sensor: TemperatureSensor) -> Self {
+        Self {
+            temperature_sensor: sensor,
+            ..self
+        }
+    }
+
+    /// Set a policy adjusting refreshes according to the panel temperature.
+    ///
+    /// When set, the controller's temperature sensor is read before each refresh and the policy
+    /// can force a full refresh or select the waveform for a different temperature. There is no
+    /// policy by default. Reading the temperature requires the interface to support `read_data`.
+    ///
+    /// The policy must be `Sync` so that the `Config` can be stored in a `static`. A policy that
+    /// isn't can be set with `Display::set_temperature_policy` instead.
+    pub const fn temperature_policy(self, policy: &'a (dyn TemperaturePolicy + Sync)) -> Self {
+        Self {
+            temperature_policy: Some(policy),
+            ..self
+        }
+    }
+
     /// Build the display Config.
     ///
-    /// Will fail if dimensions are not set.
-    pub fn build(self) -> Result<Config<'a>, BuilderError> {
+    /// Will fail if dimensions are not set or out of range, a lookup table has the wrong length,
+    /// a driving voltage or timing is out of range, or the data entry mode is unsupported or
+    /// conflicts with the frame layout. The `BuilderError` says which.
+    pub const fn build(self) -> Result<Config<'a>, BuilderError> {
+        let dimensions = match self.dimensions {
+            Some(dimensions) => dimensions,
+            None => return Err(BuilderError::MissingDimensions),
+        };
+        if dimensions.rows > display::MAX_GATE_OUTPUTS {
+            return Err(BuilderError::RowsTooLarge);
+        }
+        if dimensions.cols > display::MAX_SOURCE_OUTPUTS {
+            return Err(BuilderError::ColsTooLarge);
+        }
+        if !dimensions.cols.is_multiple_of(8) {
+            return Err(BuilderError::ColsNotByteAligned);
+        }
+        let frame_layout = match (self.frame_layout, self.increment_axis) {
+            (Some(frame_layout), None) => frame_layout,
+            (None, None | Some(IncrementAxis::Horizontal)) => FrameLayout::RowMajor,
+            (None, Some(IncrementAxis::Vertical)) => FrameLayout::ColumnMajor,
+            (Some(FrameLayout::RowMajor), Some(IncrementAxis::Horizontal)) => FrameLayout::RowMajor,
+            (Some(FrameLayout::ColumnMajor), Some(IncrementAxis::Vertical)) => {
+                FrameLayout::ColumnMajor
+            }
+            (Some(_), Some(_)) => return Err(BuilderError::ConflictingFrameLayout),
+        };
+        if matches!(self.waveform_source, WaveformSource::Custom(lut) if lut.len() != LUT_SIZE) {
+            return Err(BuilderError::LutWrongLength);
+        }
+        // Loops rather than iterators, which can't be used in a const fn
+        let mut index = 0;
+        while index < self.temperature_luts.len() {
+            if self.temperature_luts[index].lut.len() != LUT_SIZE {
+                return Err(BuilderError::LutWrongLength);
+            }
+            if index > 0
+                && self.temperature_luts[index - 1].min_temperature
+                    > self.temperature_luts[index].min_temperature
+            {
+                return Err(BuilderError::TemperatureLutsUnsorted);
+            }
+            index += 1;
+        }
+        let vcom = match self.vcom_mv {
+            Some(vcom_mv) => match vcom_register(vcom_mv) {
+                Some(vcom) => Some(vcom),
+                None => return Err(BuilderError::VcomOutOfRange),
+            },
+            None => self.vcom,
+        };
+        let gate_voltage = match self.gate_voltage_mv {
+            Some(vgh_mv) => match gate_voltage_register(vgh_mv) {
+                Some(vgh) => Some(vgh),
+                None => return Err(BuilderError::GateVoltageOutOfRange),
+            },
+            None => None,
+        };
+        let source_voltages = match self.source_voltages_mv {
+            Some((vsh1_mv, vsh2_mv, vsl_mv)) => {
+                match SourceVoltages::from_millivolts(vsh1_mv, vsh2_mv, vsl_mv) {
+                    Some(voltages) => Some(voltages),
+                    None => return Err(BuilderError::SourceVoltageOutOfRange),
+                }
+            }
+            None => None,
+        };
+        let source_option = match self.source_option {
+            Some(source_option) => source_option,
+            None if dimensions.cols > 160 => SourceOption::SourceFromS0ToS175,
+            None => SourceOption::SourceFromS8ToS167,
+        };
+        if let Some(period) = self.dummy_line_period {
+            if period > MAX_DUMMY_LINE_PERIOD {
+                return Err(BuilderError::DummyLinePeriodTooLarge);
+            }
+        }
+        if let Some(width) = self.gate_line_width {
+            if width > MAX_GATE_LINE_WIDTH {
+                return Err(BuilderError::GateLineWidthTooLarge);
+            }
+        }
+        if !refreshes_display(self.full_refresh_sequence)
+            || !refreshes_display(self.fast_refresh_sequence)
+            || !refreshes_display(self.partial_refresh_sequence)
+        {
+            return Err(BuilderError::InvalidUpdateSequence);
+        }
+        // Loading from OTP would replace a custom LUT
+        let otp_steps = match self.waveform_source {
+            WaveformSource::Otp => UpdateSequence::empty(),
+            WaveformSource::Custom(_) => UpdateSequence::LOAD_TEMPERATURE.load_lut(),
+        };
+        let booster = match self.soft_start {
+            Some(soft_start) => match soft_start.register_values() {
+                Some(booster) => Some(booster),
+                None => return Err(BuilderError::SoftStartOutOfRange),
+            },
+            None => None,
+        };
         Ok(Config {
-            _dummy_line_period: self.dummy_line_period,
-            _gate_line_width: self.gate_line_width,
-            _write_vcom: self.write_vcom,
-            _write_lut: self.write_lut,
-            _data_entry_mode: self.data_entry_mode,
-            dimensions: self.dimensions.ok_or(BuilderError {})?,
+            dummy_line_period: self.dummy_line_period,
+            gate_line_width: self.gate_line_width,
+            vcom,
+            gate_voltage,
+            source_voltages,
+            booster,
+            block_control: self.block_control,
+            waveform_source: self.waveform_source,
+            temperature_luts: self.temperature_luts,
+            dimensions,
             rotation: self.rotation,
+            drawing_offset: self.drawing_offset,
+            frame_layout,
+            bit_order: self.bit_order,
+            full_refresh_interval: self.full_refresh_interval,
+            aging_compensation: self.aging_compensation,
+            partial_update_reset: self.partial_update_reset,
+            border: self.border,
+            gate_scan_start: self.gate_scan_start,
+            black_ram_option: self.black_ram_option,
+            red_ram_option: self.red_ram_option,
+            source_option,
+            init_profile: self.init_profile,
+            full_refresh_sequence: self.full_refresh_sequence.difference(otp_steps),
+            fast_refresh_sequence: self.fast_refresh_sequence.difference(otp_steps),
+            partial_refresh_sequence: self.partial_refresh_sequence.difference(otp_steps),
+            auto_deep_sleep: self.auto_deep_sleep,
+            temperature_sensor: self.temperature_sensor,
+            temperature_policy: self.temperature_policy,
         })
     }
 }
+
+impl<'a> Config<'a> {
+    /// Returns the source voltages to apply for `usage`, if an aging table or fixed voltages were
+    /// configured.
+    pub(crate) fn voltages_for_usage(&self, usage: u32) -> Option<SourceVoltages> {
+        let Some(first) = self.aging_compensation.first() else {
+            return self.source_voltages;
+        };
+        let selected = self
+            .aging_compensation
+            .iter()
+            .rev()
+            .find(|step| step.min_usage <= usage)
+            .unwrap_or(first);
+        Some(selected.voltages)
+    }
+
+    /// Returns the temperature LUT to use at `temperature` (in °C), if any were configured.
+    pub(crate) fn lut_for_temperature(&self, temperature: i16) -> Option<&'a [u8]> {
+        let first = self.temperature_luts.first()?;
+        let selected = self
+            .temperature_luts
+            .iter()
+            .rev()
+            .find(|entry| i16::from(entry.min_temperature) <= temperature)
+            .unwrap_or(first);
+        Some(selected.lut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_start_encodes_strength_minus_one() {
+        let phase = |strength, min_off_time| SoftStartPhase {
+            strength,
+            min_off_time,
+            duration_ms: 10,
+        };
+        // Power on value of phase 1
+        assert_eq!(phase(1, 0xB).register_values(), Some((0x8B, 0b00)));
+        assert_eq!(phase(8, 0xF).register_values(), Some((0xFF, 0b00)));
+        assert_eq!(phase(0, 0xB).register_values(), None);
+        assert_eq!(phase(9, 0xB).register_values(), None);
+        assert_eq!(phase(1, 0x3).register_values(), None);
+
+        let soft_start = SoftStart {
+            phase1: phase(1, 0xB),
+            phase2: SoftStartPhase {
+                duration_ms: 40,
+                ..phase(2, 0xC)
+            },
+            phase3: SoftStartPhase {
+                duration_ms: 20,
+                ..phase(3, 0x6)
+            },
+        };
+        assert_eq!(
+            soft_start.register_values(),
+            Some([0x8B, 0x9C, 0xA6, 0b01_11_00])
+        );
+    }
+
+    #[test]
+    fn vcom_follows_datasheet_table() {
+        assert_eq!(vcom_register(-200), Some(0x08));
+        assert_eq!(vcom_register(-300), Some(0x0B));
+        assert_eq!(vcom_register(-600), Some(0x17));
+        assert_eq!(vcom_register(-1_000), Some(0x28));
+        assert_eq!(vcom_register(-1_200), Some(0x2F));
+        assert_eq!(vcom_register(-1_600), Some(0x40));
+        assert_eq!(vcom_register(-1_900), Some(0x4B));
+        assert_eq!(vcom_register(-2_900), Some(0x7C));
+        assert_eq!(vcom_register(-3_000), Some(0x82));
+        assert_eq!(vcom_register(-100), None);
+        assert_eq!(vcom_register(-1_650), None);
+        assert_eq!(vcom_register(-3_100), None);
+        assert_eq!(vcom_register(0), None);
+        assert_eq!(vcom_register(200), None);
+        assert!(VCOM_REGISTERS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn builder_reports_invalid_configuration() {
+        let dimensions = |rows, cols| Dimensions { rows, cols };
+        let error = |builder: Builder<'_>| builder.build().err();
+        assert_eq!(error(Builder::new()), Some(BuilderError::MissingDimensions));
+        assert_eq!(
+            error(Builder::new().dimensions(dimensions(297, 8))),
+            Some(BuilderError::RowsTooLarge)
+        );
+        assert_eq!(
+            error(Builder::new().dimensions(dimensions(8, 184))),
+            Some(BuilderError::ColsTooLarge)
+        );
+        assert_eq!(
+            error(Builder::new().dimensions(dimensions(8, 12))),
+            Some(BuilderError::ColsNotByteAligned)
+        );
+
+        let builder = || Builder::new().dimensions(dimensions(8, 8));
+        let lut = [0u8; LUT_SIZE];
+        assert!(builder().lut(&lut).build().is_ok());
+        assert_eq!(
+            error(builder().lut(&lut[..70])),
+            Some(BuilderError::LutWrongLength)
+        );
+        let luts = [
+            TemperatureLut {
+                min_temperature: 10,
+                lut: &lut,
+            },
+            TemperatureLut {
+                min_temperature: 0,
+                lut: &lut,
+            },
+        ];
+        assert_eq!(
+            error(builder().temperature_luts(&luts)),
+            Some(BuilderError::TemperatureLutsUnsorted)
+        );
+        let vcom = |vcom_mv| {
+            builder()
+                .vcom_voltage(vcom_mv)
+                .build()
+                .map(|config| config.vcom)
+        };
+        assert_eq!(vcom(-200), Ok(Some(0x08)));
+        assert_eq!(vcom(-1_600), Ok(Some(0x40)));
+        assert_eq!(vcom(-3_000), Ok(Some(0x82)));
+        assert_eq!(vcom(-1_650), Err(BuilderError::VcomOutOfRange));
+        assert_eq!(vcom(-3_100), Err(BuilderError::VcomOutOfRange));
+        assert_eq!(vcom(1_000), Err(BuilderError::VcomOutOfRange));
+        assert_eq!(
+            error(builder().gate_driving_voltage(9_500)),
+            Some(BuilderError::GateVoltageOutOfRange)
+        );
+        assert_eq!(
+            error(builder().source_driving_voltage(15_000, 5_000, -30_000)),
+            Some(BuilderError::SourceVoltageOutOfRange)
+        );
+        assert_eq!(
+            error(builder().dummy_line_period(128)),
+            Some(BuilderError::DummyLinePeriodTooLarge)
+        );
+        assert_eq!(
+            error(builder().gate_line_width(0x10)),
+            Some(BuilderError::GateLineWidthTooLarge)
+        );
+        assert_eq!(
+            error(builder().partial_refresh_sequence(UpdateSequence::LOAD_LUT_MODE_2)),
+            Some(BuilderError::InvalidUpdateSequence)
+        );
+        assert_eq!(
+            error(builder().full_refresh_sequence(UpdateSequence::DISPLAY)),
+            Some(BuilderError::InvalidUpdateSequence)
+        );
+        let vertical = || builder().data_entry_mode(IncrementAxis::Vertical);
+        let layout = |builder: Builder<'_>| builder.build().map(|config| config.frame_layout);
+        assert_eq!(layout(vertical()), Ok(FrameLayout::ColumnMajor));
+        assert_eq!(
+            layout(vertical().frame_layout(FrameLayout::ColumnMajor)),
+            Ok(FrameLayout::ColumnMajor)
+        );
+        assert_eq!(
+            error(vertical().frame_layout(FrameLayout::RowMajor)),
+            Some(BuilderError::ConflictingFrameLayout)
+        );
+    }
+}
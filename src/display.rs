@@ -1,10 +1,21 @@
+#[cfg(feature = "otp-program")]
+use crate::command::OtpProgramMode;
+#[cfg(feature = "graphics-core")]
+use crate::frame::Frame;
 use crate::{
     command::{
-        BufCommand, Command, DataEntryMode, DeepSleepMode, DisplayUpdateSequenceOption,
-        IncrementAxis, RamOption, SourceOption, TemperatureSensor,
+        BorderWaveform, BufCommand, Command, DataEntryMode, DeepSleepMode, IncrementAxis,
+        LutLoadMode, RamOption, TemperatureSensor, UpdateSequence, MAX_VCOM_SENSE_DURATION,
     },
-    config::Config,
+    config::{Config, SourceVoltages, TemperaturePolicy, WaveformSource},
+    error::Ssd1680Error,
     interface::DisplayInterface,
+    stats::{Metered, Stats},
+};
+use core::{
+    future::Future,
+    iter,
+    ops::{Deref, DerefMut},
 };
 
 // Max display resolution is 176x296 // was 160x296
@@ -13,11 +24,27 @@ pub const MAX_GATE_OUTPUTS: u16 = 296;
 /// The maximum number of columns supported by the controller
 pub const MAX_SOURCE_OUTPUTS: u8 = 176;
 
+// Auto write patterns with steps larger than the panel, filling the whole RAM with one value
+const AUTO_WRITE_FILL_0: u8 = 0x77;
+const AUTO_WRITE_FILL_1: u8 = 0xF7;
+
+// Auto write pattern of 16 by 16 pixel squares, starting with black
+const AUTO_WRITE_CHECKERBOARD: u8 = 0x11;
+
+// Ordered dither thresholds for `Pattern::Gradient`
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// VCI threshold for `check_power_rails`, the lowest supported level of 2.3V
+const VCI_DETECTION_THRESHOLD: u8 = 0x03;
+
 // Magic numbers from the data sheet
-// const ANALOG_BLOCK_CONTROL_MAGIC: u8 = 0x54;
-// const DIGITAL_BLOCK_CONTROL_MAGIC: u8 = 0x3B;
+const ANALOG_BLOCK_CONTROL_MAGIC: u8 = 0x54;
+const DIGITAL_BLOCK_CONTROL_MAGIC: u8 = 0x3B;
 
 /// Represents the dimensions of the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dimensions {
     /// The number of rows the display has.
     ///
@@ -34,7 +61,9 @@ pub struct Dimensions {
 /// For example the native orientation of the Inky pHAT display is a tall (portrait) 104x212
 /// display. `Rotate270` can be used to make it the right way up when attached to a Raspberry Pi
 /// Zero with the ports on the top.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Rotation {
     Rotate0,
     Rotate90,
@@ -49,13 +78,382 @@ impl Default for Rotation {
     }
 }
 
+/// Order in which the packed bytes of a frame buffer are laid out.
+///
+/// Each byte always holds 8 horizontally adjacent pixels, in the configured `BitOrder`. The layout
+/// selects whether consecutive bytes run along a row or down a column. The controller's address
+/// counter is configured to match, so buffers in either layout are written without transposition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameLayout {
+    /// Consecutive bytes run along a row (X first), `cols / 8` bytes per row.
+    #[default]
+    RowMajor,
+    /// Consecutive bytes run down a column of bytes (Y first), `rows` bytes per byte column.
+    ColumnMajor,
+}
+
+impl FrameLayout {
+    /// Returns the byte index of the pixel at physical position `x`, `y` in a frame of the given
+    /// dimensions.
+    pub fn byte_index(self, x: u32, y: u32, cols: u32, rows: u32) -> u32 {
+        match self {
+            FrameLayout::RowMajor => x / 8 + (cols / 8) * y,
+            FrameLayout::ColumnMajor => (x / 8) * rows + y,
+        }
+    }
+
+    pub(crate) fn increment_axis(self) -> IncrementAxis {
+        match self {
+            FrameLayout::RowMajor => IncrementAxis::Horizontal,
+            FrameLayout::ColumnMajor => IncrementAxis::Vertical,
+        }
+    }
+}
+
+/// Order of the pixels within each byte of a frame buffer.
+///
+/// The controller takes the leftmost pixel of each byte from the most significant bit. Buffers
+/// packed least significant bit first, as produced by image2cpp and several vendor tools, are
+/// reversed within each byte as they are written to RAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BitOrder {
+    /// The leftmost pixel is in the most significant bit.
+    #[default]
+    MsbFirst,
+    /// The leftmost pixel is in the least significant bit.
+    LsbFirst,
+}
+
+impl BitOrder {
+    /// Returns the mask of the bit holding the pixel at physical column `x` within its byte.
+    pub fn bit_mask(self, x: u32) -> u8 {
+        match self {
+            BitOrder::MsbFirst => 0x80 >> (x % 8),
+            BitOrder::LsbFirst => 0x01 << (x % 8),
+        }
+    }
+
+    /// Convert a byte, or a mask, between MSB first and this order.
+    pub(crate) fn convert(self, byte: u8) -> u8 {
+        match self {
+            BitOrder::MsbFirst => byte,
+            BitOrder::LsbFirst => byte.reverse_bits(),
+        }
+    }
+}
+
+/// A rectangular region of the display, in physical (unrotated) pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Region {
+    /// Column of the left edge.
+    pub x: u16,
+    /// Row of the top edge.
+    pub y: u16,
+    /// Width in pixels.
+    pub width: u16,
+    /// Height in pixels.
+    pub height: u16,
+}
+
+impl Region {
+    /// Returns the smallest region containing both regions.
+    pub fn union(self, other: Region) -> Region {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Region {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+
+    /// Returns the region expanded horizontally to whole bytes of the frame buffer.
+    pub fn byte_aligned(self) -> Region {
+        let x = self.x / 8 * 8;
+        let right = (self.x + self.width).div_ceil(8) * 8;
+        Region {
+            x,
+            y: self.y,
+            width: right - x,
+            height: self.height,
+        }
+    }
+}
+
+/// The RAM planes of the controller.
+///
+/// Even on B/W panels the red RAM takes part in waveform selection: for Display Mode 2 (partial)
+/// refreshes the controller treats it as the previous image and drives only the pixels that differ
+/// from the B/W RAM. Keeping the red RAM in sync with what is displayed, e.g. with
+/// `Display::write_ram`, gives cleaner partial refreshes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RamPlane {
+    /// The B/W RAM, written with command 0x24. 1 = white, 0 = black.
+    BlackWhite,
+    /// The red RAM, written with command 0x26. On tri-color panels 1 = red, 0 = use the B/W RAM.
+    Red,
+}
+
+/// A colour the whole panel can be cleared to with `Display::clear_ram`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Color {
+    /// Every pixel white
+    White,
+    /// Every pixel black
+    Black,
+    /// Every pixel red, on tri-color panels
+    Red,
+}
+
+/// An image for checking a panel during hardware bring-up, drawn with
+/// `Display::draw_test_pattern`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Pattern {
+    /// Black and white squares of 16 by 16 pixels, starting with black at the origin
+    Checkerboard,
+    /// Vertical stripes one pixel wide, starting with white, to check every source line
+    Stripes,
+    /// A dithered ramp from white on the first row to black on the last, to check the greys
+    /// left by the waveform
+    Gradient,
+}
+
+/// The identification stored in the controller's OTP, returned by `Display::read_otp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Otp {
+    /// Whether the VCOM register is loaded from OTP
+    pub vcom_otp_selection: u8,
+    /// The VCOM register, in the encoding of `Command::WriteVCOM`
+    pub vcom: u8,
+    /// The display mode (WS0 to WS36) selecting full or partial waveforms per refresh
+    pub display_mode: [u8; 5],
+    /// The waveform version burned in by the panel vendor
+    pub waveform_version: [u8; 4],
+    /// The user ID burned in by the panel vendor or during provisioning
+    pub user_id: [u8; 10],
+}
+
+/// The state of the power supplies, returned by `Display::check_power_rails`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerRails {
+    /// The booster reached its target voltage
+    pub hv_ready: bool,
+    /// VCI is above the 2.3V detection threshold
+    pub vci_ok: bool,
+}
+
+/// Low power state to leave the controller in when a `PowerGuard` is finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DropAction {
+    /// Leave the controller as it is.
+    Nothing,
+    /// Enter deep sleep mode, as `Display::deep_sleep`.
+    DeepSleep,
+    /// Disable the analog block and clock without entering deep sleep.
+    PowerOff,
+}
+
+/// Convert a temperature to the temperature register format.
+///
+/// The register holds a 12 bit two's complement value in 1/16 °C, left aligned.
+fn temperature_register(celsius: i8) -> u16 {
+    (i16::from(celsius) << 8) as u16
+}
+
+/// Returns the byte of a test pattern at byte column `x_byte` and row `y`, most significant bit
+/// first.
+///
+/// `draw_test_pattern` has the controller write the checkerboard with auto write, the bytes here
+/// are what it writes.
+fn pattern_byte(pattern: Pattern, x_byte: usize, y: usize, rows: u16) -> u8 {
+    match pattern {
+        Pattern::Checkerboard if (x_byte / 2 + y / 16).is_multiple_of(2) => 0x00,
+        Pattern::Checkerboard => 0xFF,
+        Pattern::Stripes => 0xAA,
+        Pattern::Gradient => {
+            // The number of the 16 dither thresholds drawn black, 0 on the first row and 16 on
+            // the last
+            let level = y * 16 / usize::from(rows.saturating_sub(1).max(1));
+            (0..8).fold(0, |byte, bit| {
+                let x = x_byte * 8 + bit;
+                if usize::from(BAYER_4X4[y % 4][x % 4]) < level {
+                    byte
+                } else {
+                    byte | 0x80 >> bit
+                }
+            })
+        }
+    }
+}
+
+/// Displays that can be put into a low power state by a `PowerGuard`.
+pub trait PowerDown {
+    /// The error returned when the action fails.
+    type Error;
+
+    /// Perform `action`.
+    fn power_down(&mut self, action: DropAction) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// Wraps a `Display` or `GraphicDisplay` that should be left in a low power state once it is no
+/// longer used.
+///
+/// The guard dereferences to the wrapped display. Call `finish` when done with it to perform the
+/// action, e.g. after scoped usage in tests or short-lived tasks.
+///
+/// **Note:** Drop cannot await, so dropping a guard without calling `finish`, e.g. on an early
+/// return, leaves the controller as it is. With the `defmt` feature a warning is logged. Use
+/// [with_power_down] where errors may return early, which performs the action in any case.
+pub struct PowerGuard<D>
+where
+    D: PowerDown,
+{
+    display: Option<D>,
+    action: DropAction,
+}
+
+impl<D> PowerGuard<D>
+where
+    D: PowerDown,
+{
+    /// Wrap `display`, performing `action` when the guard is finished.
+    pub fn new(display: D, action: DropAction) -> Self {
+        Self {
+            display: Some(display),
+            action,
+        }
+    }
+
+    /// Change the action performed when the guard is finished.
+    pub fn set_action(&mut self, action: DropAction) {
+        self.action = action;
+    }
+
+    /// Perform the action, returning the display.
+    pub async fn finish(mut self) -> Result<D, D::Error> {
+        let mut display = self.display.take().expect("display present until dropped");
+        display.power_down(self.action).await?;
+        Ok(display)
+    }
+
+    /// Unwrap the display without performing the action.
+    pub fn into_inner(mut self) -> D {
+        self.display.take().expect("display present until dropped")
+    }
+}
+
+impl<D> Deref for PowerGuard<D>
+where
+    D: PowerDown,
+{
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        self.display
+            .as_ref()
+            .expect("display present until dropped")
+    }
+}
+
+impl<D> DerefMut for PowerGuard<D>
+where
+    D: PowerDown,
+{
+    fn deref_mut(&mut self) -> &mut D {
+        self.display
+            .as_mut()
+            .expect("display present until dropped")
+    }
+}
+
+impl<D> Drop for PowerGuard<D>
+where
+    D: PowerDown,
+{
+    fn drop(&mut self) {
+        #[cfg(feature = "defmt")]
+        if self.display.is_some() && self.action != DropAction::Nothing {
+            defmt::warn!("PowerGuard dropped without finish, the controller is left as it is");
+        }
+    }
+}
+
+/// Run `f` with `display`, then perform `action` whether `f` succeeded or not.
+///
+/// This is the way to leave the controller in a low power state when `f` can return early, e.g.
+/// with `?`, which would skip `PowerGuard::finish`. If `f` fails, its error is returned and an
+/// error performing the action is dropped. Otherwise an error performing the action is returned.
+/// As with any future, nothing is done if the returned future is dropped before completing.
+///
+/// ```ignore
+/// let temperature = with_power_down(&mut display, DropAction::DeepSleep, async |display| {
+///     display.reset().await?;
+///     display.update(&frame).await?;
+///     display.read_temperature().await
+/// })
+/// .await?;
+/// ```
+pub async fn with_power_down<D, T>(
+    display: &mut D,
+    action: DropAction,
+    f: impl AsyncFnOnce(&mut D) -> Result<T, D::Error>,
+) -> Result<T, D::Error>
+where
+    D: PowerDown,
+{
+    let result = f(display).await;
+    let powered_down = display.power_down(action).await;
+    let output = result?;
+    powered_down?;
+    Ok(output)
+}
+
+/// The state of the controller, as tracked by the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ControllerState {
+    /// The controller has not been initialised with `reset` since the display was created, or
+    /// since a `splash`.
+    Uninitialized,
+    /// The controller is awake and configured.
+    Ready,
+    /// The controller is in deep sleep and must be woken with `reset` or `wake`.
+    Asleep,
+}
+
 /// A configured display with a hardware interface.
 pub struct Display<'a, I>
 where
     I: DisplayInterface,
 {
-    interface: I,
+    interface: Metered<I>,
     config: Config<'a>,
+    partial_updates: u16,
+    panel_usage: u32,
+    source_voltages: Option<SourceVoltages>,
+    inverted: bool,
+    ram_retained: bool,
+    state: ControllerState,
+    temperature_sensor: TemperatureSensor,
+    waveform_override: Option<i8>,
+    temperature_policy: Option<&'a dyn TemperaturePolicy>,
+    /// The waveform temperature last requested by the temperature policy, and the override it
+    /// replaced
+    policy_override: Option<(i8, Option<i8>)>,
 }
 
 impl<'a, I> Display<'a, I>
@@ -66,206 +464,2065 @@ where
     ///
     /// The `Config` is typically created with `config::Builder`.
     pub fn new(interface: I, config: Config<'a>) -> Self {
-        Self { interface, config }
+        let temperature_sensor = config.temperature_sensor;
+        let temperature_policy = config
+            .temperature_policy
+            .map(|policy| policy as &dyn TemperaturePolicy);
+        Self {
+            interface: Metered::new(interface),
+            config,
+            partial_updates: 0,
+            panel_usage: 0,
+            source_voltages: None,
+            inverted: false,
+            ram_retained: false,
+            state: ControllerState::Uninitialized,
+            temperature_sensor,
+            waveform_override: None,
+            temperature_policy,
+            policy_override: None,
+        }
+    }
+
+    /// Release the interface and configuration, e.g. to share the SPI bus or pins with other
+    /// peripherals.
+    ///
+    /// Put the controller into deep sleep first if it should stay in a low power state. The
+    /// state tracked by the display, such as the number of partial updates, is lost, so call
+    /// `reset` after creating a new `Display` from the released parts.
+    pub fn release(self) -> (I, Config<'a>) {
+        (self.interface.inner, self.config)
+    }
+
+    /// Returns the interface, e.g. to inspect a `SimulatorInterface` between updates.
+    pub fn interface(&self) -> &I {
+        &self.interface.inner
     }
 
     /// Perform a hardware reset followed by software reset.
     ///
-    /// This will wake a controller that has previously entered deep sleep.
-    pub async fn reset(&mut self) -> Result<(), I::Error> {
+    /// This will wake a controller that has previously entered deep sleep. The waveform is then
+    /// loaded according to the configured `InitProfile`.
+    pub async fn reset(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
         self.chip_reset().await?;
         self.sw_reset().await?;
-        self.init_for_fast().await?;
-        self.init().await
+        self.init_waveform().await?;
+        self.init().await?;
+        self.state = ControllerState::Ready;
+        Ok(())
     }
 
-    async fn chip_reset(&mut self) -> Result<(), I::Error> {
-        self.interface.reset().await;
-        self.interface.busy_wait().await
+    /// Wake the controller from deep sleep, keeping the contents of the RAM.
+    ///
+    /// Pulses the hardware reset, then restores only what it cleared: the configuration registers
+    /// and the waveform that was loaded before sleeping. Unlike `reset`, there is no software
+    /// reset, the OTP waveform is loaded at most once, and the RAM window is left to the next
+    /// write. If the RAM was retained (see `ram_retained`) the existing frame can then be shown
+    /// with `refresh`, or used as the base for partial updates, without sending it again.
+    pub async fn wake(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.chip_reset().await?;
+        self.write_registers().await?;
+        self.restore_waveform().await?;
+        self.state = ControllerState::Ready;
+        Ok(())
+    }
+
+    /// Show a boot splash image as quickly as possible.
+    ///
+    /// Performs a hardware reset and the minimal initialisation needed to write `black` to the
+    /// RAM, then triggers a refresh with the fast waveform (loaded for a high temperature, as with
+    /// `InitProfile::Fast`, or with `Builder::fast_refresh_sequence`). It returns as soon as the
+    /// refresh has been triggered, without waiting for it to complete. The buffer is laid out as
+    /// for `update`.
+    ///
+    /// Only a subset of the configuration is applied, call `reset` before performing further
+    /// updates.
+    pub async fn splash(&mut self, black: &[u8]) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_frame(black)?;
+        self.chip_reset().await?;
+        self.state = ControllerState::Uninitialized;
+        self.write_block_control().await?;
+        Command::DriverOutputControl(self.config.dimensions.rows - 1, 0x00)
+            .execute(&mut self.interface)
+            .await?;
+        self.write_data_entry_mode().await?;
+        self.set_full_window().await?;
+        self.set_temperature_sensor().await?;
+        Command::WriteTemperatureSensor(temperature_register(100))
+            .execute(&mut self.interface)
+            .await?;
+        if let WaveformSource::Custom(lut) = self.config.waveform_source {
+            BufCommand::WriteLUT(lut)
+                .execute(&mut self.interface)
+                .await?;
+        }
+
+        self.write_plane(RamPlane::BlackWhite, &black[..self.frame_size()])
+            .await?;
+        self.ram_retained = true;
+
+        // By default, load the LUT for the written temperature and refresh in a single sequence
+        Command::UpdateDisplayOption2(self.config.fast_refresh_sequence)
+            .execute(&mut self.interface)
+            .await?;
+        Command::UpdateDisplay.execute(&mut self.interface).await?;
+        self.partial_updates = 0;
+
+        Ok(())
     }
 
-    async fn sw_reset(&mut self) -> Result<(), I::Error> {
+    async fn chip_reset(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.interface.reset().await?;
+        self.interface.busy_wait().await?;
+        Ok(())
+    }
+
+    async fn sw_reset(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
         Command::SoftReset.execute(&mut self.interface).await?;
-        self.interface.busy_wait().await
+        self.interface.busy_wait().await?;
+        Ok(())
     }
 
     /// Initialize the controller according to Section 9: Typical Operating Sequence
     /// from the data sheet
-    async fn init(&mut self) -> Result<(), I::Error> {
+    async fn init(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
         // Matches Section 9: Typical Operating Sequence from the data sheet
         self.interface.busy_wait().await?;
+        if let Some(voltages) = self.config.voltages_for_usage(self.panel_usage) {
+            self.source_voltages = Some(voltages);
+        }
+        self.write_registers().await?;
+        self.set_full_window().await
+    }
+
+    /// Write the configuration registers, which are cleared by a hardware reset.
+    async fn write_registers(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.write_block_control().await?;
         Command::DriverOutputControl(self.config.dimensions.rows - 1, 0x00)
             .execute(&mut self.interface)
             .await?;
-        Command::DataEntryMode(
-            DataEntryMode::IncrementYIncrementX, // DataEntryMode::IncrementXDecrementY
-            IncrementAxis::Horizontal,
-        )
-        .execute(&mut self.interface)
-        .await?;
-        Command::TemperatureSensorSelection(TemperatureSensor::Internal)
-            .execute(&mut self.interface)
-            .await?;
+        self.write_data_entry_mode().await?;
+        if self.config.gate_scan_start != 0 {
+            Command::GateScanStartPosition(self.config.gate_scan_start)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        self.set_temperature_sensor().await?;
+        if let Some(vcom) = self.config.vcom {
+            Command::WriteVCOM(vcom)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        if let Some([phase1, phase2, phase3, duration]) = self.config.booster {
+            Command::BoosterEnable(phase1, phase2, phase3, duration)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        if let Some(vgh) = self.config.gate_voltage {
+            Command::GateDrivingVoltage(vgh)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        if let Some(voltages) = self.source_voltages {
+            Command::SourceDrivingVoltage(voltages.vsh1, voltages.vsh2, voltages.vsl)
+                .execute(&mut self.interface)
+                .await?;
+        }
 
-        let end = self.cols_as_bytes() - 1;
-        Command::StartEndXPosition(0, end)
-            .execute(&mut self.interface)
-            .await?;
-        Command::StartEndYPosition(0, self.config.dimensions.rows - 1)
-            .execute(&mut self.interface)
-            .await?;
+        if let Some(period) = self.config.dummy_line_period {
+            Command::DummyLinePeriod(period)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        if let Some(tgate) = self.config.gate_line_width {
+            Command::GateLineWidth(tgate)
+                .execute(&mut self.interface)
+                .await?;
+        }
 
-        Command::BorderWaveform(0x05_u8)
+        Command::BorderWaveform(self.config.border)
             .execute(&mut self.interface)
             .await?;
-        Command::UpdateDisplayOption1(
-            RamOption::Normal,
-            RamOption::Normal,
-            SourceOption::SourceFromS8ToS167,
+        self.write_display_options().await
+    }
+
+    async fn write_data_entry_mode(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        Command::DataEntryMode(
+            DataEntryMode::IncrementYIncrementX,
+            self.config.frame_layout.increment_axis(),
         )
         .execute(&mut self.interface)
-        .await?;
+        .await
+    }
 
-        Command::XAddress(0x00).execute(&mut self.interface).await?;
-        Command::YAddress(self.config.dimensions.rows - 1)
-            .execute(&mut self.interface)
-            .await?;
+    /// Write the analog and digital block control registers, if configured.
+    async fn write_block_control(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        if self.config.block_control {
+            Command::AnalogBlockControl(ANALOG_BLOCK_CONTROL_MAGIC)
+                .execute(&mut self.interface)
+                .await?;
+            Command::DigitalBlockControl(DIGITAL_BLOCK_CONTROL_MAGIC)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn init_waveform(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.set_temperature_sensor().await?;
+        self.policy_override = None;
+        if let WaveformSource::Custom(lut) = self.config.waveform_source {
+            BufCommand::WriteLUT(lut)
+                .execute(&mut self.interface)
+                .await?;
+            self.waveform_override = None;
+            return Ok(());
+        }
+        self.reload_waveform().await?;
+
+        match self.config.init_profile.temperature() {
+            // Matches code example from GoodDisplay, which overrides the temperature with 100 °C
+            Some(temperature) => self.load_waveform_for(temperature).await,
+            None => Ok(()),
+        }
+    }
 
+    /// Load the waveform that was in use before a hardware reset cleared it.
+    async fn restore_waveform(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        if let WaveformSource::Custom(lut) = self.config.waveform_source {
+            return BufCommand::WriteLUT(lut).execute(&mut self.interface).await;
+        }
+        match self.waveform_override {
+            Some(celsius) => self.load_waveform_for(celsius).await,
+            None => self.reload_waveform().await,
+        }
+    }
+
+    /// Override the temperature used to select the waveform, loading the waveform for `celsius`.
+    ///
+    /// Warmer temperatures select faster waveforms, at the cost of contrast and more ghosting,
+    /// while colder temperatures select slower waveforms that drive the panel harder. The
+    /// override is kept until `reset`, `wake`, or temperature LUTs configured with
+    /// `Builder::temperature_luts` replace the waveform. This will busy wait until the waveform
+    /// has loaded.
+    pub async fn override_temperature(
+        &mut self,
+        celsius: i8,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.load_waveform_for(celsius).await?;
+        // A temperature policy that overrides the waveform restores this override afterwards
+        self.policy_override = None;
         Ok(())
     }
 
-    async fn init_for_fast(&mut self) -> Result<(), I::Error> {
-        // Matches code example from GoodDisplay
-        Command::TemperatureSensorSelection(TemperatureSensor::Internal)
+    async fn load_waveform_for(&mut self, celsius: i8) -> Result<(), Ssd1680Error<I::Error>> {
+        Command::WriteTemperatureSensor(temperature_register(celsius))
+            .execute(&mut self.interface)
+            .await?;
+
+        Command::UpdateDisplayOption2(UpdateSequence::LOAD_LUT_MODE_1)
             .execute(&mut self.interface)
             .await?;
-        Command::UpdateDisplayOption2(
-            DisplayUpdateSequenceOption::EnableClockSignal_LoadTemp_LoadLutMode1_DisableClockSignal,
-        )
-        .execute(&mut self.interface)
-        .await?;
         Command::UpdateDisplay.execute(&mut self.interface).await?;
         self.interface.busy_wait().await?;
+        self.waveform_override = Some(celsius);
 
-        Command::WriteTemperatureSensor(0x6400)
+        Ok(())
+    }
+
+    /// Load the waveform for the temperature measured by the sensor.
+    async fn reload_waveform(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        Command::UpdateDisplayOption2(UpdateSequence::LOAD_TEMPERATURE_LUT_MODE_1)
             .execute(&mut self.interface)
             .await?;
-
-        Command::UpdateDisplayOption2(
-            DisplayUpdateSequenceOption::EnableClockSignal_LoadLutMode1_DisableClockSignal,
-        )
-        .execute(&mut self.interface)
-        .await?;
         Command::UpdateDisplay.execute(&mut self.interface).await?;
         self.interface.busy_wait().await?;
+        self.waveform_override = None;
 
         Ok(())
     }
 
     /// Update the display by writing the supplied B/W and Red buffers to the controller.
     ///
+    /// The buffer must be laid out according to the configured `FrameLayout`.
+    ///
     /// This method will write the black buffer (only) to the controller then initiate the update
-    /// display command. Currently it will busy wait until the update has completed.
-    pub async fn update(&mut self, black: &[u8]) -> Result<(), I::Error> {
+    /// display command. It returns as soon as the refresh has started, the next operation waits
+    /// for it to complete. See `start_refresh`.
+    pub async fn update(&mut self, black: &[u8]) -> Result<(), Ssd1680Error<I::Error>> {
         self.update_impl(black).await?;
+        self.start_refresh().await?;
+        self.auto_deep_sleep().await
+    }
 
-        // Kick off the display update
-        Command::UpdateDisplayOption2(DisplayUpdateSequenceOption::EnableClockSignal_EnableAnalog_DisplayMode1_DisableAnalog_DisableOscillator).execute(&mut self.interface).await?; // was 0xC7, should be 0xCF
-        Command::UpdateDisplay.execute(&mut self.interface).await?;
-
-        Ok(())
+    /// Update the display with a frame rendered independently of the display.
+    ///
+    /// Returns `InvalidArgument` if the frame was created for different dimensions, a different
+    /// layout or a different bit order. See the `frame` module.
+    #[cfg(feature = "graphics-core")]
+    pub async fn present(
+        &mut self,
+        frame: &Frame<impl AsMut<[u8]> + AsRef<[u8]>>,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        if frame.rows() != self.rows()
+            || frame.cols() != self.cols()
+            || frame.frame_layout() != self.frame_layout()
+            || frame.frame_bit_order() != self.bit_order()
+            || frame.buffer().len() < self.frame_size()
+        {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        self.update(frame.buffer()).await
     }
 
-    async fn update_impl(&mut self, black: &[u8]) -> Result<(), I::Error> {
-        self.interface.busy_wait().await?;
-        // Write the B/W RAM
-        let buf_size = self.rows() as usize * self.cols() as usize;
-        let limit_adder = if buf_size % 8 != 0 { 1 } else { 0 };
-        let buf_limit = (buf_size / 8) + limit_adder;
+    /// Create a `Frame` drawing into `buffer` with the dimensions, rotation, layout and bit order
+    /// of this display, for use with `present`.
+    #[cfg(feature = "graphics-core")]
+    pub fn new_frame<B>(&self, buffer: B) -> Frame<B>
+    where
+        B: AsMut<[u8]> + AsRef<[u8]>,
+    {
+        let dimensions = Dimensions {
+            rows: self.rows(),
+            cols: self.cols(),
+        };
+        Frame::new(buffer, dimensions, self.rotation())
+            .layout(self.frame_layout())
+            .bit_order(self.bit_order())
+    }
 
-        Command::XAddress(0).execute(&mut self.interface).await?;
-        Command::YAddress(self.config.dimensions.rows - 1)
-            .execute(&mut self.interface)
-            .await?;
-        BufCommand::WriteBlackData(&black[..buf_limit])
-            .execute(&mut self.interface)
+    /// Update the display with a B/W frame produced by an iterator.
+    ///
+    /// This behaves like `update`, but the frame can be generated a line at a time as it is sent,
+    /// so no frame buffer is needed.
+    pub async fn update_from_iter(
+        &mut self,
+        black: impl Iterator<Item = u8>,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.write_ram_from_iter(RamPlane::BlackWhite, black)
             .await?;
-
-        Ok(())
+        self.start_refresh().await?;
+        self.auto_deep_sleep().await
     }
 
-    pub async fn partial_update(
+    /// Update the display with a B/W frame rendered one row at a time.
+    ///
+    /// `render` is called once per gate line with the row index and a buffer of `cols / 8` bytes
+    /// (initially white) to fill, and each row is sent as soon as it is rendered. This lets a UI be
+    /// rasterised on the fly, e.g. from a tile map, with no frame buffer. It otherwise behaves
+    /// like `update`. Requires `FrameLayout::RowMajor`, otherwise `InvalidArgument` is returned.
+    pub async fn update_rows(
         &mut self,
-        image: &[u8],
-        start_x_px: u16,
-        start_y_px: u16,
-        width_px: u16,
-        height_px: u16,
-    ) -> Result<(), I::Error> {
-        // Add hardware reset to prevent background color change
-        self.interface.reset().await;
-
-        // Lock the border to prevent flashing
-        Command::BorderWaveform(0x80)
+        mut render: impl FnMut(u16, &mut [u8]),
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        if self.config.frame_layout != FrameLayout::RowMajor {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.set_full_window().await?;
+        Command::WriteRam(RamPlane::BlackWhite)
             .execute(&mut self.interface)
             .await?;
+        let mut row = [0u8; MAX_SOURCE_OUTPUTS as usize / 8];
+        let row = &mut row[..usize::from(self.cols_as_bytes())];
+        for index in 0..self.rows() {
+            row.fill(0xFF);
+            render(index, row);
+            self.write_data(row).await?;
+        }
+        self.ram_retained = true;
+        self.start_refresh().await?;
+        self.auto_deep_sleep().await
+    }
 
-        let start_x_byte = (start_x_px / 8) as u8;
-        let width_byte = (width_px / 8) as u8;
-        let end_x_byte = start_x_byte + width_byte - 1;
-        Command::StartEndXPosition(start_x_byte, end_x_byte)
-            .execute(&mut self.interface)
-            .await?;
-        let end_y_px = start_y_px + height_px - 1;
-        Command::StartEndYPosition(start_y_px, end_y_px)
+    /// Update the display with a B/W frame from an iterator of packed rows.
+    ///
+    /// Each item, e.g. a `&[u8]`, holds one row of `cols / 8` bytes, laid out as a row of the
+    /// buffer for `update`, so rows can be decompressed or read from external flash a chunk at a
+    /// time. Only the first `rows` items are used. If a row is too short or the iterator ends
+    /// early, `InvalidArgument` is returned without refreshing, leaving the RAM partially written.
+    /// Requires `FrameLayout::RowMajor`.
+    pub async fn update_from_rows(
+        &mut self,
+        rows: impl Iterator<Item = impl AsRef<[u8]>>,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        if self.config.frame_layout != FrameLayout::RowMajor {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.set_full_window().await?;
+        Command::WriteRam(RamPlane::BlackWhite)
             .execute(&mut self.interface)
             .await?;
+        let row_len = usize::from(self.cols_as_bytes());
+        let mut sent = 0;
+        for row in rows.take(self.rows().into()) {
+            let row = row
+                .as_ref()
+                .get(..row_len)
+                .ok_or(Ssd1680Error::InvalidArgument)?;
+            self.write_data(row).await?;
+            sent += 1;
+        }
+        if sent < self.rows() {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        self.ram_retained = true;
+        self.start_refresh().await?;
+        self.auto_deep_sleep().await
+    }
 
-        Command::XAddress(start_x_byte)
-            .execute(&mut self.interface)
-            .await?;
-        Command::YAddress(start_y_px)
-            .execute(&mut self.interface)
-            .await?;
+    /// Perform a full refresh of the current RAM contents without writing a new frame.
+    ///
+    /// This will busy wait until the refresh has completed.
+    pub async fn refresh(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.start_refresh().await?;
+        self.wait_refresh().await?;
+        self.auto_deep_sleep().await
+    }
 
-        BufCommand::WriteBlackData(image)
+    /// Start a full refresh of the current RAM contents, returning without waiting for it to
+    /// complete.
+    ///
+    /// A full refresh takes around 2 s, during which other work can be done. Completion can then
+    /// be awaited with `wait_refresh` or polled with `poll_refresh`.
+    pub async fn start_refresh(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.prepare_waveform(false).await?;
+        // Partial updates lock the border, restore it for the full refresh
+        Command::BorderWaveform(self.config.border)
             .execute(&mut self.interface)
             .await?;
 
         // Kick off the display update
-        Command::UpdateDisplayOption2(DisplayUpdateSequenceOption::EnableClockSignal_EnableAnalog_DisplayMode2_DisableAnalog_DisableOscillator).execute(&mut self.interface).await?;
+        Command::UpdateDisplayOption2(self.config.full_refresh_sequence)
+            .execute(&mut self.interface)
+            .await?;
         Command::UpdateDisplay.execute(&mut self.interface).await?;
+        self.partial_updates = 0;
 
         Ok(())
     }
 
-    /// Enter deep sleep mode.
-    ///
-    /// This puts the display controller into a low power mode. `reset` must be called to wake it
-    /// from sleep.
-    pub async fn deep_sleep(&mut self) -> Result<(), I::Error> {
+    /// Wait for a refresh started by `start_refresh`, `update` or a partial update to complete.
+    pub async fn wait_refresh(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_awake()?;
         self.interface.busy_wait().await?;
-        Command::DeepSleepMode(DeepSleepMode::PreserveRAM)
-            .execute(&mut self.interface)
-            .await
+        Ok(())
     }
 
-    /// Returns the number of rows the display has.
-    pub fn rows(&self) -> u16 {
-        self.config.dimensions.rows
+    /// Returns whether a refresh started by `start_refresh`, `update` or a partial update has
+    /// completed, without waiting.
+    pub fn poll_refresh(&mut self) -> Result<bool, Ssd1680Error<I::Error>> {
+        self.check_awake()?;
+        Ok(!self.interface.is_busy()?)
     }
 
-    /// Returns the number of columns the display has.
-    pub fn cols(&self) -> u8 {
-        self.config.dimensions.cols
+    async fn update_impl(&mut self, black: &[u8]) -> Result<(), Ssd1680Error<I::Error>> {
+        // Write the B/W RAM
+        self.write_ram(RamPlane::BlackWhite, black).await
     }
 
-    pub fn cols_as_bytes(&self) -> u8 {
-        self.config.dimensions.cols / 8
+    /// Write a whole frame to one of the RAM planes without refreshing the display.
+    ///
+    /// The buffer is laid out as for `update`. Writing the displayed frame to `RamPlane::Red`
+    /// makes it the reference for the next partial refresh.
+    pub async fn write_ram(
+        &mut self,
+        plane: RamPlane,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_frame(data)?;
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        let data = &data[..self.frame_size()];
+        let mut attempt = 0;
+        loop {
+            let mut result = self.set_full_window().await;
+            if result.is_ok() {
+                result = self.write_plane(plane, data).await;
+            }
+            if !self.retry_ram_write(&result, &mut attempt).await {
+                break result?;
+            }
+        }
+        if plane == RamPlane::BlackWhite {
+            self.ram_retained = true;
+        }
+        Ok(())
     }
 
-    /// Returns the rotation the display was configured with.
-    pub fn rotation(&self) -> Rotation {
-        self.config.rotation
+    /// Write a whole frame to one of the RAM planes from an iterator, without refreshing the
+    /// display.
+    ///
+    /// The bytes are laid out as for `update`. At most `frame_size` bytes are taken from `data`.
+    pub async fn write_ram_from_iter(
+        &mut self,
+        plane: RamPlane,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.set_full_window().await?;
+        Command::WriteRam(plane)
+            .execute(&mut self.interface)
+            .await?;
+        let bit_order = self.config.bit_order;
+        self.interface
+            .send_data_iter(
+                data.take(self.frame_size())
+                    .map(|byte| bit_order.convert(byte)),
+            )
+            .await?;
+        if plane == RamPlane::BlackWhite {
+            self.ram_retained = true;
+        }
+        Ok(())
+    }
+
+    /// Write a whole frame to the red RAM without refreshing the display.
+    pub async fn write_red_ram(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<I::Error>> {
+        self.write_ram(RamPlane::Red, data).await
+    }
+
+    /// Read a whole frame from one of the RAM planes into `data`.
+    ///
+    /// The buffer is laid out as for `update`, and can be compared with the frame that was written
+    /// to check the transfer. This requires the SPI device to be able to read from the controller,
+    /// see `DisplayInterface::read_data`.
+    pub async fn read_ram(
+        &mut self,
+        plane: RamPlane,
+        data: &mut [u8],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_frame(data)?;
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.set_full_window().await?;
+        Command::ReadRamOption(plane)
+            .execute(&mut self.interface)
+            .await?;
+        let mut dummy = [0u8];
+        Command::ReadRam
+            .read(&mut self.interface, &mut dummy)
+            .await?;
+        let buf_limit = self.frame_size();
+        self.interface.read_data(&mut data[..buf_limit]).await?;
+        if self.config.bit_order == BitOrder::LsbFirst {
+            data[..buf_limit]
+                .iter_mut()
+                .for_each(|byte| *byte = byte.reverse_bits());
+        }
+        Ok(())
+    }
+
+    /// Read a whole frame from the B/W RAM into `data`.
+    pub async fn read_black_ram(&mut self, data: &mut [u8]) -> Result<(), Ssd1680Error<I::Error>> {
+        self.read_ram(RamPlane::BlackWhite, data).await
+    }
+
+    /// Fill one of the RAM planes with `value` without refreshing the display.
+    ///
+    /// Filling with 0x00 or 0xFF is performed in hardware with the auto write pattern commands,
+    /// other values are streamed to the controller.
+    pub async fn fill_ram(
+        &mut self,
+        plane: RamPlane,
+        value: u8,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        let pattern = match value {
+            0x00 => Some(AUTO_WRITE_FILL_0),
+            0xFF => Some(AUTO_WRITE_FILL_1),
+            _ => None,
+        };
+        if let Some(pattern) = pattern {
+            self.set_full_window().await?;
+            match plane {
+                RamPlane::BlackWhite => Command::AutoWriteBlackPattern(pattern),
+                RamPlane::Red => Command::AutoWriteRedPattern(pattern),
+            }
+            .execute(&mut self.interface)
+            .await?;
+            self.interface.busy_wait().await?;
+        } else {
+            let value = self.config.bit_order.convert(value);
+            let mut attempt = 0;
+            loop {
+                let mut result = self.set_full_window().await;
+                if result.is_ok() {
+                    result = Command::WriteRam(plane).execute(&mut self.interface).await;
+                }
+                if result.is_ok() {
+                    let data = iter::repeat_n(value, self.frame_size());
+                    result = self.interface.send_data_iter(data).await;
+                }
+                if !self.retry_ram_write(&result, &mut attempt).await {
+                    break result?;
+                }
+            }
+        }
+        if plane == RamPlane::BlackWhite {
+            self.ram_retained = true;
+        }
+        Ok(())
+    }
+
+    /// Fill the red RAM with `value` without refreshing the display.
+    pub async fn fill_red_ram(&mut self, value: u8) -> Result<(), Ssd1680Error<I::Error>> {
+        self.fill_ram(RamPlane::Red, value).await
+    }
+
+    /// Update a region of the display by writing the supplied B/W image to the controller.
+    ///
+    /// `image` holds the region only, laid out according to the configured `FrameLayout`. If a
+    /// full refresh interval is configured and has been reached, the whole display is refreshed
+    /// with a full refresh instead of a partial one.
+    pub async fn partial_update(
+        &mut self,
+        image: &[u8],
+        start_x_px: u16,
+        start_y_px: u16,
+        width_px: u16,
+        height_px: u16,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_window(image, start_x_px, start_y_px, width_px, height_px)?;
+        let full_refresh = self.begin_partial_update().await?;
+        self.write_window(
+            RamPlane::BlackWhite,
+            image,
+            start_x_px,
+            start_y_px,
+            width_px,
+            height_px,
+        )
+        .await?;
+        self.finish_partial_update(full_refresh).await
+    }
+
+    /// Update a region of the display, supplying the previously displayed image of the region.
+    ///
+    /// For flicker free partial updates the controller compares the previous image, held in the
+    /// red RAM, with the new image in the B/W RAM. This writes `previous` to the red RAM and
+    /// `image` to the B/W RAM before refreshing. Both hold the region only, as for
+    /// `partial_update`.
+    pub async fn partial_update_with_previous(
+        &mut self,
+        image: &[u8],
+        previous: &[u8],
+        start_x_px: u16,
+        start_y_px: u16,
+        width_px: u16,
+        height_px: u16,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_window(image, start_x_px, start_y_px, width_px, height_px)?;
+        self.check_window(previous, start_x_px, start_y_px, width_px, height_px)?;
+        let full_refresh = self.begin_partial_update().await?;
+        self.write_window(
+            RamPlane::Red,
+            previous,
+            start_x_px,
+            start_y_px,
+            width_px,
+            height_px,
+        )
+        .await?;
+        self.write_window(
+            RamPlane::BlackWhite,
+            image,
+            start_x_px,
+            start_y_px,
+            width_px,
+            height_px,
+        )
+        .await?;
+        self.finish_partial_update(full_refresh).await
+    }
+
+    /// Update several regions of the display with a single refresh.
+    ///
+    /// Each entry holds the B/W image of a region, laid out as for `partial_update`. The RAM
+    /// windows of all regions are written before a single refresh is triggered, rather than
+    /// performing one refresh cycle per region.
+    pub async fn partial_update_regions(
+        &mut self,
+        regions: &[(&[u8], Region)],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        for (image, region) in regions {
+            self.check_window(image, region.x, region.y, region.width, region.height)?;
+        }
+        let full_refresh = self.begin_partial_update().await?;
+        for (image, region) in regions {
+            self.write_window(
+                RamPlane::BlackWhite,
+                image,
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+            )
+            .await?;
+        }
+        self.finish_partial_update(full_refresh).await
+    }
+
+    /// Prepare the controller for writing partial update windows.
+    ///
+    /// Returns true if the full refresh interval has been reached and the update must be performed
+    /// as a full refresh.
+    pub(crate) async fn begin_partial_update(&mut self) -> Result<bool, Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        let interval_reached = self
+            .config
+            .full_refresh_interval
+            .is_some_and(|interval| self.partial_updates >= interval);
+
+        // Add hardware reset to prevent background color change
+        if self.config.partial_update_reset {
+            // The reset clears the registers and the waveform, restore them as when waking
+            self.chip_reset().await?;
+            self.write_registers().await?;
+            self.restore_waveform().await?;
+        }
+
+        let full_refresh = self.prepare_waveform(!interval_reached).await? || interval_reached;
+
+        // Lock the border to prevent flashing
+        let border = if full_refresh {
+            self.config.border
+        } else {
+            BorderWaveform::Vcom
+        };
+        Command::BorderWaveform(border)
+            .execute(&mut self.interface)
+            .await?;
+
+        Ok(full_refresh)
+    }
+
+    /// Write `image` to a window of one of the RAM planes without refreshing the display.
+    ///
+    /// `image` holds the window only, laid out as for `partial_update`.
+    pub async fn write_window(
+        &mut self,
+        plane: RamPlane,
+        image: &[u8],
+        start_x_px: u16,
+        start_y_px: u16,
+        width_px: u16,
+        height_px: u16,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.check_window(image, start_x_px, start_y_px, width_px, height_px)?;
+        self.ensure_awake().await?;
+        let start_x_byte = (start_x_px / 8) as u8;
+        let width_byte = (width_px / 8) as u8;
+        let end_x_byte = start_x_byte + width_byte - 1;
+        let end_y_px = start_y_px + height_px - 1;
+        let mut attempt = 0;
+        loop {
+            let mut result = self
+                .set_window((start_x_byte, end_x_byte), (start_y_px, end_y_px))
+                .await;
+            if result.is_ok() {
+                result = self.write_plane(plane, image).await;
+            }
+            if !self.retry_ram_write(&result, &mut attempt).await {
+                return result;
+            }
+        }
+    }
+
+    /// Set the RAM window to span `x` bytes and `y` gate lines, each given as (start, end), with
+    /// the address counter at the start.
+    async fn set_window(
+        &mut self,
+        x: (u8, u8),
+        y: (u16, u16),
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        Command::StartEndXPosition(x.0, x.1)
+            .execute(&mut self.interface)
+            .await?;
+        Command::StartEndYPosition(y.0, y.1)
+            .execute(&mut self.interface)
+            .await?;
+        Command::XAddress(x.0).execute(&mut self.interface).await?;
+        Command::YAddress(y.0).execute(&mut self.interface).await
+    }
+
+    /// Returns whether a RAM write that finished with `result` should be retried from the start
+    /// of its window, after waiting as the interface's retry policy asks.
+    async fn retry_ram_write(
+        &mut self,
+        result: &Result<(), Ssd1680Error<I::Error>>,
+        attempt: &mut u8,
+    ) -> bool {
+        if !matches!(result, Err(Ssd1680Error::Interface(_))) {
+            return false;
+        }
+        let retry = self.interface.retry_backoff(*attempt).await;
+        *attempt = attempt.saturating_add(1);
+        retry
+    }
+
+    /// Write frame data to one of the RAM planes, in the controller's bit order.
+    async fn write_plane(
+        &mut self,
+        plane: RamPlane,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        match self.config.bit_order {
+            BitOrder::MsbFirst => {
+                match plane {
+                    RamPlane::BlackWhite => BufCommand::WriteBlackData(data),
+                    RamPlane::Red => BufCommand::WriteRedData(data),
+                }
+                .execute(&mut self.interface)
+                .await
+            }
+            BitOrder::LsbFirst => {
+                Command::WriteRam(plane)
+                    .execute(&mut self.interface)
+                    .await?;
+                self.write_data(data).await
+            }
+        }
+    }
+
+    /// Send frame data following a RAM write command, in the controller's bit order.
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<I::Error>> {
+        match self.config.bit_order {
+            BitOrder::MsbFirst => self.interface.send_data(data).await,
+            BitOrder::LsbFirst => {
+                self.interface
+                    .send_data_iter(data.iter().map(|byte| byte.reverse_bits()))
+                    .await
+            }
+        }
+    }
+
+    /// Check that `frame` holds a whole frame.
+    fn check_frame(&self, frame: &[u8]) -> Result<(), Ssd1680Error<I::Error>> {
+        if frame.len() < self.frame_size() {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Check that a window is byte aligned and within the RAM, and that `image` covers it.
+    fn check_window(
+        &self,
+        image: &[u8],
+        start_x_px: u16,
+        start_y_px: u16,
+        width_px: u16,
+        height_px: u16,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        let aligned = start_x_px.is_multiple_of(8) && width_px.is_multiple_of(8);
+        let non_empty = width_px > 0 && height_px > 0;
+        let within_ram = u32::from(start_x_px) + u32::from(width_px)
+            <= u32::from(self.config.dimensions.cols)
+            && u32::from(start_y_px) + u32::from(height_px)
+                <= u32::from(self.config.dimensions.rows);
+        let image_size = usize::from(width_px / 8) * usize::from(height_px);
+        if !(aligned && non_empty && within_ram && image.len() >= image_size) {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Kick off the refresh for a partial update prepared with `begin_partial_update`.
+    pub(crate) async fn finish_partial_update(
+        &mut self,
+        full_refresh: bool,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        // Kick off the display update
+        let sequence = if full_refresh {
+            self.partial_updates = 0;
+            self.config.full_refresh_sequence
+        } else {
+            self.partial_updates = self.partial_updates.saturating_add(1);
+            self.config.partial_refresh_sequence
+        };
+        Command::UpdateDisplayOption2(sequence)
+            .execute(&mut self.interface)
+            .await?;
+        Command::UpdateDisplay.execute(&mut self.interface).await?;
+
+        self.auto_deep_sleep().await
+    }
+
+    /// Apply the temperature LUTs and temperature policy, if configured, before a refresh.
+    ///
+    /// Returns true if the policy forces a full refresh.
+    async fn prepare_waveform(&mut self, partial: bool) -> Result<bool, Ssd1680Error<I::Error>> {
+        if self.config.temperature_luts.is_empty() && self.temperature_policy.is_none() {
+            return Ok(false);
+        }
+
+        let temperature = self.measure_temperature().await?;
+        let adjustment = self
+            .temperature_policy
+            .map(|policy| policy.adjust(temperature, partial))
+            .unwrap_or_default();
+        if matches!(self.config.waveform_source, WaveformSource::Otp) {
+            self.apply_policy_waveform(adjustment.waveform_temperature)
+                .await?;
+        }
+        if let Some(lut) = self.config.lut_for_temperature(temperature) {
+            BufCommand::WriteLUT(lut)
+                .execute(&mut self.interface)
+                .await?;
+        }
+
+        Ok(adjustment.force_full_refresh)
+    }
+
+    /// Load the waveform for `requested` by the temperature policy, if it changed since the last
+    /// refresh.
+    ///
+    /// When the policy stops overriding the waveform, the override it replaced is restored, such
+    /// as that of `InitProfile::Fast` or `override_temperature`.
+    async fn apply_policy_waveform(
+        &mut self,
+        requested: Option<i8>,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        match (requested, self.policy_override) {
+            (Some(celsius), Some((previous, _))) if celsius == previous => {}
+            (Some(celsius), previous) => {
+                let replaced = match previous {
+                    Some((_, replaced)) => replaced,
+                    None => self.waveform_override,
+                };
+                self.load_waveform_for(celsius).await?;
+                self.policy_override = Some((celsius, replaced));
+            }
+            (None, Some((_, replaced))) => {
+                match replaced {
+                    Some(celsius) => self.load_waveform_for(celsius).await?,
+                    None => self.reload_waveform().await?,
+                }
+                self.policy_override = None;
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    /// Read the panel temperature in °C from the controller's temperature sensor.
+    ///
+    /// The controller samples the sensor before the register is read, so the reading is current.
+    /// This requires the SPI device to be able to read from the controller, see
+    /// `DisplayInterface::read_data`.
+    pub async fn read_temperature(&mut self) -> Result<i16, Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.measure_temperature().await
+    }
+
+    /// Use an LM75 compatible sensor connected to the controller's I2C pins for temperature
+    /// readings.
+    ///
+    /// The sensor's pointer is set to its temperature register, and the selection is kept across
+    /// `reset`.
+    pub async fn use_external_temperature_sensor(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.select_temperature_sensor(TemperatureSensor::External)
+            .await
+    }
+
+    /// Use the sensor built into the controller for temperature readings. This is the default,
+    /// unless another sensor was selected with `Builder::temperature_sensor`.
+    pub async fn use_internal_temperature_sensor(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.select_temperature_sensor(TemperatureSensor::Internal)
+            .await
+    }
+
+    async fn select_temperature_sensor(
+        &mut self,
+        sensor: TemperatureSensor,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.temperature_sensor = sensor;
+        self.set_temperature_sensor().await
+    }
+
+    /// Select the current temperature sensor on the controller.
+    async fn set_temperature_sensor(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        Command::TemperatureSensorSelection(self.temperature_sensor)
+            .execute(&mut self.interface)
+            .await?;
+        if self.temperature_sensor == TemperatureSensor::External {
+            // Address and pointer only, selecting the LM75 temperature register
+            Command::WriteExternalTemperatureSensor(0x00, 0x00, 0x00)
+                .execute(&mut self.interface)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Have the controller sample its temperature sensor and return the reading in °C.
+    async fn measure_temperature(&mut self) -> Result<i16, Ssd1680Error<I::Error>> {
+        Command::UpdateDisplayOption2(
+            UpdateSequence::ENABLE_CLOCK
+                | UpdateSequence::LOAD_TEMPERATURE
+                | UpdateSequence::DISABLE_CLOCK,
+        )
+        .execute(&mut self.interface)
+        .await?;
+        Command::UpdateDisplay.execute(&mut self.interface).await?;
+        self.interface.busy_wait().await?;
+
+        // Temperature register is a 12 bit two's complement value in 1/16 °C, left aligned
+        let mut data = [0u8; 2];
+        Command::ReadTemperatureSensor
+            .read(&mut self.interface, &mut data)
+            .await?;
+        Ok(i16::from_be_bytes(data) >> 8)
+    }
+
+    /// Read the display options and user ID from OTP.
+    ///
+    /// Manufacturing software can use the waveform version to identify which waveform revision
+    /// is burned into each panel. This requires the SPI device to be able to read from the
+    /// controller, see `DisplayInterface::read_data`.
+    pub async fn read_otp(&mut self) -> Result<Otp, Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        let options = self.read_display_options().await?;
+        let mut user_id = [0u8; 10];
+        Command::ReadUserId
+            .read(&mut self.interface, &mut user_id)
+            .await?;
+
+        let mut display_mode = [0u8; 5];
+        display_mode.copy_from_slice(&options[2..7]);
+        let mut waveform_version = [0u8; 4];
+        waveform_version.copy_from_slice(&options[7..11]);
+        Ok(Otp {
+            vcom_otp_selection: options[0],
+            vcom: options[1],
+            display_mode,
+            waveform_version,
+            user_id,
+        })
+    }
+
+    /// Program `vcom` into the VCOM OTP, so it is loaded at every reset.
+    ///
+    /// **OTP can only be programmed once.** Only factory provisioning tools should call this,
+    /// with a value obtained from `calibrate_vcom`. This will busy wait until programming has
+    /// completed.
+    #[cfg(feature = "otp-program")]
+    pub async fn program_vcom_otp(&mut self, vcom: u8) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        Command::WriteVCOM(vcom)
+            .execute(&mut self.interface)
+            .await?;
+        self.program_otp(Command::ProgramVCOMIntoOTP).await
+    }
+
+    /// Program the display option and user ID registers into OTP.
+    ///
+    /// Both registers are programmed together, each from 10 bytes laid out as in the data sheet.
+    /// **OTP can only be programmed once.** Only factory provisioning tools should call this.
+    /// This will busy wait until programming has completed.
+    #[cfg(feature = "otp-program")]
+    pub async fn program_display_option_otp(
+        &mut self,
+        display_option: &[u8; 10],
+        user_id: &[u8; 10],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        BufCommand::WriteDisplayOption(display_option)
+            .execute(&mut self.interface)
+            .await?;
+        BufCommand::WriteUserId(user_id)
+            .execute(&mut self.interface)
+            .await?;
+        self.program_otp(Command::ProgramOTP).await
+    }
+
+    /// Run an OTP program command with the analog block powered and the internal programming
+    /// voltage selected.
+    ///
+    /// The controller is returned to normal mode and powered off afterwards, even if programming
+    /// failed, so a failure doesn't leave the programming voltage applied. The first error is
+    /// returned.
+    #[cfg(feature = "otp-program")]
+    async fn program_otp(&mut self, program: Command) -> Result<(), Ssd1680Error<I::Error>> {
+        let programmed = self.run_otp_program(program).await;
+        let normal = Command::OTPProgramMode(OtpProgramMode::Normal)
+            .execute(&mut self.interface)
+            .await;
+        let powered_off = self.power_off().await;
+        programmed.and(normal).and(powered_off)
+    }
+
+    #[cfg(feature = "otp-program")]
+    async fn run_otp_program(&mut self, program: Command) -> Result<(), Ssd1680Error<I::Error>> {
+        self.power_on().await?;
+        Command::OTPProgramMode(OtpProgramMode::InternalVoltage)
+            .execute(&mut self.interface)
+            .await?;
+        program.execute(&mut self.interface).await?;
+        self.interface.busy_wait().await
+    }
+
+    /// Check that the booster reaches its target voltage and that VCI is above 2.3V.
+    ///
+    /// Marginal supplies show up as faint or blank images rather than errors, so this is useful
+    /// when debugging new hardware. The analog block is enabled for the detection and disabled
+    /// again afterwards. This will busy wait until detection has completed.
+    pub async fn check_power_rails(&mut self) -> Result<PowerRails, Ssd1680Error<I::Error>> {
+        self.power_on().await?;
+        Command::StartHVReadyDetection(0x00)
+            .execute(&mut self.interface)
+            .await?;
+        self.interface.busy_wait().await?;
+        Command::StartVCILevelDetection(VCI_DETECTION_THRESHOLD)
+            .execute(&mut self.interface)
+            .await?;
+        self.interface.busy_wait().await?;
+        let mut status = [0u8];
+        Command::ReadStatusBit
+            .read(&mut self.interface, &mut status)
+            .await?;
+        self.power_off().await?;
+
+        // The flags are cleared when the condition is met
+        Ok(PowerRails {
+            hv_ready: status[0] & 0b0010_0000 == 0,
+            vci_ok: status[0] & 0b0001_0000 == 0,
+        })
+    }
+
+    /// Have the controller calculate the CRC of its OTP content and return it.
+    ///
+    /// The data sheet specifies the CRC over the OTP, which holds the waveform and display
+    /// options, not over the RAM. Use `read_ram` to check that a frame was transferred intact.
+    /// This will busy wait until the calculation has completed.
+    pub async fn read_crc(&mut self) -> Result<u16, Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        Command::CalculateCRC.execute(&mut self.interface).await?;
+        self.interface.busy_wait().await?;
+        let mut data = [0u8; 2];
+        Command::ReadCRC
+            .read(&mut self.interface, &mut data)
+            .await?;
+        Ok(u16::from_be_bytes(data))
+    }
+
+    /// Returns whether the CRC of the OTP content matches `expected`, e.g. the value recorded
+    /// from a known good panel in production test. See `read_crc`.
+    pub async fn verify_crc(&mut self, expected: u16) -> Result<bool, Ssd1680Error<I::Error>> {
+        Ok(self.read_crc().await? == expected)
+    }
+
+    /// Measure the VCOM voltage of the panel.
+    ///
+    /// The controller holds the panel in VCOM sensing for `duration` + 1 seconds (at most 15)
+    /// before acquiring the value, which is latched into the VCOM register and returned. The
+    /// value is in the encoding of `Command::WriteVCOM`. This will busy wait until sensing has
+    /// completed.
+    pub async fn sense_vcom(&mut self, duration: u8) -> Result<u8, Ssd1680Error<I::Error>> {
+        if duration > MAX_VCOM_SENSE_DURATION {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        // Sensing requires the clock and analog block to be enabled
+        self.power_on().await?;
+        Command::VCOMSenseDuration(duration)
+            .execute(&mut self.interface)
+            .await?;
+        Command::EnterVCOMSensing
+            .execute(&mut self.interface)
+            .await?;
+        self.interface.busy_wait().await?;
+        let vcom = self.read_display_options().await?[1];
+        self.power_off().await?;
+        Ok(vcom)
+    }
+
+    /// Calibrate VCOM for the panel.
+    ///
+    /// Senses VCOM as `sense_vcom` and writes the result to the VCOM register, where it is kept
+    /// across `reset`. The value is returned so that it can be persisted and restored with
+    /// `Builder::vcom` on subsequent boots.
+    pub async fn calibrate_vcom(&mut self, duration: u8) -> Result<u8, Ssd1680Error<I::Error>> {
+        let vcom = self.sense_vcom(duration).await?;
+        Command::WriteVCOM(vcom)
+            .execute(&mut self.interface)
+            .await?;
+        self.config.vcom = Some(vcom);
+        Ok(vcom)
+    }
+
+    /// Read the display option registers (command 0x2D).
+    ///
+    /// The second byte holds the VCOM register.
+    async fn read_display_options(&mut self) -> Result<[u8; 11], Ssd1680Error<I::Error>> {
+        let mut data = [0u8; 11];
+        Command::ReadDisplayOption
+            .read(&mut self.interface, &mut data)
+            .await?;
+        Ok(data)
+    }
+
+    /// Fill both RAM planes so the next refresh shows `color` across the whole panel.
+    ///
+    /// The RAM is filled in hardware with the auto write pattern commands, so no image data is
+    /// transmitted. This is much faster than sending a blank frame, e.g. to blank the screen at
+    /// boot. The display is not refreshed.
+    pub async fn clear_ram(&mut self, color: Color) -> Result<(), Ssd1680Error<I::Error>> {
+        let (black_white, red) = match color {
+            Color::White => (0xFF, 0x00),
+            Color::Black => (0x00, 0x00),
+            Color::Red => (0xFF, 0xFF),
+        };
+        self.fill_ram(RamPlane::BlackWhite, black_white).await?;
+        self.fill_ram(RamPlane::Red, red).await
+    }
+
+    /// Clear ghosting by flashing the panel between all black and all white `cycles` times.
+    ///
+    /// Panel vendors recommend this to clear burn-in after a static image has been shown for a
+    /// long time. The RAM is filled in hardware with the auto write pattern commands, so no image
+    /// data is transmitted. Each cycle performs two full refreshes and this will busy wait until
+    /// they have completed. The display is left white, so the image must be updated afterwards.
+    pub async fn deghost(&mut self, cycles: u8) -> Result<(), Ssd1680Error<I::Error>> {
+        for _ in 0..cycles {
+            for value in [0x00, 0xFF] {
+                self.fill_ram(RamPlane::BlackWhite, value).await?;
+                Command::UpdateDisplayOption2(self.config.full_refresh_sequence)
+                    .execute(&mut self.interface)
+                    .await?;
+                Command::UpdateDisplay.execute(&mut self.interface).await?;
+            }
+        }
+        self.partial_updates = 0;
+        self.interface.busy_wait().await?;
+        Ok(())
+    }
+
+    /// Fill the B/W RAM with a test pattern and perform a full refresh.
+    ///
+    /// This needs no frame buffer or drawing code, so wiring and waveform quality can be checked
+    /// as soon as the display is reset. The checkerboard is written in hardware with the auto
+    /// write pattern command, the other patterns are generated a byte at a time. This will busy
+    /// wait until the refresh has completed.
+    pub async fn draw_test_pattern(
+        &mut self,
+        pattern: Pattern,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        if pattern == Pattern::Checkerboard {
+            self.ensure_awake().await?;
+            self.interface.busy_wait().await?;
+            self.set_full_window().await?;
+            Command::AutoWriteBlackPattern(AUTO_WRITE_CHECKERBOARD)
+                .execute(&mut self.interface)
+                .await?;
+            self.interface.busy_wait().await?;
+            self.ram_retained = true;
+        } else {
+            let (cols_bytes, rows) = (usize::from(self.cols_as_bytes()), self.rows());
+            let layout = self.config.frame_layout;
+            let bit_order = self.config.bit_order;
+            let bytes = (0..self.frame_size()).map(move |index| {
+                let (x_byte, y) = match layout {
+                    FrameLayout::RowMajor => (index % cols_bytes, index / cols_bytes),
+                    FrameLayout::ColumnMajor => {
+                        (index / usize::from(rows), index % usize::from(rows))
+                    }
+                };
+                // Generated most significant bit first, undoing the conversion when written
+                bit_order.convert(pattern_byte(pattern, x_byte, y, rows))
+            });
+            self.write_ram_from_iter(RamPlane::BlackWhite, bytes)
+                .await?;
+        }
+        self.refresh().await
+    }
+
+    /// Set the RAM window to cover the whole display.
+    ///
+    /// The address counter starts at the first gate line, as for the windows of partial updates,
+    /// so full frames and windows place rows alike.
+    async fn set_full_window(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.set_window(
+            (0, self.cols_as_bytes() - 1),
+            (0, self.config.dimensions.rows - 1),
+        )
+        .await
+    }
+
+    /// Reload the waveform LUT from OTP.
+    ///
+    /// Reverts a LUT previously written with `BufCommand::WriteLUT` to the factory waveform
+    /// without performing a full reset. This will busy wait until the load has completed.
+    pub async fn reload_otp_waveform(
+        &mut self,
+        mode: LutLoadMode,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.run_sequence(mode.into()).await
+    }
+
+    /// Enter deep sleep mode.
+    ///
+    /// This puts the display controller into a low power mode, preserving the RAM. `reset` or
+    /// `wake` must be called to wake it from sleep, other operations return
+    /// `Ssd1680Error::Asleep`.
+    pub async fn deep_sleep(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.deep_sleep_mode(DeepSleepMode::PreserveRAM).await
+    }
+
+    /// Enter deep sleep with the given mode.
+    ///
+    /// `DeepSleepMode::DiscardRAM` gives the lowest current, but the frame must be written again
+    /// after `reset`. See `ram_retained`.
+    pub async fn deep_sleep_mode(
+        &mut self,
+        mode: DeepSleepMode,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        if self.state == ControllerState::Asleep {
+            // The controller ignores commands while asleep, and BUSY stays high
+            return Ok(());
+        }
+        self.interface.busy_wait().await?;
+        Command::DeepSleepMode(mode)
+            .execute(&mut self.interface)
+            .await?;
+        match mode {
+            DeepSleepMode::Normal => {}
+            DeepSleepMode::PreserveRAM => self.state = ControllerState::Asleep,
+            DeepSleepMode::DiscardRAM => {
+                self.state = ControllerState::Asleep;
+                self.ram_retained = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the controller is busy, sampling the BUSY pin once without waiting.
+    ///
+    /// This allows the completion of a refresh to be polled, rather than waiting for it. The
+    /// controller also reports busy while in deep sleep.
+    pub fn is_busy(&mut self) -> Result<bool, Ssd1680Error<I::Error>> {
+        self.interface.is_busy()
+    }
+
+    /// Returns the state of the controller.
+    pub fn state(&self) -> ControllerState {
+        self.state
+    }
+
+    /// Returns an error if the controller is asleep, since it would never release BUSY.
+    fn check_awake(&self) -> Result<(), Ssd1680Error<I::Error>> {
+        match self.state {
+            ControllerState::Asleep => Err(Ssd1680Error::Asleep),
+            _ => Ok(()),
+        }
+    }
+
+    /// As `check_awake`, but wakes the controller if it was put to sleep by `auto_deep_sleep`.
+    async fn ensure_awake(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        if self.state == ControllerState::Asleep && self.config.auto_deep_sleep.is_some() {
+            self.wake().await?;
+        }
+        self.check_awake()
+    }
+
+    /// Enter deep sleep once the refresh completes, if configured with `auto_deep_sleep`.
+    async fn auto_deep_sleep(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        if let Some(mode) = self.config.auto_deep_sleep {
+            self.deep_sleep_mode(mode).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the B/W RAM holds a complete frame written by the driver.
+    ///
+    /// This is false until a frame is written, and after deep sleep with
+    /// `DeepSleepMode::DiscardRAM`. When true, the existing RAM contents can be refreshed after
+    /// `reset` without sending the frame again.
+    pub fn ram_retained(&self) -> bool {
+        self.ram_retained
+    }
+
+    /// Disable the analog block and clock signal.
+    ///
+    /// This cuts the analog power between refreshes, reducing the standby current without
+    /// entering deep sleep. The RAM and configuration are kept, and the refresh sequences enable
+    /// the analog block again as needed, so no wake up is required. This will busy wait until
+    /// the sequence has completed.
+    pub async fn power_off(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.run_sequence(UpdateSequence::POWER_OFF).await
+    }
+
+    /// Enable the clock signal and analog block.
+    ///
+    /// The refresh sequences power on the analog block themselves, but powering on ahead of time
+    /// allows the supplies to settle before a refresh is started. This will busy wait until the
+    /// sequence has completed.
+    pub async fn power_on(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.run_sequence(UpdateSequence::POWER_ON).await
+    }
+
+    async fn run_sequence(
+        &mut self,
+        sequence: UpdateSequence,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        Command::UpdateDisplayOption2(sequence)
+            .execute(&mut self.interface)
+            .await?;
+        Command::UpdateDisplay.execute(&mut self.interface).await?;
+        self.interface.busy_wait().await?;
+        Ok(())
+    }
+
+    /// Send a command to the controller after waiting for it to not be busy.
+    ///
+    /// This is an escape hatch for commands the driver doesn't manage. Commands that change state
+    /// the driver relies on, such as the data entry mode or RAM windows, may be overwritten by
+    /// later operations or confuse them.
+    pub async fn command(&mut self, command: Command) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        command.execute(&mut self.interface).await
+    }
+
+    /// Send a raw command byte and its data after waiting for the controller to not be busy.
+    ///
+    /// For commands not modelled by `Command`. The same caveats as for `command` apply.
+    pub async fn write_raw(
+        &mut self,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.interface.send_command_with_data(command, data).await
+    }
+
+    /// Send a raw command byte and read its response into `data`, such as a status or OTP read.
+    ///
+    /// This requires the SPI device to be able to read from the controller, see
+    /// `DisplayInterface::read_data`.
+    pub async fn read_raw(
+        &mut self,
+        command: u8,
+        data: &mut [u8],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.interface.busy_wait().await?;
+        self.interface.send_command(command).await?;
+        self.interface.read_data(data).await
+    }
+
+    /// Change the border waveform.
+    ///
+    /// The border is updated by the next full refresh. See `Builder::border`.
+    pub async fn set_border(
+        &mut self,
+        border: BorderWaveform,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.config.border = border;
+        self.interface.busy_wait().await?;
+        Command::BorderWaveform(border)
+            .execute(&mut self.interface)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the configured border waveform.
+    pub fn border(&self) -> BorderWaveform {
+        self.config.border
+    }
+
+    /// Invert the black/white RAM when displaying it.
+    ///
+    /// Takes effect on the next refresh without re-sending the frame buffer, so calling `refresh`
+    /// is enough to flip between light and dark modes. The inversion is kept across `reset`.
+    /// Partial updates compare against the unaltered red RAM, so perform a full refresh after
+    /// toggling.
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), Ssd1680Error<I::Error>> {
+        self.ensure_awake().await?;
+        self.inverted = inverted;
+        self.interface.busy_wait().await?;
+        self.write_display_options().await
+    }
+
+    /// Returns whether the black/white RAM is inverted when displayed.
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    async fn write_display_options(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        let black_ram_option = match (self.config.black_ram_option, self.inverted) {
+            (RamOption::Normal, true) => RamOption::Invert,
+            (RamOption::Invert, true) => RamOption::Normal,
+            (option, _) => option,
+        };
+        Command::UpdateDisplayOption1(
+            black_ram_option,
+            self.config.red_ram_option,
+            self.config.source_option,
+        )
+        .execute(&mut self.interface)
+        .await?;
+        Ok(())
+    }
+
+    /// Set the usage of the panel, used to select source voltages from the aging compensation
+    /// table.
+    ///
+    /// The new voltages are applied by the next `reset`. See `Builder::aging_compensation`.
+    pub fn set_panel_usage(&mut self, usage: u32) {
+        self.panel_usage = usage;
+    }
+
+    /// Set the policy adjusting refreshes according to the panel temperature, replacing the one
+    /// from `Builder::temperature_policy`.
+    ///
+    /// Unlike the builder, this accepts policies that aren't `Sync`, e.g. ones keeping state in a
+    /// `Cell`. The policy is consulted from the next refresh.
+    pub fn set_temperature_policy(&mut self, policy: &'a dyn TemperaturePolicy) {
+        self.temperature_policy = Some(policy);
+    }
+
+    /// Returns the source voltages applied from the aging compensation table, if any.
+    ///
+    /// This is intended for telemetry. `None` until voltages have been applied by `reset`.
+    pub fn source_voltages(&self) -> Option<SourceVoltages> {
+        self.source_voltages
+    }
+
+    /// Returns the number of partial updates performed since the last full refresh.
+    pub fn partial_updates_since_full_refresh(&self) -> u16 {
+        self.partial_updates
+    }
+
+    /// Returns the refresh and transfer counters of this display.
+    ///
+    /// The counters start at zero when the `Display` is created and are not changed by `reset`.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            partial_refreshes_since_full: self.partial_updates,
+            ..self.interface.stats
+        }
+    }
+
+    /// Set the counters returned by `stats` back to zero, e.g. after reporting them.
+    pub fn reset_stats(&mut self) {
+        self.interface.stats = Stats::default();
+    }
+
+    /// Returns the size in bytes of a frame buffer for the whole display.
+    pub fn frame_size(&self) -> usize {
+        let buf_size = self.rows() as usize * self.cols() as usize;
+        let limit_adder = if buf_size.is_multiple_of(8) { 0 } else { 1 };
+        (buf_size / 8) + limit_adder
+    }
+
+    /// Returns the number of rows the display has.
+    pub fn rows(&self) -> u16 {
+        self.config.dimensions.rows
+    }
+
+    /// Returns the number of columns the display has.
+    pub fn cols(&self) -> u8 {
+        self.config.dimensions.cols
+    }
+
+    pub fn cols_as_bytes(&self) -> u8 {
+        self.config.dimensions.cols / 8
+    }
+
+    /// Returns the layout of the buffers passed to `update` and `partial_update`.
+    pub fn frame_layout(&self) -> FrameLayout {
+        self.config.frame_layout
+    }
+
+    /// Returns the order of the pixels within each byte of the buffers passed to `update` and
+    /// `partial_update`.
+    pub fn bit_order(&self) -> BitOrder {
+        self.config.bit_order
+    }
+
+    /// Returns the current rotation.
+    ///
+    /// This is the rotation the display was configured with, unless it has been changed with
+    /// `GraphicDisplay::set_rotation`.
+    pub fn rotation(&self) -> Rotation {
+        self.config.rotation
+    }
+
+    /// Returns the offset added to drawing coordinates. See `Builder::drawing_offset`.
+    pub fn drawing_offset(&self) -> (i16, i16) {
+        self.config.drawing_offset
+    }
+
+    #[cfg(feature = "graphics-core")]
+    pub(crate) fn set_rotation(&mut self, rotation: Rotation) {
+        self.config.rotation = rotation;
+    }
+}
+
+impl<'a, I> PowerDown for Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Error = Ssd1680Error<I::Error>;
+
+    async fn power_down(&mut self, action: DropAction) -> Result<(), Self::Error> {
+        match action {
+            DropAction::Nothing => Ok(()),
+            DropAction::DeepSleep => self.deep_sleep().await,
+            DropAction::PowerOff => self.power_off().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn power_guard_acts_when_finished() {
+        use crate::{mock::MockInterface, test_util::builder};
+
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(MockInterface::<64>::new(), config);
+        display.reset().await.unwrap();
+        let mut guard = PowerGuard::new(display, DropAction::Nothing);
+        guard.set_action(DropAction::DeepSleep);
+        let display = guard.finish().await.unwrap();
+        assert_eq!(display.state(), ControllerState::Asleep);
+        assert_eq!(display.interface().commands().last(), Some(0x10));
+
+        let guard = PowerGuard::new(display, DropAction::PowerOff);
+        let display = guard.into_inner();
+        assert_eq!(display.state(), ControllerState::Asleep);
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn power_down_follows_early_returns() {
+        use crate::{mock::MockInterface, test_util::builder};
+
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(MockInterface::<256>::new(), config);
+        let result = with_power_down(&mut display, DropAction::DeepSleep, async |display| {
+            display.reset().await?;
+            display.update(&[0xFF; 4]).await?;
+            Ok(())
+        })
+        .await;
+        assert_eq!(result, Err(Ssd1680Error::InvalidArgument));
+        assert_eq!(display.state(), ControllerState::Asleep);
+        assert_eq!(display.interface().commands().last(), Some(0x10));
+
+        let temperature = with_power_down(&mut display, DropAction::PowerOff, async |display| {
+            display.reset().await?;
+            display.read_temperature().await
+        })
+        .await;
+        assert!(temperature.is_ok());
+        assert!(display.interface().data_for(0x22).eq([0x03]));
+        assert_eq!(display.interface().commands().last(), Some(0x20));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn wake_restores_only_what_the_reset_cleared() {
+        use crate::{config::InitProfile, mock::MockInterface, test_util::builder};
+
+        let config = builder(8, 8)
+            .init_profile(InitProfile::Fast)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<256>::new(), config);
+        display.reset().await.unwrap();
+        display.deep_sleep().await.unwrap();
+        let (mut interface, config) = display.release();
+        interface.clear();
+
+        let mut display = Display::new(interface, config);
+        display.waveform_override = Some(100);
+        display.wake().await.unwrap();
+        let mut commands = display.interface().commands();
+        // No software reset, RAM window or second waveform load
+        assert!(!commands.any(|command| [0x12, 0x44, 0x45].contains(&command)));
+        let updates = display
+            .interface()
+            .commands()
+            .filter(|&command| command == 0x20);
+        assert_eq!(updates.count(), 1);
+        assert!(display.interface().data_for(0x22).eq([0x91]));
+        assert!(display.interface().data_for(0x1A).eq([0x64, 0x00]));
+        assert!(display.interface().data_for(0x01).eq([0x07, 0x00, 0x00]));
+        assert_eq!(display.state(), ControllerState::Ready);
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn temperature_policy_restores_the_waveform_it_replaced() {
+        use crate::{
+            config::{InitProfile, RefreshAdjustment, TemperaturePolicy},
+            mock::MockInterface,
+            test_util::builder,
+        };
+
+        struct Hot;
+
+        impl TemperaturePolicy for Hot {
+            fn adjust(&self, temperature: i16, _partial: bool) -> RefreshAdjustment {
+                RefreshAdjustment {
+                    force_full_refresh: false,
+                    waveform_temperature: (temperature > 40).then_some(50),
+                }
+            }
+        }
+
+        let policy = Hot;
+        let config = builder(8, 8)
+            .init_profile(InitProfile::Fast)
+            .temperature_policy(&policy)
+            .build()
+            .expect("invalid config");
+        // Measured temperatures of 0, 50 and 0 °C
+        let readings = [0x00, 0x00, 0x32, 0x00, 0x00, 0x00];
+        let interface = MockInterface::<512>::new().read_data(&readings);
+        let mut display = Display::new(interface, config);
+        display.reset().await.unwrap();
+        let waveform_loads = |display: &Display<'_, MockInterface<'_, 512>>| {
+            display
+                .interface()
+                .commands()
+                .filter(|&command| command == 0x1A)
+                .count()
+        };
+        assert_eq!(waveform_loads(&display), 1);
+
+        // The policy makes no adjustment, so the fast waveform is kept
+        display.refresh().await.unwrap();
+        assert_eq!(waveform_loads(&display), 1);
+
+        display.refresh().await.unwrap();
+        assert_eq!(waveform_loads(&display), 2);
+        assert!(display.interface().data_for(0x1A).eq([0x32, 0x00]));
+
+        display.refresh().await.unwrap();
+        assert_eq!(waveform_loads(&display), 3);
+        assert!(display.interface().data_for(0x1A).eq([0x64, 0x00]));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn temperature_policies_need_not_be_sync() {
+        use crate::{
+            config::{RefreshAdjustment, TemperaturePolicy},
+            mock::MockInterface,
+            test_util::builder,
+        };
+        use core::cell::Cell;
+
+        /// Counts the refreshes it is consulted for.
+        struct Counting {
+            refreshes: Cell<u8>,
+        }
+
+        impl TemperaturePolicy for Counting {
+            fn adjust(&self, _temperature: i16, _partial: bool) -> RefreshAdjustment {
+                self.refreshes.set(self.refreshes.get() + 1);
+                RefreshAdjustment::default()
+            }
+        }
+
+        let policy = Counting {
+            refreshes: Cell::new(0),
+        };
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(MockInterface::<512>::new(), config);
+        display.set_temperature_policy(&policy);
+        display.reset().await.unwrap();
+        display.refresh().await.unwrap();
+        display.refresh().await.unwrap();
+        assert_eq!(policy.refreshes.get(), 2);
+    }
+
+    #[cfg(all(feature = "mock", feature = "otp-program"))]
+    #[futures_test::test]
+    async fn failed_otp_programming_returns_to_normal_mode() {
+        use crate::{mock::MockInterface, test_util::builder};
+        use core::convert::Infallible;
+
+        /// Times out waiting for the VCOM to be programmed.
+        struct Unprogrammable(MockInterface<'static, 128>);
+
+        impl DisplayInterface for Unprogrammable {
+            type Error = Infallible;
+
+            async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<Infallible>> {
+                self.0.send_command(command).await
+            }
+
+            async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<Infallible>> {
+                self.0.send_data(data).await
+            }
+
+            async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<Infallible>> {
+                match self.0.commands().last() {
+                    Some(0x2A) => Err(Ssd1680Error::BusyTimeout { waited_ms: 0 }),
+                    _ => self.0.busy_wait().await,
+                }
+            }
+        }
+
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(Unprogrammable(MockInterface::new()), config);
+        let result = display.program_vcom_otp(0x50).await;
+        assert!(matches!(result, Err(Ssd1680Error::BusyTimeout { .. })));
+        let interface = &display.interface().0;
+        assert!(interface
+            .commands()
+            .eq([0x2C, 0x22, 0x20, 0x39, 0x2A, 0x39, 0x22, 0x20]));
+        assert!(interface.data_for(0x39).eq([0x00]));
+        assert!(interface.data_for(0x22).eq([0x03]));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn external_sensor_is_set_up_before_the_waveform_is_loaded() {
+        use crate::{mock::MockInterface, test_util::builder};
+
+        let config = builder(8, 8)
+            .temperature_sensor(TemperatureSensor::External)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<256>::new(), config);
+        let first = |display: &Display<'_, MockInterface<'_, 256>>, command| {
+            display
+                .interface()
+                .commands()
+                .position(|sent| sent == command)
+        };
+        display.reset().await.unwrap();
+        assert!(first(&display, 0x1C).is_some());
+        assert!(first(&display, 0x1C) < first(&display, 0x20));
+
+        let (_, config) = display.release();
+        let mut display = Display::new(MockInterface::<256>::new(), config);
+        display.splash(&[0xFF; 8]).await.unwrap();
+        assert!(first(&display, 0x1C).is_some());
+        assert!(first(&display, 0x1C) < first(&display, 0x20));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn custom_waveform_is_not_replaced_from_otp() {
+        use crate::{config::LUT_SIZE, mock::MockInterface, test_util::builder, WaveformSource};
+
+        let lut = [0x5Au8; LUT_SIZE];
+        let config = builder(3, 8)
+            .waveform_source(WaveformSource::Custom(&lut))
+            .full_refresh_sequence(UpdateSequence::LOAD_AND_DISPLAY_MODE_1)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<512>::new(), config);
+        assert!(display.reset().await.is_ok());
+        assert!(display.interface().data_for(0x32).eq(lut.into_iter()));
+        assert!(!display
+            .interface()
+            .commands()
+            .any(|command| command == 0x22));
+
+        assert!(display.update(&[0xFF; 3]).await.is_ok());
+        assert!(display.interface().data_for(0x22).eq([0xC7].into_iter()));
+        assert!(display.splash(&[0xFF; 3]).await.is_ok());
+        assert!(display.interface().data_for(0x22).eq([0xC7].into_iter()));
+        assert!(display.interface().data_for(0x32).eq(lut.into_iter()));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn configured_temperature_sensor_is_selected_on_reset() {
+        use crate::{command::TemperatureSensor, mock::MockInterface, test_util::builder};
+
+        let config = builder(3, 8)
+            .temperature_sensor(TemperatureSensor::External)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<64>::new(), config);
+        assert!(display.reset().await.is_ok());
+
+        let (interface, _) = display.release();
+        assert!(interface.data_for(0x18).eq([0x48].into_iter()));
+        assert!(interface.commands().any(|command| command == 0x1C));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn inversion_applies_to_the_black_white_ram() {
+        use crate::{mock::MockInterface, test_util::builder};
+
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(MockInterface::<64>::new(), config);
+        display.reset().await.unwrap();
+        assert!(display.interface().data_for(0x21).eq([0x00, 0x80]));
+
+        display.set_inverted(true).await.unwrap();
+        assert!(display.is_inverted());
+        assert!(display.interface().data_for(0x21).eq([0x08, 0x80]));
+        display.set_inverted(false).await.unwrap();
+        assert!(display.interface().data_for(0x21).eq([0x00, 0x80]));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn ram_and_source_options_are_sent() {
+        use crate::{
+            command::{RamOption, SourceOption},
+            mock::MockInterface,
+            test_util::builder,
+        };
+
+        let config = builder(8, 8)
+            .ram_options(RamOption::Bypass, RamOption::Invert)
+            .source_option(SourceOption::SourceFromS0ToS175)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<64>::new(), config);
+        display.reset().await.unwrap();
+        // Red in the high nibble, black/white in the low one
+        assert!(display.interface().data_for(0x21).eq([0x84, 0x00]));
+
+        // A bypassed black/white RAM is not inverted
+        display.set_inverted(true).await.unwrap();
+        assert!(display.interface().data_for(0x21).eq([0x84, 0x00]));
+
+        let config = builder(8, 8)
+            .ram_options(RamOption::Invert, RamOption::Normal)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<64>::new(), config);
+        display.reset().await.unwrap();
+        assert!(display.interface().data_for(0x21).eq([0x08, 0x80]));
+        display.set_inverted(true).await.unwrap();
+        assert!(display.interface().data_for(0x21).eq([0x00, 0x80]));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn partial_update_reset_keeps_the_configuration() {
+        use crate::{config::LUT_SIZE, mock::MockInterface, test_util::builder, WaveformSource};
+
+        let lut = [0x5Au8; LUT_SIZE];
+        let config = builder(8, 8)
+            .waveform_source(WaveformSource::Custom(&lut))
+            .vcom_voltage(-1_600)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(MockInterface::<512>::new(), config);
+        display.reset().await.unwrap();
+        display.set_inverted(true).await.unwrap();
+        display
+            .partial_update(&[0xFF; 8], 0, 0, 8, 8)
+            .await
+            .unwrap();
+
+        // Written again after the reset of the partial update
+        let interface = display.interface();
+        let sent_twice =
+            |command| interface.commands().filter(|&sent| sent == command).count() == 2;
+        assert!(sent_twice(0x32));
+        assert!(sent_twice(0x2C));
+        assert!(interface.data_for(0x32).eq(lut));
+        assert!(interface.data_for(0x2C).eq([0x40]));
+        // The inversion is restored too
+        assert!(interface.data_for(0x21).eq([0x08, 0x80]));
+        let before_ram_write = interface.commands().take_while(|&sent| sent != 0x24);
+        assert_eq!(before_ram_write.filter(|&sent| sent == 0x32).count(), 2);
+    }
+
+    #[futures_test::test]
+    async fn test_patterns_are_generated_a_byte_at_a_time() {
+        use crate::test_util::{builder, NullInterface};
+
+        // Squares of 2 bytes by 16 rows, starting with black
+        assert_eq!(pattern_byte(Pattern::Checkerboard, 1, 15, 32), 0x00);
+        assert_eq!(pattern_byte(Pattern::Checkerboard, 2, 0, 32), 0xFF);
+        assert_eq!(pattern_byte(Pattern::Checkerboard, 0, 16, 32), 0xFF);
+        assert_eq!(pattern_byte(Pattern::Checkerboard, 3, 31, 32), 0x00);
+        assert_eq!(pattern_byte(Pattern::Stripes, 3, 5, 32), 0xAA);
+        // White on the first row and black on the last
+        assert_eq!(pattern_byte(Pattern::Gradient, 0, 0, 32), 0xFF);
+        assert_eq!(pattern_byte(Pattern::Gradient, 3, 31, 32), 0x00);
+        let middle = pattern_byte(Pattern::Gradient, 0, 16, 32);
+        assert_eq!(middle.count_zeros(), 4);
+
+        let config = builder(32, 32).build().expect("invalid config");
+        let mut display = Display::new(NullInterface, config);
+        for pattern in [Pattern::Checkerboard, Pattern::Stripes, Pattern::Gradient] {
+            assert!(display.draw_test_pattern(pattern).await.is_ok());
+        }
+        assert_eq!(display.stats().refreshes, 3);
+    }
+
+    #[cfg(feature = "simulator")]
+    #[futures_test::test]
+    async fn test_patterns_fill_the_panel() {
+        use crate::{
+            graphics::{BLACK, WHITE},
+            simulator::SimulatorInterface,
+            test_util::builder,
+        };
+
+        let config = builder(32, 32).build().expect("invalid config");
+        let dimensions = Dimensions { rows: 32, cols: 32 };
+        let mut display = Display::new(SimulatorInterface::new(dimensions), config);
+        display.reset().await.unwrap();
+
+        display
+            .draw_test_pattern(Pattern::Checkerboard)
+            .await
+            .unwrap();
+        let simulator = display.interface();
+        assert_eq!(simulator.pixel(0, 0), Some(BLACK));
+        assert_eq!(simulator.pixel(16, 0), Some(WHITE));
+        assert_eq!(simulator.pixel(15, 16), Some(WHITE));
+        assert_eq!(simulator.pixel(31, 31), Some(BLACK));
+        // The controller writes the bytes the pattern is generated with
+        for y in 0..32 {
+            for x in 0..32 {
+                let byte = pattern_byte(Pattern::Checkerboard, x / 8, y, 32);
+                let expected = if byte & 0x80 >> (x % 8) == 0 {
+                    BLACK
+                } else {
+                    WHITE
+                };
+                assert_eq!(simulator.pixel(x as u32, y as u32), Some(expected));
+            }
+        }
+
+        display.draw_test_pattern(Pattern::Stripes).await.unwrap();
+        let simulator = display.interface();
+        assert_eq!(simulator.pixel(0, 5), Some(WHITE));
+        assert_eq!(simulator.pixel(1, 5), Some(BLACK));
+        assert_eq!(simulator.pixel(30, 31), Some(WHITE));
+
+        display.draw_test_pattern(Pattern::Gradient).await.unwrap();
+        assert_eq!(display.interface().refreshes(), 3);
     }
 }
@@ -0,0 +1,39 @@
+//! Errors reported by the driver.
+
+/// An error from the driver.
+///
+/// All fallible `Display` and `GraphicDisplay` methods return this, so applications have one
+/// error type to match on. `E` is the error type of the `DisplayInterface`, e.g. the SPI error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Ssd1680Error<E> {
+    /// The interface to the controller failed.
+    Interface(E),
+    /// The controller did not release the BUSY pin in time.
+    ///
+    /// This usually means the controller has hung or the panel is disconnected, and a hardware
+    /// reset is the appropriate recovery.
+    BusyTimeout {
+        /// How long the driver waited for the BUSY pin, in milliseconds
+        waited_ms: u32,
+    },
+    /// Reading the BUSY pin failed.
+    BusyPinError,
+    /// Setting the DC pin failed.
+    DcPinError,
+    /// Setting the RESET pin failed.
+    ResetPinError,
+    /// An argument was out of range, such as a window outside the display or a buffer that is
+    /// too short.
+    InvalidArgument,
+    /// The controller is in deep sleep. Call `Display::reset` or `Display::wake` first.
+    Asleep,
+    /// The `DisplayInterface` does not support the operation, e.g. reading from the controller.
+    Unsupported,
+}
+
+impl<E> From<E> for Ssd1680Error<E> {
+    fn from(error: E) -> Self {
+        Ssd1680Error::Interface(error)
+    }
+}
@@ -0,0 +1,269 @@
+//! A frame buffer decoupled from the hardware, enabled by the `graphics` or `graphics-core`
+//! feature.
+//!
+//! A [Frame] is an embedded-graphics `DrawTarget` that does not hold the `Display`, so it can be
+//! rendered anywhere, e.g. in a different task or while the previous refresh is still running,
+//! and is then shown with `Display::present`. Two frames make a double-buffered UI: one is drawn
+//! while the other is presented.
+//!
+//! ```
+//! use embedded_graphics::{
+//!     prelude::*,
+//!     primitives::{PrimitiveStyle, Rectangle},
+//! };
+//! use ssd1680::{
+//!     frame::Frame,
+//!     graphics::{BLACK, WHITE},
+//!     Dimensions, Rotation,
+//! };
+//!
+//! let dimensions = Dimensions { rows: 16, cols: 16 };
+//! let mut frame = Frame::new([0u8; 32], dimensions, Rotation::Rotate0);
+//! frame.clear(WHITE).unwrap();
+//! Rectangle::new(Point::new(0, 0), Size::new(8, 2))
+//!     .into_styled(PrimitiveStyle::with_fill(BLACK))
+//!     .draw(&mut frame)
+//!     .unwrap();
+//! assert_eq!(frame.buffer()[..4], [0x00, 0xFF, 0x00, 0xFF]);
+//! ```
+
+#[cfg(feature = "std")]
+use crate::snapshot::Snapshot;
+use crate::{
+    display::{BitOrder, Dimensions, FrameLayout, Rotation},
+    graphics::{fill_region, rotated_region, rotation, BLACK, WHITE},
+};
+use core::convert::Infallible;
+use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+
+/// A B/W frame buffer with the dimensions, rotation and layout of a display.
+pub struct Frame<B> {
+    buffer: B,
+    rows: u16,
+    cols: u8,
+    rotation: Rotation,
+    layout: FrameLayout,
+    bit_order: BitOrder,
+}
+
+impl<B> Frame<B>
+where
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create a frame drawing into `buffer`, which should be `rows` * `cols` / 8 bytes long.
+    ///
+    /// The frame uses `FrameLayout::RowMajor` and `BitOrder::MsbFirst`, see `layout` and
+    /// `bit_order` for displays configured otherwise.
+    /// `Display::new_frame` creates a frame matching a display.
+    pub fn new(buffer: B, dimensions: Dimensions, rotation: Rotation) -> Self {
+        Frame {
+            buffer,
+            rows: dimensions.rows,
+            cols: dimensions.cols,
+            rotation,
+            layout: FrameLayout::RowMajor,
+            bit_order: BitOrder::MsbFirst,
+        }
+    }
+
+    /// Set the layout of the buffer, which must match the `frame_layout` of the display.
+    pub fn layout(self, layout: FrameLayout) -> Self {
+        Self { layout, ..self }
+    }
+
+    /// Set the order of the pixels within each byte, which must match the `bit_order` of the
+    /// display.
+    pub fn bit_order(self, bit_order: BitOrder) -> Self {
+        Self { bit_order, ..self }
+    }
+
+    /// Returns the packed frame, as passed to `Display::update`.
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+
+    /// Returns the packed frame for modification.
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer.as_mut()
+    }
+
+    /// Release the buffer.
+    pub fn release(self) -> B {
+        self.buffer
+    }
+
+    /// Returns the number of rows of the display the frame is for.
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    /// Returns the number of columns of the display the frame is for.
+    pub fn cols(&self) -> u8 {
+        self.cols
+    }
+
+    /// Returns the layout of the buffer.
+    pub fn frame_layout(&self) -> FrameLayout {
+        self.layout
+    }
+
+    /// Returns the order of the pixels within each byte of the buffer.
+    pub fn frame_bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Returns a copy of the frame as an image. See the `snapshot` module.
+    #[cfg(feature = "std")]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(
+            self.buffer.as_ref(),
+            self.cols,
+            self.rows,
+            self.layout,
+            self.bit_order,
+        )
+    }
+
+    fn set_pixel(&mut self, x: u32, y: u32, color: BinaryColor) {
+        let (cols, rows) = (u32::from(self.cols), u32::from(self.rows));
+        let (x, y) = rotation(x, y, cols, rows, self.rotation);
+        let index = self.layout.byte_index(x, y, cols, rows) as usize;
+        let bit = self.bit_order.bit_mask(x);
+        match color {
+            BLACK => self.buffer.as_mut()[index] &= !bit,
+            WHITE => self.buffer.as_mut()[index] |= bit,
+        }
+    }
+}
+
+impl<B> DrawTarget for Frame<B>
+where
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Self::Error>
+    where
+        Iter: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+        for Pixel(Point { x, y }, color) in pixels {
+            let (x, y) = (x as u32, y as u32);
+            if x < size.width && y < size.height {
+                self.set_pixel(x, y, color);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&Rectangle::new(Point::zero(), self.size()));
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        let region = rotated_region(
+            area.top_left.x as u32,
+            area.top_left.y as u32,
+            area.size.width,
+            area.size.height,
+            self.cols.into(),
+            self.rows.into(),
+            self.rotation,
+        );
+        fill_region(
+            self.buffer.as_mut(),
+            self.cols / 8,
+            self.rows,
+            self.layout,
+            self.bit_order,
+            region,
+            color,
+        );
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let value = match color {
+            BLACK => 0x00,
+            WHITE => 0xFF,
+        };
+        self.buffer.as_mut().fill(value);
+        Ok(())
+    }
+}
+
+impl<B> OriginDimensions for Frame<B> {
+    fn size(&self) -> Size {
+        match self.rotation {
+            Rotation::Rotate0 | Rotation::Rotate180 => {
+                Size::new(self.cols.into(), self.rows.into())
+            }
+            Rotation::Rotate90 | Rotation::Rotate270 => {
+                Size::new(self.rows.into(), self.cols.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        display::Display,
+        graphics::GraphicDisplay,
+        test_util::{builder, NullInterface},
+        Ssd1680Error,
+    };
+
+    #[futures_test::test]
+    async fn present_checks_frame_matches_display() {
+        let config = builder(3, 8).build().expect("invalid config");
+        let mut display = Display::new(NullInterface, config);
+        let frame = display.new_frame([0xFFu8; 3]);
+        assert!(display.present(&frame).await.is_ok());
+
+        let other = Frame::new(
+            [0xFFu8; 3],
+            Dimensions { rows: 1, cols: 24 },
+            Rotation::Rotate0,
+        );
+        assert!(matches!(
+            display.present(&other).await,
+            Err(Ssd1680Error::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn frames_are_drawn_like_the_display() {
+        const SIZE: usize = 16 * 8 / 8;
+        let area = Rectangle::new(Point::new(1, 2), Size::new(5, 3));
+
+        for rotation in [Rotation::Rotate0, Rotation::Rotate90] {
+            for layout in [FrameLayout::RowMajor, FrameLayout::ColumnMajor] {
+                let config = builder(16, 8)
+                    .rotation(rotation)
+                    .frame_layout(layout)
+                    .bit_order(BitOrder::LsbFirst)
+                    .build()
+                    .expect("invalid config");
+                let mut black_buffer = [0u8; SIZE];
+                let mut work_buffer = [0u8; SIZE];
+                let display = Display::new(NullInterface, config);
+                let mut frame = display.new_frame([0u8; SIZE]);
+                frame.clear(WHITE).unwrap();
+                frame.fill_solid(&area, BLACK).unwrap();
+                Pixel(Point::new(7, 0), BLACK).draw(&mut frame).unwrap();
+
+                {
+                    let mut display =
+                        GraphicDisplay::new(display, &mut black_buffer, &mut work_buffer);
+                    display.clear(WHITE);
+                    display.fill_solid(&area, BLACK).unwrap();
+                    Pixel(Point::new(7, 0), BLACK).draw(&mut display).unwrap();
+                }
+                assert_eq!(frame.buffer(), &black_buffer);
+            }
+        }
+    }
+}
@@ -1,16 +1,36 @@
+#[cfg(feature = "std")]
+use crate::snapshot::Snapshot;
 use crate::{
-    display::{Display, Rotation},
+    display::{BitOrder, Display, DropAction, FrameLayout, PowerDown, RamPlane, Region, Rotation},
+    error::Ssd1680Error,
     interface::DisplayInterface,
 };
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec};
 use core::{
     convert::{AsMut, AsRef},
     ops::{Deref, DerefMut},
 };
-use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics_core::{geometry::Point, pixelcolor::BinaryColor, primitives::Rectangle};
 
 pub const WHITE: BinaryColor = BinaryColor::On;
 pub const BLACK: BinaryColor = BinaryColor::Off;
 
+// Changes covering at least 1/FULL_REFRESH_AREA_DIVISOR of the display use a full refresh
+const FULL_REFRESH_AREA_DIVISOR: u32 = 2;
+
+/// The refresh performed by `GraphicDisplay::swap_and_update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Refresh {
+    /// Nothing changed, so the display was not refreshed.
+    Skipped,
+    /// The region that changed was partially updated.
+    Partial(Region),
+    /// Most of the display changed, so it was fully refreshed.
+    Full,
+}
+
 /// A display that holds buffers for drawing into and updating the display from.
 ///
 /// When the `graphics` feature is enabled `GraphicDisplay` implements the `Draw` trait from
@@ -23,6 +43,9 @@ where
     display: Display<'a, I>,
     black_buffer: B,
     work_buffer: B,
+    previous_buffer: Option<B>,
+    dirty: Option<Region>,
+    clip: Option<Rectangle>,
 }
 
 impl<'a, I, B> GraphicDisplay<'a, I, B>
@@ -40,37 +63,382 @@ where
             display,
             black_buffer,
             work_buffer,
+            previous_buffer: None,
+            dirty: None,
+            clip: None,
         }
     }
 
-    /// Update the display by writing the buffers to the controller.
-    pub async fn update(&mut self) -> Result<(), I::Error> {
-        self.display.update(self.black_buffer.as_ref()).await
+    /// Promote a `Display` to a `GraphicDisplay` that performs differential partial updates.
+    ///
+    /// In addition to the buffers required by `new`, a buffer of the same size holding the
+    /// previously displayed frame must be supplied. Partial updates then write the previous image
+    /// of the region to the red RAM and the new image to the B/W RAM, which the controller uses
+    /// for flicker free partial refreshes. The previous frame is kept up to date automatically.
+    pub fn with_previous_frame(
+        display: Display<'a, I>,
+        black_buffer: B,
+        work_buffer: B,
+        previous_buffer: B,
+    ) -> Self {
+        GraphicDisplay {
+            display,
+            black_buffer,
+            work_buffer,
+            previous_buffer: Some(previous_buffer),
+            dirty: None,
+            clip: None,
+        }
+    }
+
+    /// Release the display and the buffers, in the order they were passed to `new`, followed by
+    /// the previous frame buffer if one was supplied.
+    pub fn release(self) -> (Display<'a, I>, B, B, Option<B>) {
+        (
+            self.display,
+            self.black_buffer,
+            self.work_buffer,
+            self.previous_buffer,
+        )
+    }
+
+    /// Change the rotation used to map drawing coordinates onto the buffer.
+    ///
+    /// This only affects subsequent drawing: the contents of the buffer are not rotated. `size`
+    /// reports the rotated dimensions straight away.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.display.set_rotation(rotation);
     }
 
     /// Update the display by writing the buffers to the controller.
+    pub async fn update(&mut self) -> Result<(), Ssd1680Error<I::Error>> {
+        self.display.update(self.black_buffer.as_ref()).await?;
+        if let Some(previous_buffer) = self.previous_buffer.as_mut() {
+            previous_buffer
+                .as_mut()
+                .copy_from_slice(self.black_buffer.as_ref());
+        }
+        Ok(())
+    }
+
+    /// Update a region of the display by writing the buffers to the controller.
+    ///
+    /// If the display was created with `with_previous_frame`, the previous image of the region is
+    /// written to the red RAM as well.
     pub async fn partial_update(
         &mut self,
         start_x_px: u16,
         start_y_px: u16,
         width_px: u16,
         height_px: u16,
-    ) -> Result<(), I::Error> {
-        let work_buf_ref = self.work_buffer.as_mut();
-        let sub_image = make_sub_image(
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        self.partial_update_regions(&[Region {
+            x: start_x_px,
+            y: start_y_px,
+            width: width_px,
+            height: height_px,
+        }])
+        .await
+    }
+
+    /// Update the part of the display covered by `rect`, in drawing coordinates.
+    ///
+    /// The rectangle is in the same rotated coordinates as drawing, with the drawing offset
+    /// applied, so the bounding box of a drawn shape or text can be passed directly. It is clipped
+    /// to the display and widened to whole bytes horizontally. Nothing is refreshed if it is
+    /// entirely outside the display.
+    pub async fn partial_update_rect(
+        &mut self,
+        rect: Rectangle,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        let (width, height) = self.rotated_size();
+        let rect = Rectangle::new(rect.top_left + self.offset(), rect.size)
+            .intersection(&Rectangle::new(Point::zero(), Size::new(width, height)));
+        if rect.is_zero_sized() {
+            return Ok(());
+        }
+        let region = self
+            .physical_region(
+                rect.top_left.x as u32,
+                rect.top_left.y as u32,
+                rect.size.width,
+                rect.size.height,
+            )
+            .byte_aligned();
+        self.partial_update(region.x, region.y, region.width, region.height)
+            .await
+    }
+
+    /// Update several regions of the display with a single refresh.
+    ///
+    /// The RAM windows of all regions (e.g. a clock and a battery icon) are written before a
+    /// single refresh is triggered. Regions are in physical (unrotated) pixels and must be byte
+    /// aligned horizontally, as for `partial_update`.
+    pub async fn partial_update_regions(
+        &mut self,
+        regions: &[Region],
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        let full_refresh = self.display.begin_partial_update().await?;
+        for region in regions {
+            for (plane, buffer) in [
+                (
+                    RamPlane::Red,
+                    self.previous_buffer.as_ref().map(AsRef::as_ref),
+                ),
+                (RamPlane::BlackWhite, Some(self.black_buffer.as_ref())),
+            ] {
+                let Some(buffer) = buffer else {
+                    continue;
+                };
+                let sub_image = make_sub_image(
+                    buffer,
+                    self.work_buffer.as_mut(),
+                    self.display.cols_as_bytes(),
+                    self.display.rows(),
+                    self.display.frame_layout(),
+                    region.x,
+                    region.y,
+                    region.width,
+                    region.height,
+                );
+                self.display
+                    .write_window(
+                        plane,
+                        sub_image,
+                        region.x,
+                        region.y,
+                        region.width,
+                        region.height,
+                    )
+                    .await?;
+            }
+        }
+        self.display.finish_partial_update(full_refresh).await?;
+
+        if let Some(previous_buffer) = self.previous_buffer.as_mut() {
+            for region in regions {
+                copy_region(
+                    self.black_buffer.as_ref(),
+                    previous_buffer.as_mut(),
+                    self.display.cols_as_bytes(),
+                    self.display.rows(),
+                    self.display.frame_layout(),
+                    region.x,
+                    region.y,
+                    region.width,
+                    region.height,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Update only the region of the display that changed since the last update.
+    ///
+    /// Compares the black buffer against the last flushed frame, computes the smallest
+    /// byte-aligned rectangle containing every change and partially updates just that region.
+    /// Returns `true` if an update was performed, `false` if nothing changed.
+    ///
+    /// This requires the display to have been created with `with_previous_frame`, otherwise the
+    /// whole display is updated.
+    pub async fn update_diff(&mut self) -> Result<bool, Ssd1680Error<I::Error>> {
+        let Some(previous_buffer) = self.previous_buffer.as_ref() else {
+            self.update().await?;
+            return Ok(true);
+        };
+
+        match diff_bounds(
             self.black_buffer.as_ref(),
-            work_buf_ref,
+            previous_buffer.as_ref(),
             self.display.cols_as_bytes(),
-            start_x_px,
-            start_y_px,
-            width_px,
-            height_px,
-        );
-        self.display
-            .partial_update(sub_image, start_x_px, start_y_px, width_px, height_px)
+            self.display.rows(),
+            self.display.frame_layout(),
+        ) {
+            Some(region) => {
+                self.partial_update(region.x, region.y, region.width, region.height)
+                    .await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Present the frame drawn since the last update, choosing the refresh automatically.
+    ///
+    /// The black buffer is the back buffer being drawn, and the previous frame buffer is the front
+    /// buffer holding what the panel shows. They are compared, and nothing is refreshed if they
+    /// match, the changed region is partially updated if it is small, and the whole display is
+    /// refreshed if the changes cover half of it or more. The front buffer is then brought up to
+    /// date. Requires the display to have been created with `with_previous_frame`, otherwise
+    /// every call is a full refresh.
+    pub async fn swap_and_update(&mut self) -> Result<Refresh, Ssd1680Error<I::Error>> {
+        let changed = match self.previous_buffer.as_ref() {
+            Some(previous_buffer) => diff_bounds(
+                self.black_buffer.as_ref(),
+                previous_buffer.as_ref(),
+                self.display.cols_as_bytes(),
+                self.display.rows(),
+                self.display.frame_layout(),
+            ),
+            None => None,
+        };
+        let display_area = u32::from(self.cols()) * u32::from(self.rows());
+        match changed {
+            None if self.previous_buffer.is_some() => Ok(Refresh::Skipped),
+            Some(region)
+                if u32::from(region.width)
+                    * u32::from(region.height)
+                    * FULL_REFRESH_AREA_DIVISOR
+                    < display_area =>
+            {
+                self.partial_update(region.x, region.y, region.width, region.height)
+                    .await?;
+                Ok(Refresh::Partial(region))
+            }
+            _ => {
+                self.update().await?;
+                Ok(Refresh::Full)
+            }
+        }
+    }
+
+    /// Scroll the contents of `rect` vertically by `pixels` and partially update it.
+    ///
+    /// `rect` is in drawing coordinates, as for `partial_update_rect`, so the contents move along
+    /// the y axis of the rotated display. Positive values move the contents up, as in a console,
+    /// and negative values move them down. Rows scrolled into view are cleared to white. The
+    /// rectangle is clipped to the display, and the partial update covers the byte aligned
+    /// physical region around it.
+    pub async fn scroll_vertical(
+        &mut self,
+        rect: Rectangle,
+        pixels: i32,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        let (width, height) = self.rotated_size();
+        let rect = Rectangle::new(rect.top_left + self.offset(), rect.size)
+            .intersection(&Rectangle::new(Point::zero(), Size::new(width, height)));
+        if rect.is_zero_sized() {
+            return Ok(());
+        }
+        let (x, y) = (rect.top_left.x as u32, rect.top_left.y as u32);
+        let Size { width, height } = rect.size;
+        let shift = pixels.unsigned_abs().min(height);
+        for offset in 0..height {
+            // Visit rows in the order that reads each source row before it is overwritten
+            let (row, source) = if pixels > 0 {
+                (y + offset, y + offset + shift)
+            } else {
+                (
+                    y + height - 1 - offset,
+                    (y + height - 1 - offset).saturating_sub(shift),
+                )
+            };
+            let scrolled_in = offset + shift >= height;
+            for column in x..x + width {
+                let color = if scrolled_in {
+                    WHITE
+                } else {
+                    self.get_pixel(column, source).unwrap_or(WHITE)
+                };
+                self.write_pixel(column, row, color);
+            }
+        }
+        let region = self.physical_region(x, y, width, height).byte_aligned();
+        self.partial_update(region.x, region.y, region.width, region.height)
             .await
     }
 
+    /// Restrict subsequent drawing to `clip`, in rotated coordinates as used for drawing.
+    ///
+    /// Pixels drawn through `DrawTarget` outside the clip rectangle are discarded, so a widget
+    /// can be rendered into its own area without scribbling over its neighbours. `DrawTarget`'s
+    /// `clear` only fills the clip rectangle, while `GraphicDisplay::clear` and `blit` ignore it.
+    pub fn set_clip(&mut self, clip: Rectangle) {
+        self.clip = Some(clip);
+    }
+
+    /// Remove the clip rectangle, allowing drawing to the whole display again.
+    pub fn clear_clip(&mut self) {
+        self.clip = None;
+    }
+
+    /// Returns the area drawing is restricted to, which is the whole display unless a clip
+    /// rectangle has been set.
+    ///
+    /// The area is in drawing coordinates, before the `Builder::drawing_offset` is added.
+    pub fn clip(&self) -> Rectangle {
+        let (width, height) = self.rotated_size();
+        let display = Rectangle::new(-self.offset(), Size::new(width, height));
+        match self.clip {
+            Some(clip) => clip.intersection(&display),
+            None => display,
+        }
+    }
+
+    /// Invert the pixels inside `rect`, in drawing coordinates, e.g. to highlight a selected
+    /// menu item without redrawing it.
+    ///
+    /// The rectangle is clipped and offset as for drawing. Inverting the same rectangle again
+    /// restores the original pixels.
+    pub fn invert_region(&mut self, rect: Rectangle) {
+        let rect = rect.intersection(&self.clip());
+        if rect.is_zero_sized() {
+            return;
+        }
+        let top_left = rect.top_left + self.offset();
+        let region = self.physical_region(
+            top_left.x as u32,
+            top_left.y as u32,
+            rect.size.width,
+            rect.size.height,
+        );
+        update_region(
+            self.black_buffer.as_mut(),
+            self.display.cols_as_bytes(),
+            self.display.rows(),
+            self.display.frame_layout(),
+            self.display.bit_order(),
+            region,
+            |byte, mask| byte ^ mask,
+        );
+        self.mark_dirty(region);
+    }
+
+    /// Returns the drawing offset as a point.
+    fn offset(&self) -> Point {
+        let (x, y) = self.drawing_offset();
+        Point::new(x.into(), y.into())
+    }
+
+    /// Returns the byte-aligned region drawn to since the last call, and resets the tracking.
+    ///
+    /// The region is in physical (unrotated) coordinates, suitable for `partial_update`. Returns
+    /// `None` if nothing has been drawn.
+    pub fn take_dirty_region(&mut self) -> Option<Region> {
+        self.dirty.take().map(Region::byte_aligned)
+    }
+
+    /// Partially update exactly the region drawn to since the last flush.
+    ///
+    /// Returns `true` if an update was performed, `false` if nothing has been drawn.
+    pub async fn flush_dirty(&mut self) -> Result<bool, Ssd1680Error<I::Error>> {
+        match self.take_dirty_region() {
+            Some(region) => {
+                self.partial_update(region.x, region.y, region.width, region.height)
+                    .await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn mark_dirty(&mut self, region: Region) {
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(region),
+            None => region,
+        });
+    }
+
     /// Clear the buffers, filling them a single color.
     pub fn clear(&mut self, color: BinaryColor) {
         let black = match color {
@@ -81,19 +449,172 @@ where
         for byte in &mut self.black_buffer.as_mut().iter_mut() {
             *byte = black; // background_color.get_byte_value();
         }
+        self.mark_dirty(Region {
+            x: 0,
+            y: 0,
+            width: self.cols().into(),
+            height: self.rows(),
+        });
+    }
+
+    /// Returns the color of the pixel at `x`, `y` in the buffer, in rotated coordinates, or `None`
+    /// if the position is outside the display. Unlike `GetPixel::pixel`, the drawing offset is not
+    /// added.
+    ///
+    /// This reads what has been drawn, not what the panel currently shows, e.g. for XOR cursors
+    /// or hit testing.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<BinaryColor> {
+        let (width, height) = self.rotated_size();
+        if x >= width || y >= height {
+            return None;
+        }
+        let (x, y) = rotation(
+            x,
+            y,
+            self.cols() as u32,
+            self.rows() as u32,
+            self.rotation(),
+        );
+        let index = self
+            .frame_layout()
+            .byte_index(x, y, self.cols() as u32, self.rows() as u32) as usize;
+        let bit = self.bit_order().bit_mask(x);
+        Some(if self.black_buffer.as_ref()[index] & bit == 0 {
+            BLACK
+        } else {
+            WHITE
+        })
+    }
+
+    /// Returns a copy of the buffer as an image, showing exactly what `update` would send to the
+    /// panel. See the `snapshot` module.
+    #[cfg(feature = "std")]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(
+            self.black_buffer.as_ref(),
+            self.cols(),
+            self.rows(),
+            self.frame_layout(),
+            self.bit_order(),
+        )
+    }
+
+    /// Copy a packed 1 bit per pixel image into the buffer at `x`, `y`, in rotated coordinates.
+    /// The drawing offset is not added.
+    ///
+    /// Each row of the image starts `stride` bytes after the previous one, packed in the
+    /// configured `BitOrder` with set bits drawn white, as in the buffer. Pixels
+    /// outside the display are discarded. Rows are copied a byte at a time when the display is
+    /// not rotated and `x` is a multiple of 8, and pixel by pixel otherwise. `InvalidArgument` is
+    /// returned if `data` is too short for the image.
+    pub fn blit(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        stride: usize,
+    ) -> Result<(), Ssd1680Error<I::Error>> {
+        let row_bytes = width.div_ceil(8) as usize;
+        if height > 0
+            && (stride < row_bytes || data.len() < stride * (height as usize - 1) + row_bytes)
+        {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        let (display_width, display_height) = self.rotated_size();
+        let width = width.min(display_width.saturating_sub(x));
+        let height = height.min(display_height.saturating_sub(y));
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let (cols, rows) = (self.cols() as u32, self.rows() as u32);
+        let layout = self.frame_layout();
+        let bit_order = self.bit_order();
+        let rotation = self.rotation();
+        let buffer = self.black_buffer.as_mut();
+        if matches!(rotation, Rotation::Rotate0) && x.is_multiple_of(8) {
+            for row in 0..height {
+                let source = &data[row as usize * stride..];
+                for byte in 0..width.div_ceil(8) {
+                    let remaining = width - byte * 8;
+                    let mask = if remaining < 8 {
+                        bit_order.convert(!(0xFF >> remaining))
+                    } else {
+                        0xFF
+                    };
+                    let index = layout.byte_index(x + byte * 8, y + row, cols, rows) as usize;
+                    buffer[index] = (buffer[index] & !mask) | (source[byte as usize] & mask);
+                }
+            }
+        } else {
+            for row in 0..height {
+                let source = &data[row as usize * stride..];
+                for column in 0..width {
+                    let white = source[column as usize / 8] & bit_order.bit_mask(column) != 0;
+                    let (px, py) = self::rotation(x + column, y + row, cols, rows, rotation);
+                    let index = layout.byte_index(px, py, cols, rows) as usize;
+                    let bit = bit_order.bit_mask(px);
+                    if white {
+                        buffer[index] |= bit;
+                    } else {
+                        buffer[index] &= !bit;
+                    }
+                }
+            }
+        }
+        let region = self.physical_region(x, y, width, height);
+        self.mark_dirty(region);
+        Ok(())
+    }
+
+    /// Returns the width and height of the display in rotated coordinates.
+    fn rotated_size(&self) -> (u32, u32) {
+        match self.rotation() {
+            Rotation::Rotate0 | Rotation::Rotate180 => (self.cols() as u32, self.rows() as u32),
+            Rotation::Rotate90 | Rotation::Rotate270 => (self.rows() as u32, self.cols() as u32),
+        }
+    }
+
+    /// Map a non-empty rectangle in rotated coordinates to the physical region it covers.
+    fn physical_region(&self, x: u32, y: u32, width: u32, height: u32) -> Region {
+        rotated_region(
+            x,
+            y,
+            width,
+            height,
+            self.cols() as u32,
+            self.rows() as u32,
+            self.rotation(),
+        )
     }
 
     #[allow(dead_code, reason = "Carried in implementation from previous driver.")]
     fn set_pixel(&mut self, x: u32, y: u32, color: BinaryColor) {
-        let (index, bit) = rotation(
+        let (x, y) = self.write_pixel(x, y, color);
+        self.mark_dirty(Region {
+            x: x as u16,
+            y: y as u16,
+            width: 1,
+            height: 1,
+        });
+    }
+
+    /// Set the pixel at `x`, `y` in rotated coordinates without marking it dirty, returning its
+    /// physical position.
+    fn write_pixel(&mut self, x: u32, y: u32, color: BinaryColor) -> (u32, u32) {
+        let (x, y) = rotation(
             x,
             y,
             self.cols() as u32,
             self.rows() as u32,
             self.rotation(),
         );
-        let index = index as usize;
-
+        let index = self
+            .frame_layout()
+            .byte_index(x, y, self.cols() as u32, self.rows() as u32) as usize;
+        let bit = self.bit_order().bit_mask(x);
         match color {
             BLACK => {
                 self.black_buffer.as_mut()[index] &= !bit;
@@ -102,6 +623,73 @@ where
                 self.black_buffer.as_mut()[index] |= bit;
             }
         }
+        (x, y)
+    }
+}
+
+/// A `GraphicDisplay` that owns its buffers as arrays of `N` bytes.
+///
+/// Without a borrowed buffer the display can be stored in a `static`, e.g. with `StaticCell`,
+/// or moved into an embassy task. `N` must be the `frame_size` of the display, such as the
+/// `BUFFER_SIZE` of a [panel!](../macro.panel.html).
+pub type OwnedGraphicDisplay<I, const N: usize> = GraphicDisplay<'static, I, [u8; N]>;
+
+impl<'a, I, const N: usize> GraphicDisplay<'a, I, [u8; N]>
+where
+    I: DisplayInterface,
+{
+    /// Promote a `Display` to a `GraphicDisplay` with buffers of its own, cleared to white.
+    ///
+    /// Returns `InvalidArgument` if `N` is not the `frame_size` of the display.
+    pub fn new_owned(display: Display<'a, I>) -> Result<Self, Ssd1680Error<I::Error>> {
+        if N != display.frame_size() {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        Ok(Self::new(display, [0xFF; N], [0; N]))
+    }
+
+    /// Promote a `Display` to a `GraphicDisplay` with buffers of its own that performs
+    /// differential partial updates. See `with_previous_frame`.
+    ///
+    /// Returns `InvalidArgument` if `N` is not the `frame_size` of the display.
+    pub fn with_previous_frame_owned(
+        display: Display<'a, I>,
+    ) -> Result<Self, Ssd1680Error<I::Error>> {
+        if N != display.frame_size() {
+            return Err(Ssd1680Error::InvalidArgument);
+        }
+        Ok(Self::with_previous_frame(
+            display, [0xFF; N], [0; N], [0xFF; N],
+        ))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, I> GraphicDisplay<'a, I, Box<[u8]>>
+where
+    I: DisplayInterface,
+{
+    /// Promote a `Display` to a `GraphicDisplay` with buffers allocated from the heap, sized
+    /// for the display and cleared to white.
+    pub fn new_boxed(display: Display<'a, I>) -> Self {
+        let size = display.frame_size();
+        Self::new(
+            display,
+            vec![0xFF; size].into_boxed_slice(),
+            vec![0; size].into_boxed_slice(),
+        )
+    }
+
+    /// Promote a `Display` to a `GraphicDisplay` with buffers allocated from the heap that
+    /// performs differential partial updates. See `with_previous_frame`.
+    pub fn with_previous_frame_boxed(display: Display<'a, I>) -> Self {
+        let size = display.frame_size();
+        Self::with_previous_frame(
+            display,
+            vec![0xFF; size].into_boxed_slice(),
+            vec![0; size].into_boxed_slice(),
+            vec![0xFF; size].into_boxed_slice(),
+        )
     }
 }
 
@@ -125,25 +713,53 @@ where
     }
 }
 
+impl<'a, I, B> PowerDown for GraphicDisplay<'a, I, B>
+where
+    I: DisplayInterface,
+{
+    type Error = Ssd1680Error<I::Error>;
+
+    async fn power_down(&mut self, action: DropAction) -> Result<(), Self::Error> {
+        self.display.power_down(action).await
+    }
+}
+
+/// Map logical coordinates to the physical (unrotated) position in the frame buffer.
 #[allow(dead_code, reason = "Carried in implementation from previous driver.")]
-fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32, u8) {
+pub(crate) fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32, u32) {
     match rotation {
-        Rotation::Rotate0 => (x / 8 + (width / 8) * y, 0x80 >> (x % 8)),
-        Rotation::Rotate90 => ((width - 1 - y) / 8 + (width / 8) * x, 0x01 << (y % 8)),
-        Rotation::Rotate180 => (
-            ((width / 8) * height - 1) - (x / 8 + (width / 8) * y),
-            0x01 << (x % 8),
-        ),
-        Rotation::Rotate270 => (y / 8 + (height - 1 - x) * (width / 8), 0x80 >> (y % 8)),
+        Rotation::Rotate0 => (x, y),
+        Rotation::Rotate90 => (width - 1 - y, x),
+        Rotation::Rotate180 => (width - 1 - x, height - 1 - y),
+        Rotation::Rotate270 => (y, height - 1 - x),
     }
 }
 
-#[cfg(feature = "graphics")]
-extern crate embedded_graphics;
-#[cfg(feature = "graphics")]
-use self::embedded_graphics::prelude::*;
+/// Map a non-empty rectangle in logical coordinates to the physical region it covers.
+pub(crate) fn rotated_region(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    cols: u32,
+    rows: u32,
+    rotation: Rotation,
+) -> Region {
+    // A rotated rectangle is still a rectangle, spanned by the rotated corners
+    let (x0, y0) = self::rotation(x, y, cols, rows, rotation);
+    let (x1, y1) = self::rotation(x + width - 1, y + height - 1, cols, rows, rotation);
+    Region {
+        x: x0.min(x1) as u16,
+        y: y0.min(y1) as u16,
+        width: x0.abs_diff(x1) as u16 + 1,
+        height: y0.abs_diff(y1) as u16 + 1,
+    }
+}
+
+#[cfg(feature = "graphics-core")]
+use embedded_graphics_core::{image::GetPixel, prelude::*};
 
-#[cfg(feature = "graphics")]
+#[cfg(feature = "graphics-core")]
 impl<'a, I, B> DrawTarget for GraphicDisplay<'a, I, B>
 where
     I: DisplayInterface,
@@ -157,19 +773,70 @@ where
     where
         Iter: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        let sz = self.size();
-        for Pixel(Point { x, y }, color) in pixels {
-            let x = x as u32;
-            let y = y as u32;
-            if x < sz.width && y < sz.height {
-                self.set_pixel(x, y, color)
+        let clip = self.clip();
+        let offset = self.offset();
+        for Pixel(point, color) in pixels {
+            if clip.contains(point) {
+                let point = point + offset;
+                self.set_pixel(point.x as u32, point.y as u32, color)
             }
         }
         Ok(())
     }
+
+    /// Fill a rectangle by writing whole bytes of the buffer where possible.
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.clip());
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        let area = Rectangle::new(area.top_left + self.offset(), area.size);
+        let region = self.physical_region(
+            area.top_left.x as u32,
+            area.top_left.y as u32,
+            area.size.width,
+            area.size.height,
+        );
+        fill_region(
+            self.black_buffer.as_mut(),
+            self.display.cols_as_bytes(),
+            self.display.rows(),
+            self.display.frame_layout(),
+            self.display.bit_order(),
+            region,
+            color,
+        );
+        self.mark_dirty(region);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        if self.clip.is_some() {
+            return self.fill_solid(&self.clip(), color);
+        }
+        GraphicDisplay::clear(self, color);
+        Ok(())
+    }
 }
 
-#[cfg(feature = "graphics")]
+#[cfg(feature = "graphics-core")]
+impl<I, B> GetPixel for GraphicDisplay<'_, I, B>
+where
+    I: DisplayInterface,
+    B: AsMut<[u8]>,
+    B: AsRef<[u8]>,
+{
+    type Color = BinaryColor;
+
+    fn pixel(&self, point: Point) -> Option<Self::Color> {
+        let point = point + self.offset();
+        let x = u32::try_from(point.x).ok()?;
+        let y = u32::try_from(point.y).ok()?;
+        self.get_pixel(x, y)
+    }
+}
+
+#[cfg(feature = "graphics-core")]
 impl<'a, I, B> OriginDimensions for GraphicDisplay<'a, I, B>
 where
     I: DisplayInterface,
@@ -187,10 +854,13 @@ where
 }
 
 #[allow(clippy::indexing_slicing)]
+#[allow(clippy::too_many_arguments)]
 fn make_sub_image<'a>(
     black_buffer: &[u8],
     work_buffer: &'a mut [u8],
     display_width_as_bytes: u8,
+    display_rows: u16,
+    layout: FrameLayout,
     start_x_px: u16,
     start_y_px: u16,
     width_px: u16,
@@ -200,72 +870,177 @@ fn make_sub_image<'a>(
     let start_x_bytes = start_x_px / 8;
     let width_bytes = width_px / 8;
     let end_y_px = start_y_px + height_px;
-    for i in start_y_px..end_y_px {
-        let start_x = ((i * display_width_as_bytes as u16) + start_x_bytes) as usize;
-        let end_x = start_x + width_bytes as usize;
-        for b in black_buffer.iter().take(end_x).skip(start_x) {
-            work_buffer[at] = *b;
-            at += 1;
+    match layout {
+        FrameLayout::RowMajor => {
+            for i in start_y_px..end_y_px {
+                let start_x = ((i * display_width_as_bytes as u16) + start_x_bytes) as usize;
+                let end_x = start_x + width_bytes as usize;
+                for b in black_buffer.iter().take(end_x).skip(start_x) {
+                    work_buffer[at] = *b;
+                    at += 1;
+                }
+            }
+        }
+        FrameLayout::ColumnMajor => {
+            for x_byte in start_x_bytes..start_x_bytes + width_bytes {
+                let start = (x_byte as usize * display_rows as usize) + start_y_px as usize;
+                let end = start + height_px as usize;
+                for b in black_buffer.iter().take(end).skip(start) {
+                    work_buffer[at] = *b;
+                    at += 1;
+                }
+            }
         }
     }
     let num_bytes = (width_bytes * height_px) as usize;
     &work_buffer[0..num_bytes]
 }
 
+/// Returns the smallest byte-aligned region containing every byte that differs between `current`
+/// and `previous`.
+fn diff_bounds(
+    current: &[u8],
+    previous: &[u8],
+    display_width_as_bytes: u8,
+    display_rows: u16,
+    layout: FrameLayout,
+) -> Option<Region> {
+    let width_bytes = usize::from(display_width_as_bytes);
+    let rows = usize::from(display_rows);
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (index, _) in current
+        .iter()
+        .zip(previous.iter())
+        .enumerate()
+        .filter(|(_, (current, previous))| current != previous)
+    {
+        let (x_byte, y) = match layout {
+            FrameLayout::RowMajor => (index % width_bytes, index / width_bytes),
+            FrameLayout::ColumnMajor => (index / rows, index % rows),
+        };
+        bounds = Some(match bounds {
+            None => (x_byte, y, x_byte, y),
+            Some((min_x, min_y, max_x, max_y)) => (
+                min_x.min(x_byte),
+                min_y.min(y),
+                max_x.max(x_byte),
+                max_y.max(y),
+            ),
+        });
+    }
+
+    bounds.map(|(min_x, min_y, max_x, max_y)| Region {
+        x: (min_x * 8) as u16,
+        y: min_y as u16,
+        width: ((max_x - min_x + 1) * 8) as u16,
+        height: (max_y - min_y + 1) as u16,
+    })
+}
+
+/// Fill a region of the buffer with `color`, setting whole bytes and masking the partial bytes at
+/// the left and right edges.
+pub(crate) fn fill_region(
+    buffer: &mut [u8],
+    display_width_as_bytes: u8,
+    display_rows: u16,
+    layout: FrameLayout,
+    bit_order: BitOrder,
+    region: Region,
+    color: BinaryColor,
+) {
+    update_region(
+        buffer,
+        display_width_as_bytes,
+        display_rows,
+        layout,
+        bit_order,
+        region,
+        |byte, mask| match color {
+            BLACK => byte & !mask,
+            WHITE => byte | mask,
+        },
+    );
+}
+
+/// Replace each byte of a region of the buffer with `update(byte, mask)`, where `mask` selects the
+/// bits of the byte inside the region.
+fn update_region(
+    buffer: &mut [u8],
+    display_width_as_bytes: u8,
+    display_rows: u16,
+    layout: FrameLayout,
+    bit_order: BitOrder,
+    region: Region,
+    update: impl Fn(u8, u8) -> u8,
+) {
+    let end_x = region.x + region.width;
+    for y in region.y..region.y + region.height {
+        for x_byte in region.x / 8..end_x.div_ceil(8) {
+            let first = region.x.max(x_byte * 8) - x_byte * 8;
+            let last = end_x.min(x_byte * 8 + 8) - x_byte * 8;
+            let mask = bit_order.convert(((0xFF_u16 >> first) & !(0xFF_u16 >> last)) as u8);
+            let index = layout.byte_index(
+                u32::from(x_byte) * 8,
+                y.into(),
+                u32::from(display_width_as_bytes) * 8,
+                display_rows.into(),
+            ) as usize;
+            buffer[index] = update(buffer[index], mask);
+        }
+    }
+}
+
+/// Copy a region of `source` into the same region of `destination`.
+#[allow(clippy::too_many_arguments)]
+fn copy_region(
+    source: &[u8],
+    destination: &mut [u8],
+    display_width_as_bytes: u8,
+    display_rows: u16,
+    layout: FrameLayout,
+    start_x_px: u16,
+    start_y_px: u16,
+    width_px: u16,
+    height_px: u16,
+) {
+    for y in start_y_px..start_y_px + height_px {
+        for x_byte in start_x_px / 8..(start_x_px + width_px) / 8 {
+            let index = layout.byte_index(
+                u32::from(x_byte) * 8,
+                y.into(),
+                u32::from(display_width_as_bytes) * 8,
+                display_rows.into(),
+            ) as usize;
+            destination[index] = source[index];
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use self::embedded_graphics::primitives::{PrimitiveStyleBuilder, Rectangle};
     use super::*;
     use crate::{
-        config::Builder,
-        display::{Dimensions, Display, Rotation},
+        command::DeepSleepMode,
+        config::{RefreshAdjustment, TemperaturePolicy},
+        display::{ControllerState, Display, Region, Rotation},
         graphics::GraphicDisplay,
+        test_util::{builder, NullInterface},
+    };
+    use embedded_graphics::{
+        prelude::Primitive,
+        primitives::{PrimitiveStyleBuilder, Rectangle},
     };
 
     const ROWS: u16 = 3;
     const COLS: u8 = 8;
     const BUFFER_SIZE: usize = (ROWS * COLS as u16) as usize / 8;
 
-    struct MockInterface {}
-    struct MockError {}
-
-    impl MockInterface {
-        fn new() -> Self {
-            MockInterface {}
-        }
-    }
-
-    impl DisplayInterface for MockInterface {
-        type Error = MockError;
-
-        async fn reset(&mut self) {}
-
-        async fn send_command(&mut self, _command: u8) -> Result<(), Self::Error> {
-            Ok(())
-        }
-
-        async fn send_data(&mut self, _data: &[u8]) -> Result<(), Self::Error> {
-            Ok(())
-        }
-
-        async fn busy_wait(&mut self) -> Result<(), Self::Error> {
-            Ok(())
-        }
-    }
-
-    fn build_mock_display<'a>() -> Display<'a, MockInterface> {
-        let interface = MockInterface::new();
-        let dimensions = Dimensions {
-            rows: ROWS,
-            cols: COLS,
-        };
-
-        let config = Builder::new()
-            .dimensions(dimensions)
+    fn build_mock_display() -> Display<'static, NullInterface> {
+        let config = builder(ROWS, COLS)
             .rotation(Rotation::Rotate270)
             .build()
             .expect("invalid config");
-        Display::new(interface, config)
+        Display::new(NullInterface, config)
     }
 
     #[test]
@@ -325,6 +1100,531 @@ mod tests {
         assert_eq!(work_buffer, [0_u8; BUFFER_SIZE]);
     }
 
+    #[futures_test::test]
+    async fn partial_updates_reset_after_full_refresh_interval() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let config = builder(ROWS, COLS)
+            .full_refresh_interval(2)
+            .build()
+            .expect("invalid config");
+        let mut display = GraphicDisplay::new(
+            Display::new(NullInterface, config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+
+        let mut counts = [0; 4];
+        for count in counts.iter_mut() {
+            display
+                .partial_update(0, 0, COLS.into(), ROWS)
+                .await
+                .unwrap_or_else(|_| panic!("partial update failed"));
+            *count = display.partial_updates_since_full_refresh();
+        }
+
+        assert_eq!(counts, [1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn fill_solid_matches_drawing_pixels() {
+        const COLS: u8 = 16;
+        const ROWS: u16 = 12;
+        const SIZE: usize = COLS as usize * ROWS as usize / 8;
+        let area = Rectangle::new(Point::new(3, 2), Size::new(7, 5));
+
+        for rotation in [
+            Rotation::Rotate0,
+            Rotation::Rotate90,
+            Rotation::Rotate180,
+            Rotation::Rotate270,
+        ] {
+            let mut buffers = [[0xA5u8; SIZE]; 2];
+            for (index, buffer) in buffers.iter_mut().enumerate() {
+                let config = builder(ROWS, COLS)
+                    .rotation(rotation)
+                    .build()
+                    .expect("invalid config");
+                let mut work_buffer = [0u8; SIZE];
+                let mut display = GraphicDisplay::new(
+                    Display::new(NullInterface, config),
+                    &mut buffer[..],
+                    &mut work_buffer[..],
+                );
+                if index == 0 {
+                    display.fill_solid(&area, WHITE).unwrap();
+                    display.fill_solid(&area.offset(-1), BLACK).unwrap();
+                } else {
+                    display
+                        .draw_iter(area.points().map(|point| Pixel(point, WHITE)))
+                        .unwrap();
+                    display
+                        .draw_iter(area.offset(-1).points().map(|point| Pixel(point, BLACK)))
+                        .unwrap();
+                }
+            }
+            assert_eq!(buffers[0], buffers[1], "{rotation:?}");
+        }
+    }
+
+    #[test]
+    fn get_pixel_reads_back_drawn_pixels() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let mut display =
+            GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+        display.clear(WHITE);
+        Pixel(Point::new(1, 6), BLACK).draw(&mut display).unwrap();
+
+        assert_eq!(display.get_pixel(1, 6), Some(BLACK));
+        assert_eq!(display.get_pixel(0, 6), Some(WHITE));
+        assert_eq!(display.pixel(Point::new(1, 6)), Some(BLACK));
+        assert_eq!(display.pixel(Point::new(-1, 0)), None);
+        assert_eq!(display.get_pixel(3, 0), None);
+    }
+
+    #[test]
+    fn blit_copies_packed_rows() {
+        const COLS: u8 = 16;
+        const ROWS: u16 = 4;
+        const SIZE: usize = COLS as usize * ROWS as usize / 8;
+        // A 12x2 image with a stride of 3 bytes
+        let image = [
+            0b1010_1010,
+            0b1111_0000,
+            0xEE,
+            0b0101_0101,
+            0b0000_1111,
+            0xEE,
+        ];
+
+        for rotation in [Rotation::Rotate0, Rotation::Rotate90] {
+            let mut black_buffer = [0u8; SIZE];
+            let mut work_buffer = [0u8; SIZE];
+            let config = builder(ROWS, COLS)
+                .rotation(rotation)
+                .build()
+                .expect("invalid config");
+            let mut display = GraphicDisplay::new(
+                Display::new(NullInterface, config),
+                &mut black_buffer,
+                &mut work_buffer,
+            );
+            assert!(display.blit(0, 1, 12, 2, &image, 3).is_ok());
+
+            for (row, y) in [(0, 1), (1, 2)] {
+                for column in 0..12 {
+                    let white = image[row * 3 + column / 8] & (0x80 >> (column % 8)) != 0;
+                    let expected = if white { WHITE } else { BLACK };
+                    let pixel = display.get_pixel(column as u32, y);
+                    // Rotated by 90 degrees only the first 4 columns are on the display
+                    if matches!(rotation, Rotation::Rotate0) || column < 4 {
+                        assert_eq!(pixel, Some(expected), "{rotation:?} {column},{y}");
+                    }
+                }
+            }
+            if matches!(rotation, Rotation::Rotate0) {
+                assert_eq!(display.get_pixel(12, 1), Some(BLACK));
+            }
+            assert!(matches!(
+                display.blit(0, 0, 12, 2, &image[..4], 3),
+                Err(Ssd1680Error::InvalidArgument)
+            ));
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn lsb_first_bit_order_is_reversed_in_ram() {
+        use crate::display::{BitOrder, RamPlane};
+        use crate::mock::MockInterface;
+
+        const COLS: u8 = 16;
+        const ROWS: u16 = 2;
+        const SIZE: usize = COLS as usize * ROWS as usize / 8;
+        let config = builder(ROWS, COLS)
+            .bit_order(BitOrder::LsbFirst)
+            .build()
+            .expect("invalid config");
+        let mut black_buffer = [0u8; SIZE];
+        let mut work_buffer = [0u8; SIZE];
+        let mut display = GraphicDisplay::new(
+            Display::new(MockInterface::<64>::new(), config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+        display.clear(WHITE);
+        Pixel(Point::new(0, 0), BLACK).draw(&mut display).unwrap();
+        display
+            .fill_solid(&Rectangle::new(Point::new(0, 1), Size::new(3, 1)), BLACK)
+            .unwrap();
+        assert!(display.blit(8, 0, 4, 1, &[0b0000_0101], 1).is_ok());
+        assert_eq!(display.get_pixel(8, 0), Some(WHITE));
+        assert_eq!(display.get_pixel(9, 0), Some(BLACK));
+
+        let (mut display, black_buffer, _, _) = display.release();
+        assert_eq!(*black_buffer, [0xFE, 0xF5, 0xF8, 0xFF]);
+        display
+            .write_ram(RamPlane::BlackWhite, black_buffer)
+            .await
+            .unwrap();
+        let (interface, _) = display.release();
+        assert!(interface
+            .data_for(0x24)
+            .eq([0x7F, 0xAF, 0x1F, 0xFF].into_iter()));
+    }
+
+    #[cfg(feature = "mock")]
+    #[futures_test::test]
+    async fn configured_refresh_sequences_are_used() {
+        use crate::{command::UpdateSequence, mock::MockInterface};
+
+        let config = builder(ROWS, COLS)
+            .full_refresh_sequence(UpdateSequence::LOAD_AND_DISPLAY_MODE_1)
+            .partial_refresh_sequence(UpdateSequence::LOAD_AND_DISPLAY_MODE_2)
+            .build()
+            .expect("invalid config");
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let mut display = GraphicDisplay::new(
+            Display::new(MockInterface::<256>::new(), config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+        assert!(display.update().await.is_ok());
+        assert!(display.interface().data_for(0x22).eq([0xF7].into_iter()));
+        assert!(display.partial_update(0, 0, COLS.into(), 1).await.is_ok());
+        assert!(display.interface().data_for(0x22).eq([0xFF].into_iter()));
+    }
+
+    #[test]
+    fn clip_discards_pixels_outside() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        {
+            let mut display =
+                GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+            display.set_clip(Rectangle::new(Point::new(1, 2), Size::new(2, 4)));
+            DrawTarget::clear(&mut display, WHITE).unwrap();
+            Pixel(Point::new(0, 0), WHITE).draw(&mut display).unwrap();
+            display.clear_clip();
+            Pixel(Point::new(0, 7), WHITE).draw(&mut display).unwrap();
+        }
+
+        // Rotated by 270 degrees, logical x selects the row from the bottom and y the column
+        #[rustfmt::skip]
+        assert_eq!(black_buffer, [0b0011_1100,
+                                  0b0011_1100,
+                                  0b0000_0001]);
+    }
+
+    #[test]
+    fn drawing_offset_shifts_pixels() {
+        const COLS: u8 = 16;
+        const ROWS: u16 = 2;
+        const SIZE: usize = COLS as usize * ROWS as usize / 8;
+        let mut black_buffer = [0u8; SIZE];
+        let mut work_buffer = [0u8; SIZE];
+        let config = builder(ROWS, COLS)
+            .drawing_offset(4, -1)
+            .build()
+            .expect("invalid config");
+        {
+            let mut display = GraphicDisplay::new(
+                Display::new(NullInterface, config),
+                &mut black_buffer,
+                &mut work_buffer,
+            );
+            Pixel(Point::new(0, 1), WHITE).draw(&mut display).unwrap();
+            // Shifted off the top of the display
+            Pixel(Point::new(0, 0), WHITE).draw(&mut display).unwrap();
+            display
+                .fill_solid(&Rectangle::new(Point::new(6, 2), Size::new(20, 1)), WHITE)
+                .unwrap();
+            assert_eq!(display.pixel(Point::new(0, 1)), Some(WHITE));
+        }
+
+        #[rustfmt::skip]
+        assert_eq!(black_buffer, [0b0000_1000, 0b0000_0000,
+                                  0b0000_0000, 0b0011_1111]);
+    }
+
+    #[futures_test::test]
+    async fn swap_and_update_chooses_refresh() {
+        let mut display: GraphicDisplay<'_, _, [u8; BUFFER_SIZE]> =
+            GraphicDisplay::with_previous_frame_owned(build_mock_display()).unwrap();
+
+        assert!(matches!(
+            display.swap_and_update().await,
+            Ok(Refresh::Skipped)
+        ));
+        // Rotated by 270 degrees, this is the first byte of the last row
+        Pixel(Point::new(0, 0), BLACK).draw(&mut display).unwrap();
+        assert!(matches!(
+            display.swap_and_update().await,
+            Ok(Refresh::Partial(Region {
+                x: 0,
+                y: 2,
+                width: 8,
+                height: 1
+            }))
+        ));
+        display.clear(BLACK);
+        assert!(matches!(display.swap_and_update().await, Ok(Refresh::Full)));
+        assert!(matches!(
+            display.swap_and_update().await,
+            Ok(Refresh::Skipped)
+        ));
+    }
+
+    #[futures_test::test]
+    async fn partial_update_rect_maps_rotated_rectangle() {
+        let mut display: GraphicDisplay<'_, _, [u8; BUFFER_SIZE]> =
+            GraphicDisplay::with_previous_frame_owned(build_mock_display()).unwrap();
+        display.clear(BLACK);
+
+        // Rotated by 270 degrees, logical x selects the row from the bottom
+        let rect = Rectangle::new(Point::new(1, 2), Size::new(1, 3));
+        assert!(display.partial_update_rect(rect).await.is_ok());
+        let (_, _, _, previous_buffer) = display.release();
+        assert_eq!(previous_buffer, Some([0xFF, 0x00, 0xFF]));
+    }
+
+    #[test]
+    fn invert_region_flips_pixels() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        {
+            let mut display =
+                GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+            display.clear(WHITE);
+            Pixel(Point::new(0, 3), BLACK).draw(&mut display).unwrap();
+            // Rotated by 270 degrees, logical x selects the row from the bottom
+            display.invert_region(Rectangle::new(Point::new(0, 2), Size::new(2, 3)));
+        }
+
+        #[rustfmt::skip]
+        assert_eq!(black_buffer, [0b1111_1111,
+                                  0b1100_0111,
+                                  0b1101_0111]);
+    }
+
+    #[futures_test::test]
+    async fn owned_buffers_start_white() {
+        let mut display: GraphicDisplay<'_, _, [u8; BUFFER_SIZE]> =
+            GraphicDisplay::with_previous_frame_owned(build_mock_display()).unwrap();
+        assert!(display.update().await.is_ok());
+        display.clear(BLACK);
+
+        let (_, black_buffer, _, previous_buffer) = display.release();
+        assert_eq!(black_buffer, [0x00; BUFFER_SIZE]);
+        assert_eq!(previous_buffer, Some([0xFF; BUFFER_SIZE]));
+
+        let wrong_size = GraphicDisplay::<'_, _, [u8; 4]>::new_owned(build_mock_display());
+        assert!(matches!(wrong_size, Err(Ssd1680Error::InvalidArgument)));
+        let wrong_size =
+            GraphicDisplay::<'_, _, [u8; 2]>::with_previous_frame_owned(build_mock_display());
+        assert!(matches!(wrong_size, Err(Ssd1680Error::InvalidArgument)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn boxed_buffers_are_sized_for_the_display() {
+        let mut display = GraphicDisplay::new_boxed(build_mock_display());
+        display.clear(BLACK);
+
+        let (_, black_buffer, work_buffer, previous_buffer) = display.release();
+        assert_eq!(*black_buffer, [0x00; BUFFER_SIZE]);
+        assert_eq!(work_buffer.len(), BUFFER_SIZE);
+        assert!(previous_buffer.is_none());
+    }
+
+    #[futures_test::test]
+    async fn differential_partial_update_tracks_previous_frame() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let mut previous_buffer = [0xAAu8; BUFFER_SIZE];
+
+        {
+            let mut display = GraphicDisplay::with_previous_frame(
+                build_mock_display(),
+                &mut black_buffer,
+                &mut work_buffer,
+                &mut previous_buffer,
+            );
+            display.clear(WHITE);
+            display
+                .partial_update(0, 1, COLS.into(), 1)
+                .await
+                .unwrap_or_else(|_| panic!("partial update failed"));
+        }
+
+        assert_eq!(previous_buffer, [0xAA, 0xFF, 0xAA]);
+    }
+
+    #[futures_test::test]
+    async fn updates_fail_while_asleep() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let mut display =
+            GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+
+        assert!(display.reset().await.is_ok());
+        assert_eq!(display.state(), ControllerState::Ready);
+        assert!(display.deep_sleep().await.is_ok());
+        assert_eq!(display.state(), ControllerState::Asleep);
+        assert!(matches!(display.update().await, Err(Ssd1680Error::Asleep)));
+        assert!(display.wake().await.is_ok());
+        assert!(display.update().await.is_ok());
+    }
+
+    #[futures_test::test]
+    async fn invalid_windows_are_rejected() {
+        let mut display = build_mock_display();
+        let image = [0u8; BUFFER_SIZE];
+
+        assert!(display.partial_update(&image, 0, 0, 8, 3).await.is_ok());
+        for (x, y, width, height) in [(0, 1, 8, 3), (4, 0, 8, 1), (0, 0, 4, 1), (0, 0, 8, 0)] {
+            assert!(matches!(
+                display.partial_update(&image, x, y, width, height).await,
+                Err(Ssd1680Error::InvalidArgument)
+            ));
+        }
+        assert!(matches!(
+            display.update(&image[..1]).await,
+            Err(Ssd1680Error::InvalidArgument)
+        ));
+    }
+
+    #[futures_test::test]
+    async fn auto_deep_sleep_wakes_for_next_update() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let config = builder(ROWS, COLS)
+            .auto_deep_sleep(DeepSleepMode::PreserveRAM)
+            .build()
+            .expect("invalid config");
+        let mut display = GraphicDisplay::new(
+            Display::new(NullInterface, config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+
+        assert!(display.update().await.is_ok());
+        assert_eq!(display.state(), ControllerState::Asleep);
+        assert!(display.partial_update(0, 0, COLS.into(), 1).await.is_ok());
+        assert_eq!(display.state(), ControllerState::Asleep);
+    }
+
+    #[futures_test::test]
+    async fn temperature_policy_forces_full_refresh() {
+        struct Cold;
+
+        impl TemperaturePolicy for Cold {
+            fn adjust(&self, temperature: i16, partial: bool) -> RefreshAdjustment {
+                RefreshAdjustment {
+                    force_full_refresh: partial && temperature < 5,
+                    waveform_temperature: None,
+                }
+            }
+        }
+
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let policy = Cold;
+        let config = builder(ROWS, COLS)
+            .temperature_policy(&policy)
+            .build()
+            .expect("invalid config");
+        let mut display = GraphicDisplay::new(
+            Display::new(NullInterface, config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+
+        // The mock reads a temperature of 0 °C
+        assert!(display.partial_update(0, 0, COLS.into(), 1).await.is_ok());
+        assert_eq!(display.partial_updates_since_full_refresh(), 0);
+    }
+
+    #[test]
+    fn drawing_tracks_dirty_region() {
+        const ROWS: u16 = 16;
+        const COLS: u8 = 24;
+        let mut black_buffer = [0u8; (ROWS as usize * COLS as usize) / 8];
+        let mut work_buffer = [0u8; (ROWS as usize * COLS as usize) / 8];
+        let config = builder(ROWS, COLS).build().expect("invalid config");
+        let mut display = GraphicDisplay::new(
+            Display::new(NullInterface, config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+        assert_eq!(display.take_dirty_region(), None);
+
+        Rectangle::with_corners(Point::new(9, 2), Point::new(10, 5))
+            .into_styled(PrimitiveStyleBuilder::new().fill_color(BLACK).build())
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(3, 7), BLACK).draw(&mut display).unwrap();
+
+        assert_eq!(
+            display.take_dirty_region(),
+            Some(Region {
+                x: 0,
+                y: 2,
+                width: 16,
+                height: 6
+            })
+        );
+        assert_eq!(display.take_dirty_region(), None);
+    }
+
+    #[test]
+    fn set_rotation_changes_coordinate_mapping() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+
+        {
+            let mut display =
+                GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+            display.clear(WHITE);
+            assert_eq!(display.size(), Size::new(ROWS.into(), COLS.into()));
+
+            display.set_rotation(Rotation::Rotate0);
+            assert_eq!(display.size(), Size::new(COLS.into(), ROWS.into()));
+            Pixel(Point::new(7, 0), BLACK).draw(&mut display).unwrap();
+        }
+
+        assert_eq!(black_buffer, [0b11111110, 0xFF, 0xFF]);
+    }
+
+    #[futures_test::test]
+    async fn scroll_vertical_follows_the_rotation() {
+        let mut black_buffer = [0x01, 0x02, 0x03];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        // Rotated by 270 degrees, logical y runs along the physical columns
+        let all = Rectangle::new(Point::zero(), Size::new(ROWS.into(), COLS.into()));
+
+        {
+            let mut display =
+                GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+            assert!(display.reset().await.is_ok());
+            assert!(display.scroll_vertical(all, 1).await.is_ok());
+        }
+        assert_eq!(black_buffer, [0x03, 0x05, 0x07]);
+
+        {
+            let mut display =
+                GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut work_buffer);
+            assert!(display.reset().await.is_ok());
+            assert!(display.scroll_vertical(all, -2).await.is_ok());
+            // Logical x selects the row from the bottom, so this is the middle row
+            let column = Rectangle::new(Point::new(1, 4), Size::new(1, 8));
+            assert!(display.scroll_vertical(column, 8).await.is_ok());
+        }
+        assert_eq!(black_buffer, [0xC0, 0xCF, 0xC1]);
+    }
+
     #[test]
     fn make_sub_image_creates_subset_image_with_8_pixels_per_byte_horizontally() {
         const COLS: u16 = 48; // 6 bytes
@@ -347,6 +1647,8 @@ mod tests {
             &buffer,
             &mut work_buffer,
             (COLS / PIXELS_PER_BYTE) as u8,
+            ROWS,
+            FrameLayout::RowMajor,
             start_x_px,
             start_y_px,
             width_px,
@@ -355,4 +1657,60 @@ mod tests {
         assert_eq!(result_slice.len(), expected_size);
         assert_eq!(result_slice, expected_buffer);
     }
+
+    #[test]
+    fn make_sub_image_column_major_copies_byte_columns() {
+        const COLS: u16 = 24; // 3 bytes
+        const ROWS: u16 = 4;
+        // Byte column 0, then byte column 1, then byte column 2
+        let buffer: [u8; 12] = [
+            0x00, 0x01, 0x02, 0x03, 0x10, 0x11, 0x12, 0x13, 0x20, 0x21, 0x22, 0x23,
+        ];
+        let mut work_buffer = [0_u8; 12];
+        let result_slice = make_sub_image(
+            &buffer,
+            &mut work_buffer,
+            (COLS / 8) as u8,
+            ROWS,
+            FrameLayout::ColumnMajor,
+            8,
+            1,
+            16,
+            2,
+        );
+        assert_eq!(result_slice, [0x11, 0x12, 0x21, 0x22]);
+    }
+
+    #[test]
+    fn diff_bounds_covers_changed_bytes() {
+        // 3 bytes wide, 4 rows
+        let previous = [0u8; 12];
+        let mut current = [0u8; 12];
+        assert_eq!(
+            diff_bounds(&current, &previous, 3, 4, FrameLayout::RowMajor),
+            None
+        );
+
+        current[4] = 0x01; // x byte 1, row 1
+        current[9] = 0x80; // x byte 0, row 3
+        assert_eq!(
+            diff_bounds(&current, &previous, 3, 4, FrameLayout::RowMajor),
+            Some(Region {
+                x: 0,
+                y: 1,
+                width: 16,
+                height: 3
+            })
+        );
+        // x byte 1, row 0 and x byte 2, row 1
+        assert_eq!(
+            diff_bounds(&current, &previous, 3, 4, FrameLayout::ColumnMajor),
+            Some(Region {
+                x: 8,
+                y: 0,
+                width: 16,
+                height: 2
+            })
+        );
+    }
 }
@@ -0,0 +1,159 @@
+//! Drawing with embedded-graphics 0.7, enabled by the `graphics-0_7` feature.
+//!
+//! embedded-graphics 0.7 is built on `embedded-graphics-core` 0.3, whose traits are distinct from
+//! those of 0.4 used by the rest of the crate. This module implements the 0.3 `DrawTarget` and
+//! `OriginDimensions` traits for [GraphicDisplay], [Frame] and [Tiled], so projects that can't
+//! move to embedded-graphics 0.8 yet can draw to them. The two versions of
+//! `embedded-graphics-core` can be used side by side without a dependency conflict.
+//!
+//! The pixels are converted to the 0.4 types and drawn as with embedded-graphics 0.8, so clipping
+//! and the drawing offset apply in the same way. `GetPixel` doesn't exist in 0.3.
+//!
+//! [GraphicDisplay]: ../graphics/struct.GraphicDisplay.html
+//! [Frame]: ../frame/struct.Frame.html
+//! [Tiled]: ../tiled/struct.Tiled.html
+
+use crate::{
+    frame::Frame,
+    graphics::GraphicDisplay,
+    interface::DisplayInterface,
+    tiled::{Tile, Tiled},
+};
+use core::convert::Infallible;
+use embedded_graphics_core as core_0_4;
+use embedded_graphics_core_0_3::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Point, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Pixel,
+};
+
+fn point(point: Point) -> core_0_4::geometry::Point {
+    core_0_4::geometry::Point::new(point.x, point.y)
+}
+
+fn rectangle(rectangle: &Rectangle) -> core_0_4::primitives::Rectangle {
+    let size = core_0_4::geometry::Size::new(rectangle.size.width, rectangle.size.height);
+    core_0_4::primitives::Rectangle::new(point(rectangle.top_left), size)
+}
+
+fn color(color: BinaryColor) -> core_0_4::pixelcolor::BinaryColor {
+    match color {
+        BinaryColor::Off => core_0_4::pixelcolor::BinaryColor::Off,
+        BinaryColor::On => core_0_4::pixelcolor::BinaryColor::On,
+    }
+}
+
+fn size(size: core_0_4::geometry::Size) -> Size {
+    Size::new(size.width, size.height)
+}
+
+/// Implement the 0.3 traits by converting to the 0.4 implementations.
+macro_rules! draw_target_0_3 {
+    ($(
+        $(#[$meta:meta])*
+        impl<$($generic:ident),*> for $target:ty where { $($bounds:tt)* }
+    )*) => {
+        $(
+            $(#[$meta])*
+            impl<$($generic),*> DrawTarget for $target
+            where
+                $($bounds)*
+            {
+                type Color = BinaryColor;
+                type Error = Infallible;
+
+                fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Infallible>
+                where
+                    Iter: IntoIterator<Item = Pixel<BinaryColor>>,
+                {
+                    let pixels = pixels
+                        .into_iter()
+                        .map(|Pixel(at, on)| core_0_4::Pixel(point(at), color(on)));
+                    core_0_4::draw_target::DrawTarget::draw_iter(self, pixels)
+                }
+
+                fn fill_solid(&mut self, area: &Rectangle, on: BinaryColor) -> Result<(), Infallible> {
+                    core_0_4::draw_target::DrawTarget::fill_solid(self, &rectangle(area), color(on))
+                }
+
+                fn clear(&mut self, on: BinaryColor) -> Result<(), Infallible> {
+                    core_0_4::draw_target::DrawTarget::clear(self, color(on))
+                }
+            }
+
+            $(#[$meta])*
+            impl<$($generic),*> OriginDimensions for $target
+            where
+                $($bounds)*
+            {
+                fn size(&self) -> Size {
+                    size(core_0_4::geometry::OriginDimensions::size(self))
+                }
+            }
+        )*
+    };
+}
+
+draw_target_0_3! {
+    impl<I, B> for GraphicDisplay<'_, I, B> where {
+        I: DisplayInterface,
+        B: AsMut<[u8]> + AsRef<[u8]>,
+    }
+    impl<B> for Frame<B> where { B: AsMut<[u8]> + AsRef<[u8]> }
+    impl<A, B> for Tiled<A, B> where { A: Tile, B: Tile }
+    #[cfg(feature = "blocking")]
+    impl<I, B> for crate::blocking::GraphicDisplay<'_, I, B> where {
+        I: crate::blocking::BlockingInterface,
+        B: AsMut<[u8]> + AsRef<[u8]>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        display::{Dimensions, Rotation},
+        frame::Frame,
+        graphics::{BLACK, WHITE},
+    };
+    use embedded_graphics_core as core_0_4;
+    use embedded_graphics_core_0_3 as core_0_3;
+
+    const DIMENSIONS: Dimensions = Dimensions { rows: 8, cols: 16 };
+
+    #[test]
+    fn frames_can_be_drawn_with_embedded_graphics_0_7() {
+        use core_0_3::{
+            pixelcolor::BinaryColor,
+            prelude::{DrawTarget, OriginDimensions, Point, Size},
+            primitives::Rectangle,
+            Pixel,
+        };
+
+        let mut frame = Frame::new([0u8; 16], DIMENSIONS, Rotation::Rotate90);
+        assert_eq!(frame.size(), Size::new(8, 16));
+        frame.clear(BinaryColor::On).unwrap();
+        let area = Rectangle::new(Point::new(0, 8), Size::new(8, 8));
+        frame.fill_solid(&area, BinaryColor::Off).unwrap();
+        let pixel = Pixel(Point::new(7, 0), BinaryColor::Off);
+        frame.draw_iter([pixel]).unwrap();
+        assert_eq!(frame.buffer(), drawn_with_0_4().buffer());
+    }
+
+    /// The frame drawn by the test, drawn with `embedded-graphics-core` 0.4.
+    fn drawn_with_0_4() -> Frame<[u8; 16]> {
+        use core_0_4::{
+            prelude::{DrawTarget, Point, Size},
+            primitives::Rectangle,
+            Pixel,
+        };
+
+        let mut frame = Frame::new([0u8; 16], DIMENSIONS, Rotation::Rotate90);
+        frame.clear(WHITE).unwrap();
+        let area = Rectangle::new(Point::new(0, 8), Size::new(8, 8));
+        frame.fill_solid(&area, BLACK).unwrap();
+        frame.draw_iter([Pixel(Point::new(7, 0), BLACK)]).unwrap();
+        frame
+    }
+}
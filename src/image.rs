@@ -0,0 +1,266 @@
+//! Loading of XBM and binary PBM (P4) images, enabled by the `image-formats` feature.
+//!
+//! These are the formats produced by common e-paper image tools. Both use set bits for black
+//! pixels, and XBM packs the leftmost pixel into the least significant bit, whereas the frame
+//! buffer uses set bits for white in the configured `BitOrder`. The images are converted a row
+//! at a time as they are copied into a `GraphicDisplay` with `blit_pbm` or `blit_xbm`, so no
+//! additional buffer is needed. An XBM image looks like this:
+//!
+//! ```text
+//! #define arrow_width 4
+//! #define arrow_height 2
+//! static unsigned char arrow_bits[] = { 0x01, 0x0F };
+//! ```
+
+use crate::{display::MAX_GATE_OUTPUTS, graphics::GraphicDisplay, interface::DisplayInterface};
+
+/// The longest row of an image, in bytes, that fits on the largest display.
+const MAX_ROW_BYTES: usize = MAX_GATE_OUTPUTS.div_ceil(8) as usize;
+
+/// An error loading an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ImageError {
+    /// The image is not a valid XBM or P4 PBM image, or its data is truncated.
+    Malformed,
+    /// The image is wider than the largest display the controller supports.
+    TooWide,
+}
+
+impl<I, B> GraphicDisplay<'_, I, B>
+where
+    I: DisplayInterface,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Copy a binary PBM (P4) image into the buffer at `x`, `y`, in rotated coordinates.
+    ///
+    /// Pixels outside the display are discarded, as for `blit`.
+    pub fn blit_pbm(&mut self, x: u32, y: u32, pbm: &[u8]) -> Result<(), ImageError> {
+        let (width, height, data) = parse_pbm(pbm)?;
+        let row_bytes = width.div_ceil(8) as usize;
+        if row_bytes > MAX_ROW_BYTES {
+            return Err(ImageError::TooWide);
+        }
+        let size = row_bytes
+            .checked_mul(height as usize)
+            .ok_or(ImageError::Malformed)?;
+        if data.len() < size {
+            return Err(ImageError::Malformed);
+        }
+        if size == 0 {
+            return Ok(());
+        }
+        let bit_order = self.bit_order();
+        let mut row = [0u8; MAX_ROW_BYTES];
+        for (index, source) in data
+            .chunks_exact(row_bytes)
+            .take(height as usize)
+            .enumerate()
+        {
+            // Rows beyond the largest coordinate can't be on the display
+            let Some(row_y) = y.checked_add(index as u32) else {
+                break;
+            };
+            for (byte, source) in row.iter_mut().zip(source) {
+                *byte = !bit_order.convert(*source);
+            }
+            self.blit_row(x, row_y, width, &row[..row_bytes]);
+        }
+        Ok(())
+    }
+
+    /// Copy an XBM image into the buffer at `x`, `y`, in rotated coordinates.
+    ///
+    /// `xbm` is the C source of the image, as written by image tools. Pixels outside the display
+    /// are discarded, as for `blit`.
+    pub fn blit_xbm(&mut self, x: u32, y: u32, xbm: &str) -> Result<(), ImageError> {
+        let width = xbm_define(xbm, "_width")?;
+        let height = xbm_define(xbm, "_height")?;
+        let row_bytes = width.div_ceil(8) as usize;
+        if row_bytes > MAX_ROW_BYTES {
+            return Err(ImageError::TooWide);
+        }
+        let start = xbm.find('{').ok_or(ImageError::Malformed)? + 1;
+        let end = start + xbm[start..].find('}').ok_or(ImageError::Malformed)?;
+        let mut bytes = xbm[start..end]
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(parse_hex_byte);
+        if row_bytes == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let bit_order = self.bit_order();
+        let mut row = [0u8; MAX_ROW_BYTES];
+        for index in 0..height {
+            for byte in &mut row[..row_bytes] {
+                let source = bytes.next().ok_or(ImageError::Malformed)??;
+                *byte = !bit_order.convert(source.reverse_bits());
+            }
+            if let Some(row_y) = y.checked_add(index) {
+                self.blit_row(x, row_y, width, &row[..row_bytes]);
+            }
+        }
+        Ok(())
+    }
+
+    fn blit_row(&mut self, x: u32, y: u32, width: u32, row: &[u8]) {
+        // The row always holds enough data, so only errors for invalid arguments are possible
+        let _ = self.blit(x, y, width, 1, row, row.len());
+    }
+}
+
+/// Returns the width, height and pixel data of a P4 PBM image.
+fn parse_pbm(pbm: &[u8]) -> Result<(u32, u32, &[u8]), ImageError> {
+    let rest = pbm.strip_prefix(b"P4").ok_or(ImageError::Malformed)?;
+    let (width, rest) = pbm_number(rest)?;
+    let (height, rest) = pbm_number(rest)?;
+    // A single whitespace character separates the header from the data
+    match rest.split_first() {
+        Some((byte, data)) if byte.is_ascii_whitespace() => Ok((width, height, data)),
+        _ => Err(ImageError::Malformed),
+    }
+}
+
+/// Parse a decimal number in a PBM header, skipping the whitespace and comments before it.
+fn pbm_number(mut header: &[u8]) -> Result<(u32, &[u8]), ImageError> {
+    loop {
+        match header.first() {
+            Some(byte) if byte.is_ascii_whitespace() => header = &header[1..],
+            Some(b'#') => {
+                let end = header.iter().position(|&byte| byte == b'\n');
+                header = &header[end.ok_or(ImageError::Malformed)?..];
+            }
+            _ => break,
+        }
+    }
+    let digits = header
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let number = core::str::from_utf8(&header[..digits])
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .ok_or(ImageError::Malformed)?;
+    Ok((number, &header[digits..]))
+}
+
+/// Returns the value of the `#define` whose name ends in `suffix`.
+fn xbm_define(xbm: &str, suffix: &str) -> Result<u32, ImageError> {
+    xbm.lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("#define"), Some(name), Some(value)) if name.ends_with(suffix) => Some(value),
+                _ => None,
+            }
+        })
+        .next()
+        .and_then(|value| value.parse().ok())
+        .ok_or(ImageError::Malformed)
+}
+
+fn parse_hex_byte(token: &str) -> Result<u8, ImageError> {
+    let digits = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+        .ok_or(ImageError::Malformed)?;
+    u8::from_str_radix(digits, 16).map_err(|_| ImageError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        display::{Display, Rotation},
+        graphics::WHITE,
+        test_util::{builder, NullInterface},
+    };
+
+    const ROWS: u16 = 3;
+    const COLS: u8 = 8;
+    const BUFFER_SIZE: usize = (ROWS * COLS as u16) as usize / 8;
+
+    /// Clear a display to white, blit an image and return the black buffer.
+    fn blit(
+        blit: impl FnOnce(&mut GraphicDisplay<'_, NullInterface>) -> Result<(), ImageError>,
+    ) -> (Result<(), ImageError>, [u8; BUFFER_SIZE]) {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut work_buffer = [0u8; BUFFER_SIZE];
+        let config = builder(ROWS, COLS).build().expect("invalid config");
+        let result = {
+            let mut display = GraphicDisplay::new(
+                Display::new(NullInterface, config),
+                &mut black_buffer[..],
+                &mut work_buffer[..],
+            );
+            display.set_rotation(Rotation::Rotate0);
+            display.clear(WHITE);
+            blit(&mut display)
+        };
+        (result, black_buffer)
+    }
+
+    #[test]
+    fn images_are_converted_to_buffer_bit_order() {
+        let xbm = "
+            #define arrow_width 4
+            #define arrow_height 3
+            static unsigned char arrow_bits[] = {
+                0x01, 0x0F,
+                0x08 };
+        ";
+        let pbm = b"P4\n# arrow\n4 3\n\x80\xF0\x10";
+        #[rustfmt::skip]
+        let expected = [0b1101_1111,
+                        0b1100_0011,
+                        0b1111_1011];
+        assert_eq!(
+            blit(|display| display.blit_pbm(2, 0, pbm)),
+            (Ok(()), expected)
+        );
+        assert_eq!(
+            blit(|display| display.blit_xbm(2, 0, xbm)),
+            (Ok(()), expected)
+        );
+    }
+
+    #[test]
+    fn truncated_images_are_malformed() {
+        let (result, _) = blit(|display| display.blit_pbm(0, 0, b"P4\n4 3\n\x80"));
+        assert_eq!(result, Err(ImageError::Malformed));
+        let (result, _) = blit(|display| display.blit_xbm(0, 0, "#define a_width 4\n{ 0x01 }"));
+        assert_eq!(result, Err(ImageError::Malformed));
+    }
+
+    #[test]
+    fn empty_images_draw_nothing() {
+        let white = [0xFF; BUFFER_SIZE];
+        assert_eq!(
+            blit(|display| display.blit_pbm(0, 0, b"P4\n0 0\n")),
+            (Ok(()), white)
+        );
+        assert_eq!(
+            blit(|display| display.blit_pbm(0, 0, b"P4\n0 9\n")),
+            (Ok(()), white)
+        );
+        assert_eq!(
+            blit(|display| display.blit_pbm(0, 0, b"P4\n8 0\n")),
+            (Ok(()), white)
+        );
+        let xbm = "#define e_width 0\n#define e_height 4000000000\n{ }";
+        assert_eq!(blit(|display| display.blit_xbm(0, 0, xbm)), (Ok(()), white));
+    }
+
+    #[test]
+    fn hostile_headers_do_not_overflow() {
+        let (result, _) = blit(|display| display.blit_pbm(0, 0, b"P4\n8 4294967295\n\xFF"));
+        assert_eq!(result, Err(ImageError::Malformed));
+        let (result, buffer) = blit(|display| display.blit_pbm(0, u32::MAX, b"P4\n8 2\n\x00\x00"));
+        assert_eq!((result, buffer), (Ok(()), [0xFF; BUFFER_SIZE]));
+        let xbm = "#define a_width 8\n#define a_height 2\n{ 0x00, 0x00 }";
+        let (result, buffer) = blit(|display| display.blit_xbm(0, u32::MAX, xbm));
+        assert_eq!((result, buffer), (Ok(()), [0xFF; BUFFER_SIZE]));
+    }
+}
@@ -1,36 +1,214 @@
-use core::{fmt::Debug, future::Future};
-use embassy_embedded_hal::shared_bus::SpiDeviceError;
-use embassy_time::Timer;
-use embedded_hal::digital::{InputPin, OutputPin};
-use embedded_hal_async::spi::SpiDevice;
+use crate::{
+    busy::{Busy, BusyTiming},
+    chip_select::ChipSelectBus,
+    error::Ssd1680Error,
+    reset::{Reset, ResetTiming},
+    trace::{NoTracer, TracedDelay, Tracer},
+};
+use core::future::Future;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{
+    delay::DelayNs,
+    spi::{SpiBus, SpiDevice},
+};
 
-// Section 15.2 of the HINK-E0213A07 data sheet says to hold for 10ms
-const RESET_DELAY_MS: u64 = 10;
-const TIMEOUT_MS: u32 = 5_000;
-const NUM_RESET_DELAYS_IS_TIMEOUT: u32 = TIMEOUT_MS / (RESET_DELAY_MS as u32);
+/// Default maximum number of bytes written or read in one SPI transfer.
+///
+/// This matches the default limit of Linux's spidev driver.
+// https://github.com/torvalds/linux/blob/ccda4af0f4b92f7b4c308d3acc262f4a7e3affad/drivers/spi/spidev.c#L93
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// How commands that fail with an SPI error are retried by `Interface`.
+///
+/// On noisy cabled installations a transient bus error would otherwise abort a whole refresh,
+/// leaving the panel half drawn. Each failed command is retried up to `retries` times, waiting
+/// `backoff_ms` before the first retry and doubling the wait for each further retry. The default
+/// doesn't retry.
+///
+/// A command is always resent as a whole, together with all of its data. A failed RAM write may
+/// have advanced the controller's address counter, so `Display` also sets up the RAM window and
+/// address again before resending it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    /// Number of times a failed command is retried
+    pub retries: u8,
+    /// Time to wait before the first retry, in milliseconds
+    pub backoff_ms: u32,
+}
 
 /// Trait implemented by displays to provide implementation of core functionality.
 pub trait DisplayInterface {
+    /// The error type of the underlying bus, reported as `Ssd1680Error::Interface`.
     type Error;
 
     /// Send a command to the controller.
     ///
     /// Prefer calling `execute` on a [Command](../command/enum.Command.html) over calling this
     /// directly.
-    fn send_command(&mut self, command: u8) -> impl Future<Output = Result<(), Self::Error>>;
+    fn send_command(
+        &mut self,
+        command: u8,
+    ) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>>;
 
     /// Send data for a command.
-    fn send_data(&mut self, data: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+    fn send_data(
+        &mut self,
+        data: &[u8],
+    ) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>>;
+
+    /// Send data for a command, produced by an iterator.
+    ///
+    /// This allows a frame to be generated as it is sent, rather than held in a buffer. The
+    /// default implementation collects the data into 64 byte chunks, passing each to `send_data`.
+    fn send_data_iter(
+        &mut self,
+        data: impl Iterator<Item = u8>,
+    ) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>> {
+        async move {
+            let mut data = data;
+            let mut chunk = [0u8; 64];
+            loop {
+                let mut len = 0;
+                for (slot, byte) in chunk.iter_mut().zip(&mut data) {
+                    *slot = byte;
+                    len += 1;
+                }
+                if len > 0 {
+                    self.send_data(&chunk[..len]).await?;
+                }
+                if len < chunk.len() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Send a command followed by its data, if any.
+    ///
+    /// The default implementation calls `send_command` then `send_data`. Interfaces that control
+    /// chip select themselves override this to send both within a single chip select assertion,
+    /// toggling DC in between, as `Interface` does when built with `Interface::from_bus`.
+    fn send_command_with_data(
+        &mut self,
+        command: u8,
+        data: &[u8],
+    ) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>> {
+        async move {
+            self.send_command(command).await?;
+            if !data.is_empty() {
+                self.send_data(data).await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Read data returned by the previously sent command.
+    ///
+    /// This requires the controller's SDA line to be readable by the SPI device, which is not the
+    /// case on every board. The default returns `Ssd1680Error::Unsupported`.
+    fn read_data(
+        &mut self,
+        data: &mut [u8],
+    ) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>> {
+        let _ = data;
+        async { Err(Ssd1680Error::Unsupported) }
+    }
 
     /// Reset the controller.
-    fn reset(&mut self) -> impl Future<Output = ()>;
+    ///
+    /// The default does nothing, as for boards without a reset pin, leaving `Display::reset` to
+    /// rely on its software reset.
+    fn reset(&mut self) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>> {
+        async { Ok(()) }
+    }
 
     /// Wait for the controller to indicate it is not busy.
-    fn busy_wait(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
+    fn busy_wait(&mut self) -> impl Future<Output = Result<(), Ssd1680Error<Self::Error>>>;
+
+    /// Sample the BUSY signal once, without waiting.
+    ///
+    /// The default returns `Ssd1680Error::Unsupported`, so a refresh can only be awaited with
+    /// `busy_wait`.
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<Self::Error>> {
+        Err(Ssd1680Error::Unsupported)
+    }
+
+    /// Wait before retrying a command that failed with `Ssd1680Error::Interface`, returning
+    /// whether it should be retried.
+    ///
+    /// `attempt` is the number of retries already made for the command. The command is then
+    /// resent as a whole, see [RetryPolicy]. The default never retries.
+    fn retry_backoff(&mut self, attempt: u8) -> impl Future<Output = bool> {
+        let _ = attempt;
+        async { false }
+    }
+
+    /// How long the last `busy_wait` waited in milliseconds, if known.
+    ///
+    /// This is used to measure refresh durations for `Display::stats`. The default returns `None`.
+    fn last_busy_ms(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// The SPI connection of an [Interface] to the controller.
+///
+/// Implemented for every `SpiDevice`, which asserts chip select for each transfer, and for
+/// [ChipSelectBus](../chip_select/struct.ChipSelectBus.html), which can keep the controller
+/// selected while DC is toggled between a command and its data.
+pub trait Transport {
+    /// The error type of the SPI bus.
+    type Error;
+
+    /// Write `data` to the controller.
+    fn write(&mut self, data: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Read `data` from the controller.
+    fn read(&mut self, data: &mut [u8]) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Keep the controller selected across the following transfers, until `deselect`.
+    ///
+    /// The default does nothing, leaving each transfer selected on its own.
+    fn select(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        async { Ok(()) }
+    }
+
+    /// Release the selection made with `select`.
+    fn deselect(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        async { Ok(()) }
+    }
+}
+
+impl<T> Transport for T
+where
+    T: SpiDevice<u8>,
+{
+    type Error = T::Error;
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), T::Error> {
+        SpiDevice::write(self, data).await
+    }
+
+    async fn read(&mut self, data: &mut [u8]) -> Result<(), T::Error> {
+        SpiDevice::read(self, data).await
+    }
 }
 
 /// The hardware interface to a display.
 ///
+/// Delays for the reset pulse and BUSY polling come from any `embedded_hal_async` `DelayNs`
+/// provider, so no particular executor is required. With the `embassy-time` feature enabled,
+/// `Interface::new_embassy` uses the embassy timer instead. HALs that only provide an `SpiBus`
+/// can use `Interface::from_bus` with a chip select pin instead of an `SpiDevice`.
+///
+/// The BUSY pin is polled, unless it is wrapped in a [WaitPin](../busy/struct.WaitPin.html).
+/// Boards without a BUSY pin can pass a [FixedDelay](../busy/struct.FixedDelay.html) instead, and
+/// boards without a reset pin can pass [NoReset](../reset/struct.NoReset.html).
+///
+/// The command stream can be observed for debugging by setting a
+/// [Tracer](../trace/trait.Tracer.html).
+///
 /// ### Example
 ///
 /// This example uses the Linux implementation of the embedded HAL traits to build a display
@@ -82,118 +260,517 @@ pub trait DisplayInterface {
 ///     .expect("reset Direction");
 /// reset.set_value(1).expect("reset Value set to 1");
 ///
-/// // Build the interface from the pins and SPI device
-/// let controller = ssd1680::Interface::new(spi, cs, busy, dc, reset);
-
-#[allow(dead_code)] // Prevent warning about CS being unused
-pub struct Interface<SpiDev, BUS, CS, BUSY, DC, RESET>
-where
-    SpiDev: SpiDevice<u8, Error = SpiDeviceError<BUS, CS>>,
-    BUS: embedded_hal::spi::Error + Debug + PartialEq,
-    CS: Debug + PartialEq,
-{
+/// // Build the interface from the pins, SPI device and a delay provider. Any
+/// // `embedded_hal_async::spi::SpiDevice` can be used, e.g. one wrapping `spi` with the chip
+/// // select pin `cs`.
+/// let controller = ssd1680::Interface::new(spi_device, busy, dc, reset, Delay);
+/// ```
+pub struct Interface<SPI, BUSY, DC, RESET, DELAY, TRACER = NoTracer> {
     /// SPI Device interface (chip select is owned by this)
-    spi: SpiDev,
+    spi: SPI,
     /// Active low busy pin (input)
     busy: BUSY,
     /// Data/Command Control Pin (High for data, Low for command) (output)
     dc: DC,
-    /// Pin for resetting the controller (output)
+    /// Pin for resetting the controller (output), or `NoReset`
     reset: RESET,
+    /// Delay provider for reset pulses and busy polling
+    delay: DELAY,
+    /// Timing of the hardware reset sequence
+    reset_timing: ResetTiming,
+    /// Timeout and polling interval for the BUSY pin
+    busy_timing: BusyTiming,
+    /// Maximum number of bytes written or read in one SPI transfer
+    chunk_size: usize,
+    /// Retries of failed SPI transfers
+    retry_policy: RetryPolicy,
+    /// Receives the command stream for debugging
+    tracer: TRACER,
+    /// How long the last busy wait took, if the BUSY pin reports it
+    last_busy_ms: Option<u32>,
 }
 
-impl<SpiDev, BUS, CS, BUSY, DC, RESET> Interface<SpiDev, BUS, CS, BUSY, DC, RESET>
+impl<SPI, BUSY, DC, RESET, DELAY> Interface<SPI, BUSY, DC, RESET, DELAY>
 where
-    SpiDev: SpiDevice<u8, Error = SpiDeviceError<BUS, CS>>,
-    BUS: embedded_hal::spi::Error + Debug + PartialEq,
-    CS: Debug + PartialEq,
-    BUSY: InputPin,
+    SPI: Transport,
+    BUSY: Busy,
     DC: OutputPin,
-    RESET: OutputPin,
+    RESET: Reset,
+    DELAY: DelayNs,
 {
     /// Create a new Interface from embedded hal traits.
-    pub fn new(spi: SpiDev, busy: BUSY, dc: DC, reset: RESET) -> Self {
+    pub fn new(spi: SPI, busy: BUSY, dc: DC, reset: RESET, delay: DELAY) -> Self {
         Self {
             spi,
             busy,
             dc,
             reset,
+            delay,
+            reset_timing: ResetTiming::default(),
+            busy_timing: BusyTiming::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            retry_policy: RetryPolicy::default(),
+            tracer: NoTracer,
+            last_busy_ms: None,
         }
     }
+}
 
-    async fn write(&mut self, data: &[u8]) -> Result<(), SpiDeviceError<BUS, CS>> {
-        // Linux has a default limit of 4096 bytes per SPI transfer
-        // https://github.com/torvalds/linux/blob/ccda4af0f4b92f7b4c308d3acc262f4a7e3affad/drivers/spi/spidev.c#L93
-        if cfg!(target_os = "linux") {
-            for data_chunk in data.chunks(4096) {
-                self.spi.write(data_chunk).await?;
-            }
-        } else {
-            self.spi.write(data).await?;
+impl<SPI, BUSY, DC, RESET, DELAY, TRACER> Interface<SPI, BUSY, DC, RESET, DELAY, TRACER>
+where
+    SPI: Transport,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+    DELAY: DelayNs,
+    TRACER: Tracer,
+{
+    /// Set a tracer to receive the command stream sent to the controller.
+    ///
+    /// The tracer is also called while waiting for BUSY, see `Tracer::on_busy_poll`, e.g. to feed
+    /// a watchdog during a refresh. A `&mut` reference to a tracer can be passed to keep access to it.
+    pub fn tracer<T: Tracer>(self, tracer: T) -> Interface<SPI, BUSY, DC, RESET, DELAY, T> {
+        Interface {
+            spi: self.spi,
+            busy: self.busy,
+            dc: self.dc,
+            reset: self.reset,
+            delay: self.delay,
+            reset_timing: self.reset_timing,
+            busy_timing: self.busy_timing,
+            chunk_size: self.chunk_size,
+            retry_policy: self.retry_policy,
+            tracer,
+            last_busy_ms: self.last_busy_ms,
         }
+    }
 
-        Ok(())
+    /// Set the timing of the hardware reset sequence.
+    pub fn reset_timing(self, reset_timing: ResetTiming) -> Self {
+        Self {
+            reset_timing,
+            ..self
+        }
     }
 
-    async fn busy_wait_with_timeout(&mut self) -> Result<(), ()> {
-        let mut count = 0;
-        while match self.busy.is_high() {
-            Ok(x) => {
-                if x {
-                    Timer::after_millis(RESET_DELAY_MS).await;
-                }
-                x
-            }
-            _ => return Err(()),
-        } {
-            if count > NUM_RESET_DELAYS_IS_TIMEOUT {
-                return Err(());
-            }
-            count += 1;
+    /// Set the timeout and polling interval used when waiting for the BUSY pin.
+    pub fn busy_timing(self, busy_timing: BusyTiming) -> Self {
+        Self {
+            busy_timing,
+            ..self
         }
+    }
+
+    /// Set the maximum number of bytes written or read in one SPI transfer.
+    ///
+    /// Longer writes and reads, such as those of `Display::read_ram`, are split into several
+    /// transfers, for example to fit the descriptor limit of a DMA engine. Defaults to
+    /// [DEFAULT_CHUNK_SIZE].
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            ..self
+        }
+    }
+
+    /// Set how commands that fail with an SPI error are retried.
+    pub fn retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
+        for data_chunk in data.chunks(self.chunk_size) {
+            self.spi.write(data_chunk).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn write_command_with_data(
+        &mut self,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<SPI::Error>> {
+        self.dc.set_low().map_err(|_| Ssd1680Error::DcPinError)?;
+        self.write(&[command]).await?;
+        self.dc.set_high().map_err(|_| Ssd1680Error::DcPinError)?;
+        self.write(data).await?;
         Ok(())
     }
 }
 
-impl<SpiDev, BUS, CS, BUSY, DC, RESET> DisplayInterface
-    for Interface<SpiDev, BUS, CS, BUSY, DC, RESET>
+impl<BUS, CS, BUSY, DC, RESET, DELAY>
+    Interface<ChipSelectBus<BUS, CS, DELAY>, BUSY, DC, RESET, DELAY>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+    DELAY: DelayNs + Clone,
+{
+    /// Create a new Interface from an exclusively owned SPI bus and its chip select pin.
+    ///
+    /// The chip select pin is toggled around each transfer, and kept asserted while a command and
+    /// its data are sent with `send_command_with_data`. Fails if the pin cannot be released.
+    pub fn from_bus(
+        bus: BUS,
+        cs: CS,
+        busy: BUSY,
+        dc: DC,
+        reset: RESET,
+        delay: DELAY,
+    ) -> Result<Self, CS::Error> {
+        let spi = ChipSelectBus::new(bus, cs, delay.clone())?;
+        Ok(Self::new(spi, busy, dc, reset, delay))
+    }
+
+    /// Set the delays after asserting and before releasing the chip select pin, in nanoseconds.
+    pub fn cs_timing(self, setup_ns: u32, hold_ns: u32) -> Self {
+        Self {
+            spi: self.spi.cs_timing(setup_ns, hold_ns),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "embassy-time")]
+impl<SPI, BUSY, DC, RESET> Interface<SPI, BUSY, DC, RESET, embassy_time::Delay>
+where
+    SPI: Transport,
+    BUSY: Busy,
+    DC: OutputPin,
+    RESET: Reset,
+{
+    /// Create a new Interface using the embassy-time timer for delays.
+    pub fn new_embassy(spi: SPI, busy: BUSY, dc: DC, reset: RESET) -> Self {
+        Self::new(spi, busy, dc, reset, embassy_time::Delay)
+    }
+}
+
+impl<SPI, BUSY, DC, RESET, DELAY, TRACER> DisplayInterface
+    for Interface<SPI, BUSY, DC, RESET, DELAY, TRACER>
 where
-    SpiDev: SpiDevice<u8, Error = SpiDeviceError<BUS, CS>>,
-    BUS: embedded_hal::spi::Error + Debug + PartialEq,
-    CS: Debug + PartialEq,
-    BUSY: InputPin,
+    SPI: Transport,
+    BUSY: Busy,
     DC: OutputPin,
-    DC::Error: Debug,
-    RESET: OutputPin,
-    RESET::Error: Debug,
+    RESET: Reset,
+    DELAY: DelayNs,
+    TRACER: Tracer,
 {
-    type Error = SpiDev::Error;
+    type Error = SPI::Error;
 
-    async fn reset(&mut self) {
-        self.reset.set_low().unwrap();
-        Timer::after_millis(RESET_DELAY_MS).await;
-        self.reset.set_high().unwrap();
-        Timer::after_millis(RESET_DELAY_MS).await;
+    async fn reset(&mut self) -> Result<(), Ssd1680Error<SPI::Error>> {
+        self.reset.pulse(&mut self.delay, self.reset_timing).await
     }
 
-    async fn send_command(&mut self, command: u8) -> Result<(), SpiDeviceError<BUS, CS>> {
-        self.dc.set_low().unwrap();
+    async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<SPI::Error>> {
+        self.tracer.on_command(command);
+        self.busy.command_sent(command);
+        self.dc.set_low().map_err(|_| Ssd1680Error::DcPinError)?;
         self.write(&[command]).await?;
-        self.dc.set_high().unwrap();
+        self.dc.set_high().map_err(|_| Ssd1680Error::DcPinError)?;
+
+        Ok(())
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<SPI::Error>> {
+        self.tracer.on_data(data.len());
+        self.busy.data_sent(data);
+        self.dc.set_high().map_err(|_| Ssd1680Error::DcPinError)?;
+        self.write(data).await?;
+        Ok(())
+    }
+
+    async fn send_command_with_data(
+        &mut self,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<SPI::Error>> {
+        self.tracer.on_command(command);
+        self.busy.command_sent(command);
+        if !data.is_empty() {
+            self.tracer.on_data(data.len());
+            self.busy.data_sent(data);
+        }
+        self.spi.select().await?;
+        // Deselect even if the transfer failed, then report the first error
+        let result = self.write_command_with_data(command, data).await;
+        let deselected = self.spi.deselect().await;
+        result?;
+        deselected?;
+        Ok(())
+    }
+
+    async fn read_data(&mut self, data: &mut [u8]) -> Result<(), Ssd1680Error<SPI::Error>> {
+        self.dc.set_high().map_err(|_| Ssd1680Error::DcPinError)?;
+        for data_chunk in data.chunks_mut(self.chunk_size) {
+            self.spi.read(data_chunk).await?;
+        }
+        Ok(())
+    }
 
+    async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<SPI::Error>> {
+        let mut delay = TracedDelay {
+            delay: &mut self.delay,
+            tracer: &mut self.tracer,
+            waited_ns: 0,
+        };
+        let waited_ms = self.busy.wait_ready(&mut delay, self.busy_timing).await?;
+        self.tracer.on_busy(waited_ms);
+        self.last_busy_ms = waited_ms;
         Ok(())
     }
 
-    async fn send_data(&mut self, data: &[u8]) -> Result<(), SpiDeviceError<BUS, CS>> {
-        self.dc.set_high().unwrap();
-        self.write(data).await
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<SPI::Error>> {
+        self.busy.is_busy()
+    }
+
+    async fn retry_backoff(&mut self, attempt: u8) -> bool {
+        if attempt >= self.retry_policy.retries {
+            return false;
+        }
+        let backoff_ms = self
+            .retry_policy
+            .backoff_ms
+            .saturating_mul(1 << attempt.min(31));
+        self.delay.delay_ms(backoff_ms).await;
+        true
+    }
+
+    fn last_busy_ms(&self) -> Option<u32> {
+        self.last_busy_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::Command, display::RamPlane, test_util::builder, Display};
+    use core::{cell::Cell, convert::Infallible};
+
+    /// An interface implementing only the required methods.
+    struct WriteOnly;
+
+    impl DisplayInterface for WriteOnly {
+        type Error = ();
+
+        async fn send_command(&mut self, _command: u8) -> Result<(), Ssd1680Error<()>> {
+            Ok(())
+        }
+
+        async fn send_data(&mut self, _data: &[u8]) -> Result<(), Ssd1680Error<()>> {
+            Ok(())
+        }
+
+        async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<()>> {
+            Ok(())
+        }
+    }
+
+    #[futures_test::test]
+    async fn optional_methods_have_defaults() {
+        let mut interface = WriteOnly;
+        assert_eq!(interface.reset().await, Ok(()));
+        assert_eq!(interface.is_busy(), Err(Ssd1680Error::Unsupported));
+        assert_eq!(
+            interface.read_data(&mut [0; 2]).await,
+            Err(Ssd1680Error::Unsupported)
+        );
+        assert_eq!(Command::SoftReset.execute(&mut interface).await, Ok(()));
+
+        let config = builder(8, 8).build().expect("invalid config");
+        let mut display = Display::new(interface, config);
+        assert_eq!(display.reset().await, Ok(()));
+        assert_eq!(
+            display.read_temperature().await,
+            Err(Ssd1680Error::Unsupported)
+        );
+    }
+
+    /// Records commands, failing the first `failures` RAM writes part way through their data.
+    struct Flaky {
+        commands: [u8; 32],
+        len: usize,
+        failures: u8,
+        retries: u8,
     }
 
-    async fn busy_wait(&mut self) -> Result<(), SpiDeviceError<BUS, CS>> {
-        if self.busy_wait_with_timeout().await.is_err() {
-            Err(SpiDeviceError::Config)
-        } else {
+    impl DisplayInterface for Flaky {
+        type Error = ();
+
+        async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<()>> {
+            self.commands[self.len] = command;
+            self.len += 1;
+            Ok(())
+        }
+
+        async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<()>> {
+            if data.len() > 4 && self.failures > 0 {
+                self.failures -= 1;
+                return Err(Ssd1680Error::Interface(()));
+            }
+            Ok(())
+        }
+
+        async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<()>> {
+            Ok(())
+        }
+
+        async fn retry_backoff(&mut self, attempt: u8) -> bool {
+            attempt < self.retries
+        }
+    }
+
+    #[futures_test::test]
+    async fn failed_ram_writes_are_retried_from_the_window_setup() {
+        let config = builder(8, 64).build().expect("invalid config");
+        let interface = Flaky {
+            commands: [0; 32],
+            len: 0,
+            failures: 1,
+            retries: 1,
+        };
+        let mut display = Display::new(interface, config);
+        let frame = [0xFF; 64];
+        assert_eq!(
+            display.write_ram(RamPlane::BlackWhite, &frame).await,
+            Ok(())
+        );
+        let (interface, config) = display.release();
+        let window = [0x44, 0x45, 0x4E, 0x4F, 0x24];
+        assert_eq!(
+            interface.commands[..interface.len],
+            [window, window].concat()
+        );
+
+        let mut display = Display::new(
+            Flaky {
+                failures: 2,
+                ..interface
+            },
+            config,
+        );
+        assert_eq!(
+            display.write_ram(RamPlane::BlackWhite, &frame).await,
+            Err(Ssd1680Error::Interface(()))
+        );
+    }
+
+    #[futures_test::test]
+    async fn transfers_are_split_into_chunks() {
+        use crate::{
+            busy::FixedDelay,
+            reset::NoReset,
+            test_util::{Delay, Pin, Spi, Transfer},
+        };
+
+        let mut interface = Interface::new(
+            Spi::new(),
+            FixedDelay::new(),
+            Pin,
+            NoReset,
+            Delay::default(),
+        )
+        .chunk_size(4);
+        assert_eq!(interface.send_data(&[0; 10]).await, Ok(()));
+        assert_eq!(interface.read_data(&mut [0; 9]).await, Ok(()));
+        assert_eq!(interface.read_data(&mut [0; 2]).await, Ok(()));
+        assert_eq!(
+            interface.spi.transfers(),
+            [
+                Transfer::Write(4),
+                Transfer::Write(4),
+                Transfer::Write(2),
+                Transfer::Read(4),
+                Transfer::Read(4),
+                Transfer::Read(1),
+                Transfer::Read(2),
+            ]
+        );
+    }
+
+    /// A chip select pin counting its assertions.
+    struct CountingCs<'a>(&'a Cell<u8>);
+
+    impl embedded_hal::digital::ErrorType for CountingCs<'_> {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for CountingCs<'_> {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.0.set(self.0.get() + 1);
             Ok(())
         }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    /// An SPI bus recording the length of each write and the chip select assertion it was in.
+    struct Bus<'a> {
+        selections: &'a Cell<u8>,
+        writes: [(usize, u8); 8],
+        len: usize,
+    }
+
+    impl embedded_hal::spi::ErrorType for Bus<'_> {
+        type Error = Infallible;
+    }
+
+    impl SpiBus<u8> for Bus<'_> {
+        async fn read(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+            unimplemented!()
+        }
+
+        async fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+            self.writes[self.len] = (words.len(), self.selections.get());
+            self.len += 1;
+            Ok(())
+        }
+
+        async fn transfer(&mut self, _read: &mut [u8], _write: &[u8]) -> Result<(), Infallible> {
+            unimplemented!()
+        }
+
+        async fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+            unimplemented!()
+        }
+
+        async fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[futures_test::test]
+    async fn commands_with_data_are_sent_in_one_selection() {
+        use crate::{
+            busy::FixedDelay,
+            reset::NoReset,
+            test_util::{Delay, Pin},
+        };
+
+        let selections = Cell::new(0);
+        let bus = Bus {
+            selections: &selections,
+            writes: [(0, 0); 8],
+            len: 0,
+        };
+        let cs = CountingCs(&selections);
+        let mut interface =
+            Interface::from_bus(bus, cs, FixedDelay::new(), Pin, NoReset, Delay::default())
+                .unwrap();
+        assert_eq!(
+            interface.send_command_with_data(0x11, &[0x03]).await,
+            Ok(())
+        );
+        assert_eq!(interface.send_command(0x12).await, Ok(()));
+        assert_eq!(interface.send_data(&[0x00, 0x01]).await, Ok(()));
+        let (bus, ..) = interface.spi.release();
+        assert_eq!(bus.writes[..bus.len], [(1, 1), (1, 1), (1, 2), (2, 3)]);
     }
 }
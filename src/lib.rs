@@ -11,7 +11,8 @@
 //! * A [Display]
 //!
 //! The [Interface] captures the details of the hardware connection to the ssd1680 controller. This
-//! includes an SPI device and some GPIO pins. The ssd1680 can control many different displays that
+//! includes an SPI device and some GPIO pins. Boards without a free SPI peripheral can use the
+//! bit-banged [BitBangInterface] instead. The ssd1680 can control many different displays that
 //! vary in dimensions, rotation, and driving characteristics. The [Config] captures these details.
 //! To aid in constructing the [Config] there is a [Builder] interface. Finally when you have an
 //! interface and a [Config] a [Display] instance can be created.
@@ -28,21 +29,102 @@
 //! 1. [update](graphics/struct.GraphicDisplay.html#method.update)
 //! 1. [sleep](display/struct.Display.html#method.deep_sleep)
 //!
+//! The [prelude] module re-exports the commonly used types and traits, including the
+//! embedded-graphics prelude when the `graphics` feature is enabled. Products supporting several
+//! panels can describe each one with the [panel!] macro, which generates the configuration and
+//! correctly sized buffer types.
+//!
+//! The drawing support only needs the `embedded-graphics-core` crate. Projects that want to
+//! choose their own embedded-graphics release can disable the default features and enable
+//! `graphics-core`, which provides [GraphicDisplay] without the embedded-graphics prelude. Any
+//! embedded-graphics release built on `embedded-graphics-core` 0.4 (i.e. 0.8) can then draw to it.
+//! Projects still on embedded-graphics 0.7 can enable the `graphics-0_7` feature as well, see the
+//! `graphics_0_7` module.
+//!
+//! Projects without an async executor can enable the `blocking` feature and use the wrappers in
+//! the `blocking` module, which drive the same implementation from blocking `embedded_hal` SPI
+//! and delay traits.
+//!
+//! Factory provisioning tools can enable the `otp-program` feature to program VCOM, the display
+//! options and the user ID into the controller's one time programmable memory. It is off by
+//! default so that application firmware cannot burn the OTP by accident.
+//!
+//! With the `alloc` feature a [GraphicDisplay] can allocate its buffers from the heap with
+//! `new_boxed`, which is convenient on Linux hosts and on MCUs with an allocator.
+//! The `image-formats` feature adds loading of XBM and PBM images into a [GraphicDisplay], see
+//! the `image` module, and the `widgets` feature adds a progress bar, a battery gauge and signal
+//! bars, see the `widgets` module. With the `serde` feature the dimensions, rotation and other
+//! panel settings can be loaded from a provisioning file, see the `profile` module.
+//!
+//! The `std` feature, for hosts and CI, adds `GraphicDisplay::snapshot` to export the frame
+//! buffer as a PBM or PNG image for debugging, see the `snapshot` module. The `simulator`
+//! feature adds an interface that emulates the controller, so UIs can be developed on a desktop
+//! with the `embedded-graphics-simulator` crate, see the `simulator` module.
+//!
 //! [Interface]: interface/struct.Interface.html
+//! [BitBangInterface]: bitbang/struct.BitBangInterface.html
 //! [Display]: display/struct.Display.html
 //! [GraphicDisplay]: display/struct.GraphicDisplay.html
 //! [Config]: config/struct.Config.html
 //! [Builder]: config/struct.Builder.html
+//! [prelude]: prelude/index.html
 //! [embedded-graphics]: https://crates.io/crates/embedded-graphics
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod bitbang;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod busy;
+pub mod chip_select;
 pub mod command;
 pub mod config;
 pub mod display;
+pub mod error;
+#[cfg(feature = "graphics-core")]
+pub mod frame;
+#[cfg(feature = "graphics-core")]
 pub mod graphics;
+#[cfg(feature = "graphics-0_7")]
+pub mod graphics_0_7;
+#[cfg(feature = "image-formats")]
+pub mod image;
 pub mod interface;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod panel;
+pub mod prelude;
+#[cfg(feature = "serde")]
+pub mod profile;
+pub mod reset;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "std")]
+pub mod snapshot;
+pub mod stats;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "graphics-core")]
+pub mod tiled;
+pub mod trace;
+#[cfg(feature = "mock")]
+pub mod transcript;
+#[cfg(feature = "widgets")]
+pub mod widgets;
 
-pub use config::Builder;
-pub use display::{Dimensions, Display, Rotation};
+pub use busy::{BusyTiming, FixedDelay};
+pub use config::{Builder, InitProfile, TemperaturePolicy, WaveformSource};
+pub use display::{
+    with_power_down, BitOrder, Color, ControllerState, Dimensions, Display, DropAction,
+    FrameLayout, Otp, PowerGuard, PowerRails, RamPlane, Region, Rotation,
+};
+pub use error::Ssd1680Error;
+#[cfg(feature = "graphics-core")]
 pub use graphics::GraphicDisplay;
 pub use interface::DisplayInterface;
-pub use interface::Interface;
+pub use interface::{Interface, RetryPolicy};
+pub use reset::{NoReset, ResetTiming};
+pub use stats::Stats;
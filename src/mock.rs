@@ -0,0 +1,210 @@
+//! A test double for `DisplayInterface`, enabled by the `mock` feature.
+//!
+//! [MockInterface] records everything the driver sends, so firmware that uses the driver can be
+//! unit tested without hardware. It can also check the traffic against a list of expected
+//! [Event]s as it happens, panicking at the first difference.
+//!
+//! ```
+//! use ssd1680::command::Command;
+//! use ssd1680::mock::{Event, MockInterface};
+//!
+//! async fn sets_x_address() {
+//!     let expected = [Event::Command(0x4E), Event::Data(3)];
+//!     let mut interface = MockInterface::<16>::new().expect(&expected);
+//!     Command::XAddress(3).execute(&mut interface).await.unwrap();
+//!     interface.assert_done();
+//! }
+//! ```
+
+use crate::{
+    error::Ssd1680Error,
+    interface::DisplayInterface,
+    transcript::{self, Transcript},
+};
+use core::convert::Infallible;
+
+/// An interaction between the driver and the controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// A hardware reset
+    Reset,
+    /// A command byte, sent with DC low
+    Command(u8),
+    /// A data byte, sent with DC high
+    Data(u8),
+    /// A wait for the controller to not be busy
+    BusyWait,
+}
+
+/// A `DisplayInterface` that records up to `N` events.
+///
+/// Recording more than `N` events panics, as does an event that differs from the expected
+/// sequence set with `expect`.
+pub struct MockInterface<'a, const N: usize> {
+    events: [Event; N],
+    len: usize,
+    expected: Option<&'a [Event]>,
+    /// Number of events recorded, including those forgotten by `clear`
+    total: usize,
+    read_data: &'a [u8],
+    read_offset: usize,
+    busy: bool,
+}
+
+impl<const N: usize> Default for MockInterface<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> MockInterface<'a, N> {
+    /// Create a new mock that records events without checking them.
+    pub fn new() -> Self {
+        Self {
+            events: [Event::Reset; N],
+            len: 0,
+            expected: None,
+            total: 0,
+            read_data: &[],
+            read_offset: 0,
+            busy: false,
+        }
+    }
+
+    /// Check each event against `expected` as it is recorded.
+    ///
+    /// Call `assert_done` at the end of the test to check that every expected event occurred.
+    pub fn expect(self, expected: &'a [Event]) -> Self {
+        Self {
+            expected: Some(expected),
+            ..self
+        }
+    }
+
+    /// Set the bytes returned by `read_data`, in order. Reads past the end return zeros.
+    pub fn read_data(self, read_data: &'a [u8]) -> Self {
+        Self {
+            read_data,
+            read_offset: 0,
+            ..self
+        }
+    }
+
+    /// Set the value reported by `is_busy`.
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy = busy;
+    }
+
+    /// Returns the events recorded so far.
+    pub fn events(&self) -> &[Event] {
+        &self.events[..self.len]
+    }
+
+    /// Returns the command bytes recorded so far, in order.
+    pub fn commands(&self) -> impl Iterator<Item = u8> + '_ {
+        self.events().iter().filter_map(|event| match event {
+            Event::Command(command) => Some(*command),
+            _ => None,
+        })
+    }
+
+    /// Returns the data sent with the last occurrence of `command`.
+    pub fn data_for(&self, command: u8) -> impl Iterator<Item = u8> + '_ {
+        let start = self
+            .events()
+            .iter()
+            .rposition(|event| *event == Event::Command(command))
+            .map_or(self.len, |index| index + 1);
+        self.events()[start..]
+            .iter()
+            .map_while(|event| match event {
+                Event::Data(byte) => Some(*byte),
+                _ => None,
+            })
+    }
+
+    /// Returns the events recorded so far, formatted as a transcript.
+    ///
+    /// See the [transcript](../transcript/index.html) module.
+    pub fn transcript(&self) -> Transcript<'_> {
+        Transcript(self.events())
+    }
+
+    /// Panics unless the events recorded so far match the transcript `golden`.
+    pub fn assert_transcript(&self, golden: &str) {
+        transcript::assert_matches(self.events(), golden);
+    }
+
+    /// Forget the events recorded so far.
+    ///
+    /// Expectations continue from where they were.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Panics unless every expected event has been recorded.
+    pub fn assert_done(&self) {
+        if let Some(expected) = self.expected {
+            assert!(
+                self.total >= expected.len(),
+                "expected {} events, only {} were recorded",
+                expected.len(),
+                self.total
+            );
+        }
+    }
+
+    fn record(&mut self, event: Event) {
+        if let Some(expected) = self.expected {
+            assert_eq!(
+                expected.get(self.total),
+                Some(&event),
+                "unexpected event at index {}",
+                self.total
+            );
+        }
+        assert!(self.len < N, "more than {} events recorded", N);
+        self.events[self.len] = event;
+        self.len += 1;
+        self.total += 1;
+    }
+}
+
+impl<const N: usize> DisplayInterface for MockInterface<'_, N> {
+    type Error = Infallible;
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.record(Event::Command(command));
+        Ok(())
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        for byte in data {
+            self.record(Event::Data(*byte));
+        }
+        Ok(())
+    }
+
+    async fn read_data(&mut self, data: &mut [u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        for byte in data {
+            *byte = self.read_data.get(self.read_offset).copied().unwrap_or(0);
+            self.read_offset += 1;
+        }
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.record(Event::Reset);
+        Ok(())
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.record(Event::BusyWait);
+        Ok(())
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<Self::Error>> {
+        Ok(self.busy)
+    }
+}
@@ -0,0 +1,139 @@
+//! Panel descriptors.
+//!
+//! The [panel!](../macro.panel.html) macro generates a module describing a particular panel, so a
+//! product line with several panel SKUs can keep dimensions, buffers and configuration in one
+//! place.
+
+/// Generate a module describing a panel.
+///
+/// The generated module contains:
+///
+/// * `ROWS` and `COLS` constants, checked against the controller limits at compile time
+/// * `BUFFER_SIZE`, the size in bytes of a frame buffer, and a `Buffer` array type of that size
+/// * `GraphicDisplay<'a, I>`, an alias for a `GraphicDisplay` using `Buffer`s, if the
+///   `graphics-core` feature is enabled
+/// * `OwnedGraphicDisplay<I>`, an alias for a `GraphicDisplay` owning its `Buffer`s, likewise
+/// * `CONFIG`, the `Config` for the panel, built at compile time
+///
+/// An optional `gate_offset` sets the first gate line scanned, for panels whose glass is not
+/// connected from gate 0.
+///
+/// ### Example
+///
+/// ```
+/// ssd1680::panel! {
+///     /// GoodDisplay GDEY029T94 2.9" B/W panel
+///     pub mod gdey029t94 {
+///         rows: 296,
+///         cols: 128,
+///         rotation: Rotate90,
+///     }
+/// }
+///
+/// ssd1680::panel! {
+///     /// A 200 row panel connected from gate 8
+///     pub mod offset_panel {
+///         rows: 200,
+///         cols: 176,
+///         rotation: Rotate0,
+///         gate_offset: 8,
+///     }
+/// }
+///
+/// static CONFIG: ssd1680::config::Config<'static> = gdey029t94::CONFIG;
+/// let mut black_buffer: gdey029t94::Buffer = [0xFF; gdey029t94::BUFFER_SIZE];
+/// assert_eq!(black_buffer.len(), 296 * 128 / 8);
+/// ```
+///
+/// Dimensions the controller cannot drive fail to compile:
+///
+/// ```compile_fail
+/// ssd1680::panel! {
+///     pub mod too_wide {
+///         rows: 296,
+///         cols: 200,
+///         rotation: Rotate0,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! panel {
+    (
+        $(#[$meta:meta])*
+        $vis:vis mod $name:ident {
+            rows: $rows:expr,
+            cols: $cols:expr,
+            rotation: $rotation:ident
+            $(, gate_offset: $gate_offset:expr)? $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// The number of rows (gate lines) of the panel.
+            pub const ROWS: u16 = $rows;
+            /// The number of columns (source lines) of the panel.
+            pub const COLS: u8 = $cols;
+            /// The size in bytes of a frame buffer for the panel.
+            pub const BUFFER_SIZE: usize = (ROWS as usize * COLS as usize) / 8;
+
+            const _: () = {
+                assert!(COLS % 8 == 0, "columns must be evenly divisible by 8");
+                assert!(
+                    ROWS <= $crate::display::MAX_GATE_OUTPUTS,
+                    "rows must be less than MAX_GATE_OUTPUTS"
+                );
+                assert!(
+                    COLS <= $crate::display::MAX_SOURCE_OUTPUTS,
+                    "cols must be less than MAX_SOURCE_OUTPUTS"
+                );
+            };
+
+            /// A frame buffer for the panel.
+            pub type Buffer = [u8; BUFFER_SIZE];
+
+            $crate::__panel_graphic_displays!();
+
+            /// The display configuration for the panel.
+            pub const CONFIG: $crate::config::Config<'static> = match $crate::config::Builder::new()
+                .dimensions($crate::display::Dimensions {
+                    rows: ROWS,
+                    cols: COLS,
+                })
+                .rotation($crate::display::Rotation::$rotation)
+                $(.gate_scan_start($gate_offset))?
+                .build()
+            {
+                Ok(config) => config,
+                Err(_) => panic!("invalid panel configuration"),
+            };
+        }
+    };
+}
+
+/// The `GraphicDisplay` aliases of a [panel!](../macro.panel.html) module.
+///
+/// Defined according to the features of this crate, as a `cfg` in `panel!` would check those of
+/// the crate using it.
+#[cfg(feature = "graphics-core")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __panel_graphic_displays {
+    () => {
+        /// A `GraphicDisplay` for the panel, drawing into `Buffer`s.
+        pub type GraphicDisplay<'a, I> = $crate::graphics::GraphicDisplay<'a, I, &'a mut Buffer>;
+
+        /// A `GraphicDisplay` for the panel that owns its `Buffer`s.
+        pub type OwnedGraphicDisplay<I> = $crate::graphics::OwnedGraphicDisplay<I, BUFFER_SIZE>;
+    };
+}
+
+/// The `GraphicDisplay` aliases of a [panel!](../macro.panel.html) module, without graphics.
+#[cfg(not(feature = "graphics-core"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __panel_graphic_displays {
+    () => {};
+}
@@ -0,0 +1,38 @@
+//! Commonly used types and traits.
+//!
+//! Glob import the prelude to get everything typically needed to configure and draw to a display:
+//!
+//! ```
+//! use ssd1680::prelude::*;
+//!
+//! let config = Builder::new()
+//!     .dimensions(Dimensions {
+//!         rows: 296,
+//!         cols: 128,
+//!     })
+//!     .rotation(Rotation::Rotate90)
+//!     .build()
+//!     .expect("invalid configuration");
+//! ```
+//!
+//! When the `graphics` feature is enabled the
+//! [embedded-graphics prelude](https://docs.rs/embedded-graphics/latest/embedded_graphics/prelude/index.html)
+//! is re-exported as well. Its `Dimensions` trait is re-exported anonymously so that it does not
+//! clash with the display [Dimensions](../display/struct.Dimensions.html).
+
+pub use crate::{
+    command::{LutLoadMode, UpdateSequence},
+    config::{Builder, InitProfile},
+    display::{
+        with_power_down, ControllerState, Dimensions, Display, DropAction, FrameLayout, PowerGuard,
+        RamPlane, Region, Rotation,
+    },
+    error::Ssd1680Error,
+    interface::{DisplayInterface, Interface},
+};
+
+#[cfg(feature = "graphics-core")]
+pub use crate::graphics::{GraphicDisplay, BLACK, WHITE};
+
+#[cfg(feature = "graphics")]
+pub use embedded_graphics::{geometry::Dimensions as _, pixelcolor::BinaryColor, prelude::*};
@@ -0,0 +1,251 @@
+//! Panel profiles loaded from provisioning files, enabled by the `serde` feature.
+//!
+//! A `Config` refers to lookup tables and temperature policies in memory, so it cannot be
+//! deserialized itself. A [PanelProfile] holds the settings that vary between panels, and
+//! refers to a lookup table by [LutName], so a gateway supporting several SKUs can read its panel
+//! from JSON or TOML at run time. The profile is turned into a `Builder`, which can be customised
+//! further, e.g. with temperature lookup tables, before building the `Config`:
+//!
+//! ```
+//! use ssd1680::profile::{PanelProfile, ProfileError};
+//!
+//! static LUT_2IN13: [u8; ssd1680::config::LUT_SIZE] = [0; ssd1680::config::LUT_SIZE];
+//!
+//! let json = r#"{
+//!     "dimensions": { "rows": 250, "cols": 128 },
+//!     "rotation": "Rotate270",
+//!     "vcom_mv": -1600,
+//!     "lut": "2in13"
+//! }"#;
+//! let profile: PanelProfile = serde_json::from_str(json).unwrap();
+//! let luts = |name: &str| (name == "2in13").then_some(&LUT_2IN13[..]);
+//! let config = profile.builder(luts).unwrap().build();
+//! assert!(config.is_ok());
+//! assert_eq!(
+//!     profile.builder(|_| None).err(),
+//!     Some(ProfileError::UnknownLut)
+//! );
+//! ```
+
+use crate::{
+    command::{BorderWaveform, TemperatureSensor},
+    config::Builder,
+    display::{BitOrder, Dimensions, FrameLayout, Rotation},
+};
+use core::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The settings of a panel, as stored in a provisioning file.
+///
+/// Only `dimensions` is required, settings that are left out keep the `Builder` defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PanelProfile {
+    /// See `Builder::dimensions`
+    pub dimensions: Dimensions,
+    /// See `Builder::rotation`
+    #[serde(default)]
+    pub rotation: Rotation,
+    /// See `Builder::frame_layout`
+    #[serde(default)]
+    pub frame_layout: FrameLayout,
+    /// See `Builder::bit_order`
+    #[serde(default)]
+    pub bit_order: BitOrder,
+    /// See `Builder::drawing_offset`
+    #[serde(default)]
+    pub drawing_offset: (i16, i16),
+    /// See `Builder::border`
+    #[serde(default)]
+    pub border: Option<BorderWaveform>,
+    /// See `Builder::vcom`
+    #[serde(default)]
+    pub vcom: Option<u8>,
+    /// See `Builder::vcom_voltage`, used instead of `vcom` if both are given
+    #[serde(default)]
+    pub vcom_mv: Option<i16>,
+    /// See `Builder::gate_driving_voltage`
+    #[serde(default)]
+    pub gate_driving_voltage_mv: Option<u16>,
+    /// VSH1, VSH2 and VSL, see `Builder::source_driving_voltage`
+    #[serde(default)]
+    pub source_driving_voltage_mv: Option<(u16, u16, i16)>,
+    /// See `Builder::dummy_line_period`
+    #[serde(default)]
+    pub dummy_line_period: Option<u8>,
+    /// See `Builder::gate_line_width`
+    #[serde(default)]
+    pub gate_line_width: Option<u8>,
+    /// See `Builder::gate_scan_start`
+    #[serde(default)]
+    pub gate_scan_start: u16,
+    /// See `Builder::full_refresh_interval`
+    #[serde(default)]
+    pub full_refresh_interval: Option<u16>,
+    /// See `Builder::temperature_sensor`
+    #[serde(default)]
+    pub temperature_sensor: Option<TemperatureSensor>,
+    /// The name of the lookup table, resolved by `builder`. See `Builder::lut`.
+    #[serde(default)]
+    pub lut: Option<LutName>,
+}
+
+/// The name of a lookup table in a [PanelProfile].
+///
+/// The name is copied into the profile rather than borrowed from the input, so it can be read
+/// from escaped JSON strings and from formats like TOML. It holds up to `LutName::CAPACITY` bytes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct LutName {
+    bytes: [u8; Self::CAPACITY],
+    len: u8,
+}
+
+impl LutName {
+    /// The longest name, in bytes.
+    pub const CAPACITY: usize = 32;
+
+    /// Returns the name, or `None` if it is longer than `CAPACITY`.
+    pub fn new(name: &str) -> Option<Self> {
+        let mut bytes = [0; Self::CAPACITY];
+        bytes
+            .get_mut(..name.len())?
+            .copy_from_slice(name.as_bytes());
+        Some(Self {
+            bytes,
+            len: name.len() as u8,
+        })
+    }
+
+    /// The name as a string slice.
+    pub fn as_str(&self) -> &str {
+        // Always valid, the bytes are copied from a `str`
+        core::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for LutName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl Serialize for LutName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LutName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = LutName;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a name of at most {} bytes", LutName::CAPACITY)
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<LutName, E> {
+                LutName::new(name).ok_or_else(|| E::invalid_length(name.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// Error returned by `PanelProfile::builder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProfileError {
+    /// The profile names a lookup table that `luts` does not provide.
+    UnknownLut,
+}
+
+impl PanelProfile {
+    /// Returns a `Builder` with the settings of the profile.
+    ///
+    /// `luts` returns the lookup table with the given name, e.g. from a table of the LUTs
+    /// compiled into the firmware. The settings are validated by `Builder::build`.
+    pub fn builder<'l>(
+        &self,
+        luts: impl Fn(&str) -> Option<&'l [u8]>,
+    ) -> Result<Builder<'l>, ProfileError> {
+        let (x, y) = self.drawing_offset;
+        let mut builder = Builder::new()
+            .dimensions(self.dimensions)
+            .rotation(self.rotation)
+            .frame_layout(self.frame_layout)
+            .bit_order(self.bit_order)
+            .drawing_offset(x, y)
+            .gate_scan_start(self.gate_scan_start);
+        if let Some(border) = self.border {
+            builder = builder.border(border);
+        }
+        if let Some(vcom) = self.vcom {
+            builder = builder.vcom(vcom);
+        }
+        if let Some(vcom_mv) = self.vcom_mv {
+            builder = builder.vcom_voltage(vcom_mv);
+        }
+        if let Some(vgh_mv) = self.gate_driving_voltage_mv {
+            builder = builder.gate_driving_voltage(vgh_mv);
+        }
+        if let Some((vsh1_mv, vsh2_mv, vsl_mv)) = self.source_driving_voltage_mv {
+            builder = builder.source_driving_voltage(vsh1_mv, vsh2_mv, vsl_mv);
+        }
+        if let Some(period) = self.dummy_line_period {
+            builder = builder.dummy_line_period(period);
+        }
+        if let Some(width) = self.gate_line_width {
+            builder = builder.gate_line_width(width);
+        }
+        if let Some(interval) = self.full_refresh_interval {
+            builder = builder.full_refresh_interval(interval);
+        }
+        if let Some(sensor) = self.temperature_sensor {
+            builder = builder.temperature_sensor(sensor);
+        }
+        if let Some(name) = self.lut {
+            builder = builder.lut(luts(name.as_str()).ok_or(ProfileError::UnknownLut)?);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LutName, PanelProfile};
+    use crate::display::Dimensions;
+
+    #[test]
+    fn escaped_lut_names_can_be_read() {
+        let json = r#"{ "dimensions": { "rows": 250, "cols": 128 }, "lut": "2in\u0031\"3" }"#;
+        let profile: PanelProfile = serde_json::from_str(json).unwrap();
+        assert_eq!(profile.lut.unwrap().as_str(), "2in1\"3");
+    }
+
+    #[test]
+    fn profiles_can_be_read_from_toml() {
+        let toml = "lut = '2in13'\n[dimensions]\nrows = 250\ncols = 128\n";
+        let profile: PanelProfile = toml::from_str(toml).unwrap();
+        assert_eq!(
+            profile.dimensions,
+            Dimensions {
+                rows: 250,
+                cols: 128
+            }
+        );
+        assert_eq!(profile.lut, LutName::new("2in13"));
+    }
+
+    #[test]
+    fn long_lut_names_are_rejected() {
+        let name = "0123456789abcdef0123456789abcdefg";
+        assert_eq!(LutName::new(name), None);
+        assert!(LutName::new(&name[1..]).is_some());
+        let json = r#"{ "dimensions": { "rows": 8, "cols": 8 }, "lut": "0123456789abcdef0123456789abcdefg" }"#;
+        assert!(serde_json::from_str::<PanelProfile>(json).is_err());
+    }
+}
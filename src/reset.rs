@@ -0,0 +1,88 @@
+//! Ways of resetting the controller.
+//!
+//! Any `OutputPin` connected to the controller's RESET line can be used as the reset pin of an
+//! interface. Boards that tie RESET to the MCU reset or to VCC can pass [NoReset] instead, in which
+//! case `Display::reset` relies on the software reset alone.
+
+use crate::error::Ssd1680Error;
+use core::future::Future;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+// Section 15.2 of the HINK-E0213A07 data sheet says to hold for 10ms
+const RESET_DELAY_MS: u32 = 10;
+
+/// Timing of the hardware reset sequence, in milliseconds.
+///
+/// The defaults hold RESET low for 10 ms and wait 10 ms after releasing it, as described in
+/// section 15.2 of the HINK-E0213A07 data sheet. Check the data sheet of the module for its
+/// requirements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResetTiming {
+    /// Time to hold RESET high before pulsing it low
+    pub high_ms: u32,
+    /// Time to hold RESET low
+    pub low_ms: u32,
+    /// Time to wait after releasing RESET before talking to the controller
+    pub settle_ms: u32,
+}
+
+impl Default for ResetTiming {
+    fn default() -> Self {
+        Self {
+            high_ms: 0,
+            low_ms: RESET_DELAY_MS,
+            settle_ms: RESET_DELAY_MS,
+        }
+    }
+}
+
+/// A way of performing a hardware reset of the controller.
+pub trait Reset {
+    /// Perform the hardware reset sequence described by `timing`.
+    fn pulse<E>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timing: ResetTiming,
+    ) -> impl Future<Output = Result<(), Ssd1680Error<E>>>;
+}
+
+impl<P> Reset for P
+where
+    P: OutputPin,
+{
+    async fn pulse<E>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timing: ResetTiming,
+    ) -> Result<(), Ssd1680Error<E>> {
+        if timing.high_ms > 0 {
+            self.set_high().map_err(|_| Ssd1680Error::ResetPinError)?;
+            delay.delay_ms(timing.high_ms).await;
+        }
+        self.set_low().map_err(|_| Ssd1680Error::ResetPinError)?;
+        delay.delay_ms(timing.low_ms).await;
+        self.set_high().map_err(|_| Ssd1680Error::ResetPinError)?;
+        delay.delay_ms(timing.settle_ms).await;
+        Ok(())
+    }
+}
+
+/// Used in place of a reset pin when the controller's RESET line is not connected to the MCU.
+///
+/// Hardware resets do nothing, so `Display::reset` falls back to the software reset. Only a
+/// hardware reset wakes the controller from deep sleep, so `Display::deep_sleep` must not be used
+/// with this, nor `Builder::auto_deep_sleep`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoReset;
+
+impl Reset for NoReset {
+    async fn pulse<E>(
+        &mut self,
+        _delay: &mut impl DelayNs,
+        _timing: ResetTiming,
+    ) -> Result<(), Ssd1680Error<E>> {
+        Ok(())
+    }
+}
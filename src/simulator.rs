@@ -0,0 +1,467 @@
+//! A `DisplayInterface` that emulates the controller on a desktop, enabled by the `simulator`
+//! feature.
+//!
+//! [SimulatorInterface] decodes the command stream sent by the driver: the RAM window, address
+//! counters, data entry mode, RAM writes, auto write patterns and update sequences. As on the
+//! panel, the image only changes when an update sequence that drives the display is activated,
+//! so a missing refresh or a write while in deep sleep is visible. The image is shown in an
+//! [embedded-graphics-simulator](https://crates.io/crates/embedded-graphics-simulator) window
+//! through `SimulatorInterface::simulator_display`. The window needs SDL2, so the application
+//! enables the `with-sdl` feature of `embedded-graphics-simulator` itself:
+//!
+//! ```ignore
+//! use embedded_graphics_simulator::{OutputSettings, Window};
+//!
+//! let interface = SimulatorInterface::new(dimensions);
+//! let mut display = GraphicDisplay::new_boxed(Display::new(interface, config));
+//! let mut window = Window::new("ssd1680", &OutputSettings::default());
+//! // Draw and update as on the hardware, then show the panel
+//! display.update().await?;
+//! window.update(&display.interface().simulator_display(display.rotation()));
+//! ```
+//!
+//! The emulation is limited to the B/W image. The red RAM is kept but not shown, and partial
+//! refreshes (Display Mode 2) show the B/W RAM as a full refresh would, without the differential
+//! driving or ghosting of a real panel.
+
+use crate::{
+    display::{Dimensions, Rotation},
+    error::Ssd1680Error,
+    graphics::{self, BLACK, WHITE},
+    interface::DisplayInterface,
+};
+use core::convert::Infallible;
+use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
+use embedded_graphics_simulator::SimulatorDisplay;
+use std::{vec, vec::Vec};
+
+// Commands decoded by the simulator
+const DEEP_SLEEP: u8 = 0x10;
+const DATA_ENTRY_MODE: u8 = 0x11;
+const SW_RESET: u8 = 0x12;
+const UPDATE_DISPLAY: u8 = 0x20;
+const UPDATE_DISPLAY_OPTION_2: u8 = 0x22;
+const WRITE_BLACK: u8 = 0x24;
+const WRITE_RED: u8 = 0x26;
+const AUTO_WRITE_RED: u8 = 0x46;
+const AUTO_WRITE_BLACK: u8 = 0x47;
+const X_START_END: u8 = 0x44;
+const Y_START_END: u8 = 0x45;
+const X_ADDRESS: u8 = 0x4E;
+const Y_ADDRESS: u8 = 0x4F;
+
+// Bits of the update sequence (command 0x22)
+const SEQUENCE_DISPLAY: u8 = 0x04;
+const SEQUENCE_MODE_2: u8 = 0x08;
+
+// Data entry mode after reset: X and Y increment, X first
+const DEFAULT_DATA_ENTRY_MODE: u8 = 0b011;
+
+/// A `DisplayInterface` that emulates the controller's RAM and the image on the panel.
+pub struct SimulatorInterface {
+    cols: u8,
+    rows: u16,
+    black: Vec<u8>,
+    red: Vec<u8>,
+    panel: Vec<u8>,
+    command: Option<u8>,
+    data_index: usize,
+    data_entry_mode: u8,
+    x_window: (u8, u8),
+    y_window: (u16, u16),
+    x: u8,
+    y: u16,
+    sequence: u8,
+    asleep: bool,
+    refreshes: u32,
+    partial_refreshes: u32,
+}
+
+impl SimulatorInterface {
+    /// Create a simulator for a panel with the given dimensions, showing a white image.
+    pub fn new(dimensions: Dimensions) -> Self {
+        let size = usize::from(dimensions.cols / 8) * usize::from(dimensions.rows);
+        let mut simulator = SimulatorInterface {
+            cols: dimensions.cols,
+            rows: dimensions.rows,
+            black: vec![0xFF; size],
+            red: vec![0xFF; size],
+            panel: vec![0xFF; size],
+            command: None,
+            data_index: 0,
+            data_entry_mode: DEFAULT_DATA_ENTRY_MODE,
+            x_window: (0, 0),
+            y_window: (0, 0),
+            x: 0,
+            y: 0,
+            sequence: 0,
+            asleep: false,
+            refreshes: 0,
+            partial_refreshes: 0,
+        };
+        simulator.reset_registers();
+        simulator
+    }
+
+    /// Returns the color shown by the panel at physical position `x`, `y`, or `None` if it is
+    /// outside the panel.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<BinaryColor> {
+        self.index(x, y).map(|index| {
+            if self.panel[index] & (0x80 >> (x % 8)) == 0 {
+                BLACK
+            } else {
+                WHITE
+            }
+        })
+    }
+
+    /// Draw the image shown by the panel, in its physical orientation, to `target`.
+    pub fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let (cols, rows) = (u32::from(self.cols), u32::from(self.rows));
+        target.draw_iter((0..rows).flat_map(|y| {
+            (0..cols).filter_map(move |x| {
+                let color = self.pixel(x, y)?;
+                Some(Pixel(Point::new(x as i32, y as i32), color))
+            })
+        }))
+    }
+
+    /// Returns the image shown by the panel as a `SimulatorDisplay`, for an embedded-graphics-simulator
+    /// `Window`.
+    ///
+    /// The image is rotated by `rotation`, so it appears as drawn with a display configured with
+    /// that rotation.
+    pub fn simulator_display(&self, rotation: Rotation) -> SimulatorDisplay<BinaryColor> {
+        let (cols, rows) = (u32::from(self.cols), u32::from(self.rows));
+        let size = match rotation {
+            Rotation::Rotate0 | Rotation::Rotate180 => Size::new(cols, rows),
+            Rotation::Rotate90 | Rotation::Rotate270 => Size::new(rows, cols),
+        };
+        let mut display = SimulatorDisplay::new(size);
+        let pixels = (0..size.height).flat_map(|y| {
+            (0..size.width).filter_map(move |x| {
+                let (physical_x, physical_y) = graphics::rotation(x, y, cols, rows, rotation);
+                let color = self.pixel(physical_x, physical_y)?;
+                Some(Pixel(Point::new(x as i32, y as i32), color))
+            })
+        });
+        let Ok(()) = display.draw_iter(pixels);
+        display
+    }
+
+    /// Returns the contents of the B/W RAM, laid out as for `Display::update` with
+    /// `FrameLayout::RowMajor`.
+    pub fn black_ram(&self) -> &[u8] {
+        &self.black
+    }
+
+    /// Returns the contents of the red RAM, laid out as for `black_ram`.
+    pub fn red_ram(&self) -> &[u8] {
+        &self.red
+    }
+
+    /// Returns the number of refreshes that drove the panel, full and partial.
+    pub fn refreshes(&self) -> u32 {
+        self.refreshes
+    }
+
+    /// Returns the number of partial refreshes.
+    pub fn partial_refreshes(&self) -> u32 {
+        self.partial_refreshes
+    }
+
+    /// Returns whether the controller is in deep sleep, ignoring commands until reset.
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= u32::from(self.cols) || y >= u32::from(self.rows) {
+            return None;
+        }
+        Some((y * u32::from(self.cols / 8) + x / 8) as usize)
+    }
+
+    fn reset_registers(&mut self) {
+        self.command = None;
+        self.data_entry_mode = DEFAULT_DATA_ENTRY_MODE;
+        self.x_window = (0, (self.cols / 8).saturating_sub(1));
+        self.y_window = (0, self.rows.saturating_sub(1));
+        self.x = 0;
+        self.y = 0;
+        self.sequence = 0;
+    }
+
+    fn command(&mut self, command: u8) {
+        if self.asleep {
+            return;
+        }
+        self.command = Some(command);
+        self.data_index = 0;
+        match command {
+            SW_RESET => self.reset_registers(),
+            UPDATE_DISPLAY if self.sequence & SEQUENCE_DISPLAY != 0 => {
+                self.panel.copy_from_slice(&self.black);
+                self.refreshes += 1;
+                if self.sequence & SEQUENCE_MODE_2 != 0 {
+                    self.partial_refreshes += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn data(&mut self, byte: u8) {
+        if self.asleep {
+            return;
+        }
+        let index = self.data_index;
+        self.data_index += 1;
+        match self.command {
+            Some(DEEP_SLEEP) => self.asleep = byte != 0,
+            Some(DATA_ENTRY_MODE) => self.data_entry_mode = byte & 0b111,
+            Some(UPDATE_DISPLAY_OPTION_2) => self.sequence = byte,
+            Some(X_START_END) if index == 0 => self.x_window.0 = byte,
+            Some(X_START_END) if index == 1 => self.x_window.1 = byte,
+            Some(Y_START_END) if index < 4 => {
+                let value = if index < 2 {
+                    &mut self.y_window.0
+                } else {
+                    &mut self.y_window.1
+                };
+                *value = set_byte(*value, index % 2, byte);
+            }
+            Some(X_ADDRESS) if index == 0 => self.x = byte,
+            Some(Y_ADDRESS) if index < 2 => self.y = set_byte(self.y, index, byte),
+            Some(WRITE_BLACK) => self.write(byte, false),
+            Some(WRITE_RED) => self.write(byte, true),
+            Some(AUTO_WRITE_BLACK) if index == 0 => self.auto_write(byte, false),
+            Some(AUTO_WRITE_RED) if index == 0 => self.auto_write(byte, true),
+            _ => {}
+        }
+    }
+
+    /// Write a byte at the address counter and advance it according to the data entry mode.
+    fn write(&mut self, byte: u8, red: bool) {
+        if let Some(index) = self.index(u32::from(self.x) * 8, self.y.into()) {
+            let ram = if red { &mut self.red } else { &mut self.black };
+            ram[index] = byte;
+        }
+        let x_first = self.data_entry_mode & 0b100 == 0;
+        if x_first {
+            if self.step_x() {
+                self.step_y();
+            }
+        } else if self.step_y() {
+            self.step_x();
+        }
+    }
+
+    /// Move the X address counter, returning whether it wrapped at the end of the window.
+    fn step_x(&mut self) -> bool {
+        if self.x == self.x_window.1 {
+            self.x = self.x_window.0;
+            return true;
+        }
+        self.x = if self.data_entry_mode & 0b001 != 0 {
+            self.x.wrapping_add(1)
+        } else {
+            self.x.wrapping_sub(1)
+        };
+        false
+    }
+
+    /// Move the Y address counter, returning whether it wrapped at the end of the window.
+    fn step_y(&mut self) -> bool {
+        if self.y == self.y_window.1 {
+            self.y = self.y_window.0;
+            return true;
+        }
+        self.y = if self.data_entry_mode & 0b010 != 0 {
+            self.y.wrapping_add(1)
+        } else {
+            self.y.wrapping_sub(1)
+        };
+        false
+    }
+
+    /// Fill a RAM plane with the pattern of an auto write command (0x46, 0x47).
+    ///
+    /// The pattern starts with the value of bit 7 and alternates every step height rows and every
+    /// step width columns, giving a solid fill when the steps cover the panel.
+    fn auto_write(&mut self, pattern: u8, red: bool) {
+        const STEP_HEIGHTS: [u32; 8] = [8, 16, 32, 64, 128, 256, 296, 296];
+        const STEP_WIDTHS: [u32; 8] = [8, 16, 32, 64, 128, 176, 176, 176];
+        let first = pattern & 0x80 != 0;
+        let height = STEP_HEIGHTS[usize::from(pattern >> 4 & 0b111)];
+        let width = STEP_WIDTHS[usize::from(pattern & 0b111)];
+        let bytes_per_row = usize::from(self.cols / 8);
+        let ram = if red { &mut self.red } else { &mut self.black };
+        for (index, byte) in ram.iter_mut().enumerate() {
+            let y = (index / bytes_per_row) as u32;
+            let x = (index % bytes_per_row) as u32 * 8;
+            let white = first ^ ((x / width + y / height) % 2 == 1);
+            *byte = if white { 0xFF } else { 0x00 };
+        }
+    }
+}
+
+/// Replace the low (`index` 0) or high (`index` 1) byte of `value`.
+fn set_byte(value: u16, index: usize, byte: u8) -> u16 {
+    if index == 0 {
+        (value & 0xFF00) | u16::from(byte)
+    } else {
+        (value & 0x00FF) | (u16::from(byte) << 8)
+    }
+}
+
+impl DisplayInterface for SimulatorInterface {
+    type Error = Infallible;
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.command(command);
+        Ok(())
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        for byte in data {
+            self.data(*byte);
+        }
+        Ok(())
+    }
+
+    async fn read_data(&mut self, data: &mut [u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        data.fill(0);
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.asleep = false;
+        self.reset_registers();
+        Ok(())
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        Ok(())
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<Self::Error>> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Builder,
+        display::{Display, FrameLayout, RamPlane},
+        graphics::GraphicDisplay,
+    };
+    use embedded_graphics_core::primitives::Rectangle;
+
+    const COLS: u8 = 16;
+    const ROWS: u16 = 4;
+    const SIZE: usize = COLS as usize * ROWS as usize / 8;
+    const DIMENSIONS: Dimensions = Dimensions {
+        rows: ROWS,
+        cols: COLS,
+    };
+
+    fn simulated_display(
+        layout: FrameLayout,
+        rotation: Rotation,
+    ) -> GraphicDisplay<'static, SimulatorInterface, [u8; SIZE]> {
+        let config = Builder::new()
+            .dimensions(DIMENSIONS)
+            .rotation(rotation)
+            .frame_layout(layout)
+            .build()
+            .expect("invalid config");
+        GraphicDisplay::new_owned(Display::new(SimulatorInterface::new(DIMENSIONS), config))
+            .unwrap()
+    }
+
+    #[futures_test::test]
+    async fn simulator_shows_refreshed_frames() {
+        // A diagonal, so a frame shifted by a row would show
+        let black = |x: u32, y: u32| x == 4 * y + 1;
+        for layout in [FrameLayout::RowMajor, FrameLayout::ColumnMajor] {
+            let mut display = simulated_display(layout, Rotation::Rotate0);
+            display.reset().await.unwrap();
+            for y in 0..u32::from(ROWS) {
+                Pixel(Point::new(4 * y as i32 + 1, y as i32), BLACK)
+                    .draw(&mut display)
+                    .unwrap();
+            }
+
+            // Writing the RAM does not change the panel until it is refreshed
+            display
+                .write_ram(RamPlane::BlackWhite, &[0x00; SIZE])
+                .await
+                .unwrap();
+            assert_eq!(display.interface().pixel(0, 0), Some(WHITE));
+            display.update().await.unwrap();
+            let simulator = display.interface();
+            assert_eq!(simulator.refreshes(), 1);
+            assert_eq!(simulator.partial_refreshes(), 0);
+            for y in 0..u32::from(ROWS) {
+                for x in 0..u32::from(COLS) {
+                    let expected = if black(x, y) { BLACK } else { WHITE };
+                    assert_eq!(simulator.pixel(x, y), Some(expected), "{x},{y}");
+                }
+            }
+        }
+    }
+
+    #[futures_test::test]
+    async fn simulator_shows_partial_refreshes() {
+        for layout in [FrameLayout::RowMajor, FrameLayout::ColumnMajor] {
+            let mut display = simulated_display(layout, Rotation::Rotate0);
+            display.reset().await.unwrap();
+            display.clear(WHITE);
+            Pixel(Point::new(9, 2), BLACK).draw(&mut display).unwrap();
+            display
+                .partial_update_rect(Rectangle::new(Point::zero(), Size::new(16, 4)))
+                .await
+                .unwrap();
+            Pixel(Point::new(3, 1), BLACK).draw(&mut display).unwrap();
+            display
+                .partial_update_rect(Rectangle::new(Point::new(0, 1), Size::new(8, 1)))
+                .await
+                .unwrap();
+
+            let simulator = display.interface();
+            assert_eq!(simulator.partial_refreshes(), 2, "{layout:?}");
+            for y in 0..u32::from(ROWS) {
+                for x in 0..u32::from(COLS) {
+                    let expected = if (x, y) == (9, 2) || (x, y) == (3, 1) {
+                        BLACK
+                    } else {
+                        WHITE
+                    };
+                    assert_eq!(simulator.pixel(x, y), Some(expected), "{layout:?} {x},{y}");
+                }
+            }
+        }
+    }
+
+    #[futures_test::test]
+    async fn simulator_display_is_rotated() {
+        let mut display = simulated_display(FrameLayout::RowMajor, Rotation::Rotate90);
+        display.reset().await.unwrap();
+        Pixel(Point::new(1, 2), BLACK).draw(&mut display).unwrap();
+        display
+            .partial_update_rect(Rectangle::new(Point::zero(), Size::new(4, 16)))
+            .await
+            .unwrap();
+
+        let simulator_display = display.interface().simulator_display(Rotation::Rotate90);
+        assert_eq!(simulator_display.size(), Size::new(4, 16));
+        assert_eq!(simulator_display.get_pixel(Point::new(1, 2)), BLACK);
+        assert_eq!(simulator_display.get_pixel(Point::new(2, 1)), WHITE);
+    }
+}
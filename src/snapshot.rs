@@ -0,0 +1,131 @@
+//! Export of frame buffers as images for debugging, enabled by the `std` feature.
+//!
+//! A [Snapshot] is a copy of what `GraphicDisplay::update` would send to the panel, taken with
+//! `GraphicDisplay::snapshot`. It is independent of the configured `FrameLayout` and `BitOrder`,
+//! and is in the physical orientation of the panel, `cols` pixels wide and `rows` pixels high.
+//! It can be written as a PBM or PNG image, e.g. to inspect a layout on a host or as a CI
+//! artifact.
+
+use crate::{
+    display::{BitOrder, FrameLayout},
+    graphics::{BLACK, WHITE},
+};
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use std::{io, vec::Vec};
+
+/// A B/W image of a frame, one bit per pixel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    width: u32,
+    height: u32,
+    /// Rows of `width / 8` bytes, leftmost pixel in the most significant bit, set bits white
+    pixels: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Copy a frame buffer of a display with `cols` columns and `rows` rows.
+    pub(crate) fn new(
+        buffer: &[u8],
+        cols: u8,
+        rows: u16,
+        layout: FrameLayout,
+        bit_order: BitOrder,
+    ) -> Self {
+        let (width, height) = (u32::from(cols), u32::from(rows));
+        let mut pixels = Vec::with_capacity(buffer.len());
+        for y in 0..height {
+            for x in (0..width).step_by(8) {
+                let index = layout.byte_index(x, y, width, height) as usize;
+                pixels.push(bit_order.convert(buffer[index]));
+            }
+        }
+        Snapshot {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the color of the pixel at `x`, `y`, or `None` if it is outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<BinaryColor> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let byte = self.pixels[(y * self.width / 8 + x / 8) as usize];
+        Some(if byte & (0x80 >> (x % 8)) == 0 {
+            BLACK
+        } else {
+            WHITE
+        })
+    }
+
+    /// Returns the image as a binary PBM (P4) file.
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut pbm = std::format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+        // PBM uses set bits for black
+        pbm.extend(self.pixels.iter().map(|byte| !byte));
+        pbm
+    }
+
+    /// Write the image as a binary PBM (P4) file.
+    pub fn write_pbm(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_all(&self.to_pbm())
+    }
+
+    /// Write the image as a 1 bit grayscale PNG file.
+    pub fn write_png(&self, writer: impl io::Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        display::{BitOrder, Dimensions, FrameLayout, Rotation},
+        frame::Frame,
+        graphics::{BLACK, WHITE},
+    };
+    use embedded_graphics_core::prelude::*;
+
+    #[test]
+    fn snapshot_is_in_panel_order() {
+        let mut frame = Frame::new(
+            [0u8; 4],
+            Dimensions { rows: 2, cols: 16 },
+            Rotation::Rotate0,
+        )
+        .layout(FrameLayout::ColumnMajor)
+        .bit_order(BitOrder::LsbFirst);
+        frame.clear(WHITE).unwrap();
+        Pixel(Point::new(1, 0), BLACK).draw(&mut frame).unwrap();
+        Pixel(Point::new(8, 1), BLACK).draw(&mut frame).unwrap();
+
+        let snapshot = frame.snapshot();
+        assert_eq!((snapshot.width(), snapshot.height()), (16, 2));
+        assert_eq!(snapshot.pixel(0, 0), Some(WHITE));
+        assert_eq!(snapshot.pixel(1, 0), Some(BLACK));
+        assert_eq!(snapshot.pixel(8, 1), Some(BLACK));
+        assert_eq!(snapshot.pixel(16, 0), None);
+        assert_eq!(snapshot.to_pbm(), b"P4\n16 2\n\x40\x00\x00\x80");
+
+        let mut png = std::vec::Vec::new();
+        snapshot.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Counters of the work done by a display, for panel wear management and telemetry.
+//!
+//! Every `Display` counts the refreshes it starts and the bytes it sends, without any
+//! configuration. The counters are read with `Display::stats`.
+
+use crate::{
+    command::{Operation, OperationTracker},
+    error::Ssd1680Error,
+    interface::DisplayInterface,
+};
+
+/// Counters kept by a `Display`, returned by `Display::stats`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+    /// Refreshes started, full and partial
+    pub refreshes: u32,
+    /// Partial refreshes started
+    pub partial_refreshes: u32,
+    /// Partial refreshes since the last full refresh
+    pub partial_refreshes_since_full: u16,
+    /// Approximate duration of the last completed refresh in milliseconds, if the interface can
+    /// tell. See `DisplayInterface::last_busy_ms`.
+    pub last_refresh_ms: Option<u32>,
+    /// Bytes sent to the controller, counting command and data bytes
+    pub bytes_sent: u64,
+}
+
+/// An interface that counts the traffic passing through it.
+pub(crate) struct Metered<I> {
+    pub(crate) inner: I,
+    pub(crate) stats: Stats,
+    operations: OperationTracker,
+    refreshing: bool,
+}
+
+impl<I> Metered<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self {
+            inner,
+            stats: Stats::default(),
+            operations: OperationTracker::new(),
+            refreshing: false,
+        }
+    }
+
+    fn command_sent(&mut self, command: u8) {
+        self.stats.bytes_sent += 1;
+        match self.operations.command_sent(command) {
+            Some(Operation::FullRefresh) => self.stats.refreshes += 1,
+            Some(Operation::PartialRefresh) => {
+                self.stats.refreshes += 1;
+                self.stats.partial_refreshes += 1;
+            }
+            _ => return,
+        }
+        self.refreshing = true;
+    }
+
+    fn data_sent(&mut self, data: &[u8]) {
+        self.stats.bytes_sent += data.len() as u64;
+        self.operations.data_sent(data);
+    }
+}
+
+impl<I> DisplayInterface for Metered<I>
+where
+    I: DisplayInterface,
+{
+    type Error = I::Error;
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.command_sent(command);
+        self.inner.send_command(command).await
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.data_sent(data);
+        self.inner.send_data(data).await
+    }
+
+    async fn send_data_iter(
+        &mut self,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Ssd1680Error<Self::Error>> {
+        let bytes_sent = &mut self.stats.bytes_sent;
+        self.inner
+            .send_data_iter(data.inspect(|_| *bytes_sent += 1))
+            .await
+    }
+
+    async fn send_command_with_data(
+        &mut self,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.command_sent(command);
+        self.data_sent(data);
+        self.inner.send_command_with_data(command, data).await
+    }
+
+    async fn read_data(&mut self, data: &mut [u8]) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.inner.read_data(data).await
+    }
+
+    async fn retry_backoff(&mut self, attempt: u8) -> bool {
+        self.inner.retry_backoff(attempt).await
+    }
+
+    async fn reset(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.refreshing = false;
+        self.inner.reset().await
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<Self::Error>> {
+        self.inner.busy_wait().await?;
+        if self.refreshing {
+            self.refreshing = false;
+            self.stats.last_refresh_ms = self.inner.last_busy_ms();
+        }
+        Ok(())
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<Self::Error>> {
+        self.inner.is_busy()
+    }
+
+    fn last_busy_ms(&self) -> Option<u32> {
+        self.inner.last_busy_ms()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        display::Display,
+        test_util::{builder, NullInterface},
+    };
+
+    #[futures_test::test]
+    async fn refreshes_and_bytes_are_counted() {
+        let config = builder(8, 8)
+            .full_refresh_interval(2)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(NullInterface, config);
+        display.update(&[0xFF; 8]).await.unwrap();
+        let after_update = display.stats();
+        assert_eq!(after_update.refreshes, 1);
+        assert_eq!(after_update.partial_refreshes, 0);
+        assert!(after_update.bytes_sent > 8);
+
+        for _ in 0..3 {
+            display
+                .partial_update(&[0xFF; 8], 0, 0, 8, 8)
+                .await
+                .unwrap();
+        }
+        let stats = display.stats();
+        assert_eq!(stats.refreshes, 4);
+        assert_eq!(stats.partial_refreshes, 2);
+        assert_eq!(stats.partial_refreshes_since_full, 0);
+        assert_eq!(stats.last_refresh_ms, None);
+        assert!(stats.bytes_sent > after_update.bytes_sent);
+
+        display.reset_stats();
+        assert_eq!(display.stats().refreshes, 0);
+        assert_eq!(display.stats().bytes_sent, 0);
+    }
+}
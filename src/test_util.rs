@@ -0,0 +1,129 @@
+//! Fixtures shared by the unit tests.
+
+use crate::{
+    config::Builder, display::Dimensions, error::Ssd1680Error, interface::DisplayInterface,
+};
+use core::convert::Infallible;
+use embedded_hal::{
+    digital::{self, InputPin, OutputPin},
+    spi::{ErrorType, Operation},
+};
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+/// An interface that accepts everything and is never busy.
+pub(crate) struct NullInterface;
+
+impl DisplayInterface for NullInterface {
+    type Error = ();
+
+    async fn send_command(&mut self, _command: u8) -> Result<(), Ssd1680Error<()>> {
+        Ok(())
+    }
+
+    async fn send_data(&mut self, _data: &[u8]) -> Result<(), Ssd1680Error<()>> {
+        Ok(())
+    }
+
+    async fn read_data(&mut self, _data: &mut [u8]) -> Result<(), Ssd1680Error<()>> {
+        Ok(())
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Ssd1680Error<()>> {
+        Ok(())
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Ssd1680Error<()>> {
+        Ok(false)
+    }
+}
+
+/// A builder for a display with the given dimensions and default settings otherwise.
+pub(crate) const fn builder(rows: u16, cols: u8) -> Builder<'static> {
+    Builder::new().dimensions(Dimensions { rows, cols })
+}
+
+/// A transfer made by [Spi].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Transfer {
+    Write(usize),
+    Read(usize),
+}
+
+/// An SPI device that records the length of each transfer, in transactions of their own.
+pub(crate) struct Spi {
+    pub(crate) transfers: [Transfer; 32],
+    pub(crate) len: usize,
+}
+
+impl Spi {
+    pub(crate) fn new() -> Self {
+        Self {
+            transfers: [Transfer::Write(0); 32],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn transfers(&self) -> &[Transfer] {
+        &self.transfers[..self.len]
+    }
+}
+
+impl ErrorType for Spi {
+    type Error = Infallible;
+}
+
+impl SpiDevice<u8> for Spi {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        for operation in operations {
+            self.transfers[self.len] = match operation {
+                Operation::Write(data) => Transfer::Write(data.len()),
+                Operation::Read(data) => Transfer::Read(data.len()),
+                _ => unimplemented!(),
+            };
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// A pin whose input is always low, ignoring output.
+pub(crate) struct Pin;
+
+impl digital::ErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl InputPin for Pin {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(true)
+    }
+}
+
+/// A delay that returns immediately, adding up the time it should have waited.
+#[derive(Default, Clone)]
+pub(crate) struct Delay {
+    pub(crate) waited_ns: u64,
+}
+
+impl DelayNs for Delay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.waited_ns += u64::from(ns);
+    }
+}
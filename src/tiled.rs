@@ -0,0 +1,267 @@
+//! Several panels presented as one drawing surface, enabled by the `graphics` or `graphics-core` feature.
+//!
+//! [Tiled] places two [Tile]s side by side or one above the other, e.g. two 2.13" panels forming
+//! one wide display. Tiles nest, so three panels in a row are a `Tiled<A, Tiled<B, C>>`. Drawing
+//! is split between the panels by position, and `update` refreshes them concurrently, each
+//! through its own controller. The panels can still be updated individually through `parts_mut`.
+
+use crate::{graphics::GraphicDisplay, interface::DisplayInterface, Ssd1680Error};
+use core::{
+    convert::Infallible,
+    future::{poll_fn, Future},
+    iter,
+    pin::pin,
+    task::Poll,
+};
+use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
+
+/// A drawing surface that can be part of a [Tiled] display.
+pub trait Tile: DrawTarget<Color = BinaryColor, Error = Infallible> {
+    /// The error returned by `update`.
+    type UpdateError;
+
+    /// Update the panels by writing the buffers to the controllers.
+    fn update(&mut self) -> impl Future<Output = Result<(), Self::UpdateError>>;
+}
+
+impl<I, B> Tile for GraphicDisplay<'_, I, B>
+where
+    I: DisplayInterface,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    type UpdateError = Ssd1680Error<I::Error>;
+
+    async fn update(&mut self) -> Result<(), Self::UpdateError> {
+        GraphicDisplay::update(self).await
+    }
+}
+
+/// How the tiles of a [Tiled] display are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Arrangement {
+    /// The second tile is to the right of the first
+    Horizontal,
+    /// The second tile is below the first
+    Vertical,
+}
+
+/// An error from one of the tiles of a [Tiled] display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TiledError<A, B> {
+    /// Updating the first tile failed.
+    First(A),
+    /// Updating the second tile failed.
+    Second(B),
+}
+
+/// Two tiles presented as a single `DrawTarget`.
+///
+/// Tiles of different heights (or widths, when arranged vertically) are aligned to the top
+/// (left), and pixels outside both tiles are discarded.
+pub struct Tiled<A, B> {
+    first: A,
+    second: B,
+    arrangement: Arrangement,
+}
+
+impl<A, B> Tiled<A, B>
+where
+    A: Tile,
+    B: Tile,
+{
+    /// Place `second` to the right of `first`.
+    pub fn horizontal(first: A, second: B) -> Self {
+        Tiled {
+            first,
+            second,
+            arrangement: Arrangement::Horizontal,
+        }
+    }
+
+    /// Place `second` below `first`.
+    pub fn vertical(first: A, second: B) -> Self {
+        Tiled {
+            first,
+            second,
+            arrangement: Arrangement::Vertical,
+        }
+    }
+
+    /// Returns the tiles, e.g. to partially update one of them.
+    pub fn parts_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first, &mut self.second)
+    }
+
+    /// Release the tiles.
+    pub fn release(self) -> (A, B) {
+        (self.first, self.second)
+    }
+
+    /// Returns the position of the second tile's origin.
+    fn offset(&self) -> Point {
+        let size = self.first.bounding_box().size;
+        match self.arrangement {
+            Arrangement::Horizontal => Point::new(size.width as i32, 0),
+            Arrangement::Vertical => Point::new(0, size.height as i32),
+        }
+    }
+}
+
+impl<A, B> OriginDimensions for Tiled<A, B>
+where
+    A: Tile,
+    B: Tile,
+{
+    fn size(&self) -> Size {
+        let first = self.first.bounding_box().size;
+        let second = self.second.bounding_box().size;
+        match self.arrangement {
+            Arrangement::Horizontal => {
+                Size::new(first.width + second.width, first.height.max(second.height))
+            }
+            Arrangement::Vertical => {
+                Size::new(first.width.max(second.width), first.height + second.height)
+            }
+        }
+    }
+}
+
+impl<A, B> DrawTarget for Tiled<A, B>
+where
+    A: Tile,
+    B: Tile,
+{
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Self::Error>
+    where
+        Iter: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let first = self.first.bounding_box();
+        let offset = self.offset();
+        for Pixel(point, color) in pixels {
+            if first.contains(point) {
+                self.first.draw_iter(iter::once(Pixel(point, color)))?;
+            } else {
+                self.second
+                    .draw_iter(iter::once(Pixel(point - offset, color)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A, B> Tile for Tiled<A, B>
+where
+    A: Tile,
+    B: Tile,
+{
+    type UpdateError = TiledError<A::UpdateError, B::UpdateError>;
+
+    /// Update both tiles concurrently, returning the first tile's error if both fail.
+    async fn update(&mut self) -> Result<(), Self::UpdateError> {
+        let (first, second) = join(self.first.update(), self.second.update()).await;
+        first.map_err(TiledError::First)?;
+        second.map_err(TiledError::Second)
+    }
+}
+
+/// Poll two futures until both have completed.
+async fn join<A, B>(a: A, b: B) -> (A::Output, B::Output)
+where
+    A: Future,
+    B: Future,
+{
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    let mut a_output = None;
+    let mut b_output = None;
+    poll_fn(|cx| {
+        if a_output.is_none() {
+            if let Poll::Ready(output) = a.as_mut().poll(cx) {
+                a_output = Some(output);
+            }
+        }
+        if b_output.is_none() {
+            if let Poll::Ready(output) = b.as_mut().poll(cx) {
+                b_output = Some(output);
+            }
+        }
+        match (a_output.take(), b_output.take()) {
+            (Some(a), Some(b)) => Poll::Ready((a, b)),
+            (a, b) => {
+                a_output = a;
+                b_output = b;
+                Poll::Pending
+            }
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        display::{Display, Rotation},
+        graphics::BLACK,
+        test_util::{builder, NullInterface},
+    };
+
+    /// A display that is 3 pixels wide and 8 high after rotation.
+    fn display() -> Display<'static, NullInterface> {
+        let config = builder(3, 8)
+            .rotation(Rotation::Rotate270)
+            .build()
+            .expect("invalid config");
+        Display::new(NullInterface, config)
+    }
+
+    #[futures_test::test]
+    async fn tiled_splits_drawing_between_displays() {
+        let mut left_buffer = [0xFFu8; 3];
+        let mut right_buffer = [0xFFu8; 3];
+        let mut left_work = [0u8; 3];
+        let mut right_work = [0u8; 3];
+
+        {
+            let mut tiled = Tiled::horizontal(
+                GraphicDisplay::new(display(), &mut left_buffer, &mut left_work),
+                GraphicDisplay::new(display(), &mut right_buffer, &mut right_work),
+            );
+            assert_eq!(tiled.size(), Size::new(6, 8));
+            Pixel(Point::new(4, 0), BLACK).draw(&mut tiled).unwrap();
+            assert!(tiled.update().await.is_ok());
+        }
+
+        assert_eq!(left_buffer, [0xFF, 0xFF, 0xFF]);
+        assert_eq!(right_buffer, [0xFF, 0b01111111, 0xFF]);
+    }
+
+    #[test]
+    fn vertical_tiles_are_stacked() {
+        let mut expected = [0xFFu8; 3];
+        let mut work = [0u8; 3];
+        let mut single = GraphicDisplay::new(display(), &mut expected, &mut work);
+        Pixel(Point::new(1, 1), BLACK).draw(&mut single).unwrap();
+
+        let mut top_buffer = [0xFFu8; 3];
+        let mut bottom_buffer = [0xFFu8; 3];
+        let mut top_work = [0u8; 3];
+        let mut bottom_work = [0u8; 3];
+        {
+            let mut tiled = Tiled::vertical(
+                GraphicDisplay::new(display(), &mut top_buffer, &mut top_work),
+                GraphicDisplay::new(display(), &mut bottom_buffer, &mut bottom_work),
+            );
+            assert_eq!(tiled.size(), Size::new(3, 16));
+            Pixel(Point::new(1, 9), BLACK).draw(&mut tiled).unwrap();
+        }
+
+        assert_eq!(top_buffer, [0xFF; 3]);
+        assert_eq!(bottom_buffer, expected);
+    }
+}
@@ -0,0 +1,117 @@
+//! Tracing of the traffic between the driver and the controller.
+//!
+//! When a panel shows garbage it helps to see the exact command stream the driver produced. A
+//! [Tracer] passed to `Interface::tracer` or `BitBangInterface::tracer` is told about each
+//! command, data transfer and busy wait, and can log them, e.g. with defmt over RTT. It is also
+//! told about each interval slept while waiting for the controller, so a refresh lasting seconds
+//! can feed a hardware watchdog or blink a heartbeat LED.
+//!
+//! ```
+//! use ssd1680::trace::Tracer;
+//!
+//! #[derive(Default)]
+//! struct CountingTracer {
+//!     commands: usize,
+//!     bytes: usize,
+//! }
+//!
+//! impl Tracer for CountingTracer {
+//!     fn on_command(&mut self, _command: u8) {
+//!         self.commands += 1;
+//!     }
+//!
+//!     fn on_data(&mut self, len: usize) {
+//!         self.bytes += len;
+//!     }
+//! }
+//! ```
+
+use embedded_hal_async::delay::DelayNs;
+
+/// Receives the traffic between the driver and the controller.
+///
+/// All methods default to doing nothing.
+pub trait Tracer {
+    /// Called before a command is sent.
+    fn on_command(&mut self, _command: u8) {}
+
+    /// Called before `len` bytes of data are sent for the last command.
+    fn on_data(&mut self, _len: usize) {}
+
+    /// Called after waiting for the controller, with the approximate time waited in
+    /// milliseconds if the BUSY strategy can tell.
+    fn on_busy(&mut self, _waited_ms: Option<u32>) {}
+
+    /// Called before each sleep while waiting for the controller, with the time slept so far in
+    /// milliseconds.
+    ///
+    /// The BUSY strategies sleep in steps of `BusyTiming::poll_ms`, so this is the place to feed
+    /// a watchdog. `WaitPin` still returns as soon as the pin goes low.
+    fn on_busy_poll(&mut self, _waited_ms: u32) {}
+}
+
+impl<T> Tracer for &mut T
+where
+    T: Tracer,
+{
+    fn on_command(&mut self, command: u8) {
+        (**self).on_command(command)
+    }
+
+    fn on_data(&mut self, len: usize) {
+        (**self).on_data(len)
+    }
+
+    fn on_busy(&mut self, waited_ms: Option<u32>) {
+        (**self).on_busy(waited_ms)
+    }
+
+    fn on_busy_poll(&mut self, waited_ms: u32) {
+        (**self).on_busy_poll(waited_ms)
+    }
+}
+
+/// A tracer that ignores everything, used when no tracer is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoTracer;
+
+impl Tracer for NoTracer {}
+
+/// A delay provider that reports each sleep to a tracer, used while waiting for BUSY.
+pub(crate) struct TracedDelay<'a, D, T> {
+    pub(crate) delay: &'a mut D,
+    pub(crate) tracer: &'a mut T,
+    pub(crate) waited_ns: u64,
+}
+
+impl<D, T> TracedDelay<'_, D, T>
+where
+    T: Tracer,
+{
+    fn poll(&mut self, ns: u64) {
+        self.tracer
+            .on_busy_poll((self.waited_ns / 1_000_000) as u32);
+        self.waited_ns += ns;
+    }
+}
+
+impl<D, T> DelayNs for TracedDelay<'_, D, T>
+where
+    D: DelayNs,
+    T: Tracer,
+{
+    async fn delay_ns(&mut self, ns: u32) {
+        self.poll(ns.into());
+        self.delay.delay_ns(ns).await
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.poll(u64::from(us) * 1_000);
+        self.delay.delay_us(us).await
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.poll(u64::from(ms) * 1_000_000);
+        self.delay.delay_ms(ms).await
+    }
+}
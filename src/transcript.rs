@@ -0,0 +1,320 @@
+//! Text transcripts of the traffic between the driver and the controller, enabled by the `mock`
+//! feature.
+//!
+//! A transcript lists one [Event] per line, with data bytes grouped onto lines of up to 16:
+//!
+//! ```text
+//! # Comments run to the end of the line
+//! reset
+//! busy
+//! cmd 12
+//! busy
+//! cmd 01
+//! data 27 01 00
+//! ```
+//!
+//! Transcripts recorded from a [MockInterface](../mock/struct.MockInterface.html) can be stored as
+//! golden files and compared against with `assert_matches`, so changes to the initialisation
+//! sequence show up as a readable diff that can be checked against the data sheet or a logic
+//! analyser capture. A transcript can also be `replay`ed on real hardware.
+//!
+//! ```
+//! use ssd1680::mock::Event;
+//! use ssd1680::transcript::{self, Transcript};
+//!
+//! let events = [Event::Reset, Event::Command(0x4E), Event::Data(3)];
+//! let golden = "reset\ncmd 4E # XAddress\ndata 03\n";
+//! transcript::assert_matches(&events, golden);
+//!
+//! // Golden files are recorded by formatting the events, e.g. with `std::fs::write`
+//! assert_eq!(Transcript(&events).to_string(), "reset\ncmd 4E\ndata 03\n");
+//! ```
+
+use crate::{error::Ssd1680Error, interface::DisplayInterface, mock::Event};
+use core::{
+    fmt,
+    iter::Enumerate,
+    str::{Lines, SplitWhitespace},
+};
+
+/// The number of data bytes written on each line.
+const DATA_PER_LINE: usize = 16;
+
+/// Formats events as a transcript.
+pub struct Transcript<'e>(pub &'e [Event]);
+
+impl fmt::Display for Transcript<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data_on_line = 0;
+        for event in self.0 {
+            match event {
+                Event::Data(byte) => {
+                    if data_on_line == DATA_PER_LINE {
+                        writeln!(f)?;
+                        data_on_line = 0;
+                    }
+                    if data_on_line == 0 {
+                        write!(f, "data")?;
+                    }
+                    write!(f, " {byte:02X}")?;
+                    data_on_line += 1;
+                    continue;
+                }
+                _ if data_on_line > 0 => {
+                    writeln!(f)?;
+                    data_on_line = 0;
+                }
+                _ => {}
+            }
+            match event {
+                Event::Reset => writeln!(f, "reset")?,
+                Event::Command(command) => writeln!(f, "cmd {command:02X}")?,
+                Event::BusyWait => writeln!(f, "busy")?,
+                Event::Data(_) => unreachable!(),
+            }
+        }
+        if data_on_line > 0 {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A malformed line in a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ParseError {
+    /// The line number, starting from 1
+    pub line: usize,
+}
+
+/// Parses the events in a transcript.
+pub fn events(transcript: &str) -> Events<'_> {
+    Events {
+        lines: transcript.lines().enumerate(),
+        data: None,
+    }
+}
+
+/// Iterator over the events in a transcript and their line numbers, returned by `events`.
+pub struct Events<'a> {
+    lines: Enumerate<Lines<'a>>,
+    /// The line number and remaining bytes of the current data line
+    data: Option<(usize, SplitWhitespace<'a>)>,
+}
+
+impl Iterator for Events<'_> {
+    type Item = (usize, Result<Event, ParseError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((line, bytes)) = &mut self.data {
+                let line = *line;
+                if let Some(byte) = bytes.next() {
+                    let event = parse_byte(byte).map(Event::Data).ok_or(ParseError { line });
+                    return Some((line, event));
+                }
+                self.data = None;
+            }
+
+            let (index, text) = self.lines.next()?;
+            let line = index + 1;
+            let text = text.split('#').next().unwrap_or("");
+            let mut words = text.split_whitespace();
+            let event = match (words.next(), words.clone().next()) {
+                (None, _) => continue,
+                (Some("reset"), None) => Ok(Event::Reset),
+                (Some("busy"), None) => Ok(Event::BusyWait),
+                (Some("cmd"), Some(command)) if words.clone().count() == 1 => parse_byte(command)
+                    .map(Event::Command)
+                    .ok_or(ParseError { line }),
+                (Some("data"), Some(_)) => {
+                    self.data = Some((line, words));
+                    continue;
+                }
+                _ => Err(ParseError { line }),
+            };
+            return Some((line, event));
+        }
+    }
+}
+
+fn parse_byte(text: &str) -> Option<u8> {
+    if text.len() == 2 {
+        u8::from_str_radix(text, 16).ok()
+    } else {
+        None
+    }
+}
+
+/// Panics unless `events` matches the transcript `golden` exactly, reporting the line of the
+/// first difference.
+pub fn assert_matches(events: &[Event], golden: &str) {
+    let mut expected = self::events(golden);
+    for (index, event) in events.iter().enumerate() {
+        match expected.next() {
+            Some((line, Ok(expected))) => assert_eq!(
+                *event, expected,
+                "event {index} differs from line {line} of the transcript"
+            ),
+            Some((line, Err(_))) => panic!("malformed transcript at line {line}"),
+            None => panic!("event {index} ({event:?}) is missing from the transcript"),
+        }
+    }
+    if let Some((line, _)) = expected.next() {
+        panic!(
+            "transcript continues at line {line} after all {} events",
+            events.len()
+        );
+    }
+}
+
+/// An error from `replay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReplayError<E> {
+    /// The transcript is malformed.
+    Parse(ParseError),
+    /// Sending the transcript failed.
+    Interface(Ssd1680Error<E>),
+}
+
+/// Send the events of a transcript to `interface`.
+///
+/// The transcript is checked while it is replayed, so events before a malformed line have already
+/// been sent when `ReplayError::Parse` is returned.
+pub async fn replay<I>(transcript: &str, interface: &mut I) -> Result<(), ReplayError<I::Error>>
+where
+    I: DisplayInterface,
+{
+    let mut data = [0u8; DATA_PER_LINE];
+    let mut len = 0;
+    for (_, event) in events(transcript) {
+        let event = event.map_err(ReplayError::Parse)?;
+        if let Event::Data(byte) = event {
+            data[len] = byte;
+            len += 1;
+            if len < data.len() {
+                continue;
+            }
+        }
+        if len > 0 {
+            interface
+                .send_data(&data[..len])
+                .await
+                .map_err(ReplayError::Interface)?;
+            len = 0;
+        }
+        match event {
+            Event::Reset => interface.reset().await.map_err(ReplayError::Interface)?,
+            Event::Command(command) => interface
+                .send_command(command)
+                .await
+                .map_err(ReplayError::Interface)?,
+            Event::BusyWait => interface
+                .busy_wait()
+                .await
+                .map_err(ReplayError::Interface)?,
+            Event::Data(_) => {}
+        }
+    }
+    if len > 0 {
+        interface
+            .send_data(&data[..len])
+            .await
+            .map_err(ReplayError::Interface)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{display::Display, mock::MockInterface, test_util::builder};
+
+    #[futures_test::test]
+    async fn reset_and_update_match_the_golden_transcript() {
+        let config = builder(16, 16).build().expect("invalid config");
+        let mut display = Display::new(MockInterface::<512>::new(), config);
+        display.reset().await.unwrap();
+        display.update(&[0xAA; 32]).await.unwrap();
+        display
+            .interface()
+            .assert_transcript(include_str!("testdata/reset_update.transcript"));
+    }
+
+    #[test]
+    fn events_are_parsed_with_their_line_numbers() {
+        let transcript = "# Comment\nreset\n\ncmd 4e # XAddress\ndata 01 FF\n  busy  \n";
+        let mut events = events(transcript);
+        assert_eq!(events.next(), Some((2, Ok(Event::Reset))));
+        assert_eq!(events.next(), Some((4, Ok(Event::Command(0x4E)))));
+        assert_eq!(events.next(), Some((5, Ok(Event::Data(0x01)))));
+        assert_eq!(events.next(), Some((5, Ok(Event::Data(0xFF)))));
+        assert_eq!(events.next(), Some((6, Ok(Event::BusyWait))));
+        assert_eq!(events.next(), None);
+    }
+
+    #[test]
+    fn malformed_lines_are_reported() {
+        for (transcript, line) in [
+            ("reset\ncmd\n", 2),
+            ("cmd 4E 01\n", 1),
+            ("cmd 4\n", 1),
+            ("reset now\n", 1),
+            ("busy\ndata 01 XY\n", 2),
+            ("data\n", 1),
+            ("nop\n", 1),
+        ] {
+            let error = events(transcript).find_map(|(_, event)| event.err());
+            assert_eq!(error, Some(ParseError { line }), "{transcript:?}");
+        }
+    }
+
+    #[test]
+    fn formatted_transcripts_parse_back_to_the_events() {
+        let mut events = [Event::Data(0x5A); 20];
+        events[0] = Event::Reset;
+        events[1] = Event::Command(0x24);
+        events[19] = Event::BusyWait;
+        assert_matches(
+            &events,
+            "reset\ncmd 24\ndata 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A 5A\ndata 5A\nbusy\n",
+        );
+
+        let mut buffer = [0u8; 128];
+        let mut writer = Writer(&mut buffer, 0);
+        fmt::write(&mut writer, format_args!("{}", Transcript(&events))).unwrap();
+        let len = writer.1;
+        let formatted = core::str::from_utf8(&buffer[..len]).unwrap();
+        assert!(self::events(formatted)
+            .map(|(_, event)| event.unwrap())
+            .eq(events));
+    }
+
+    #[futures_test::test]
+    async fn replay_sends_the_transcript() {
+        let transcript = "reset\ncmd 4E\ndata 01 02\nbusy\n";
+        let mut interface = MockInterface::<8>::new();
+        replay(transcript, &mut interface).await.unwrap();
+        interface.assert_transcript(transcript);
+
+        let result = replay("cmd 4E\ndata 0\n", &mut MockInterface::<8>::new()).await;
+        assert_eq!(result, Err(ReplayError::Parse(ParseError { line: 2 })));
+    }
+
+    /// Formats into a fixed buffer.
+    struct Writer<'b>(&'b mut [u8], usize);
+
+    impl fmt::Write for Writer<'_> {
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            let end = self.1 + text.len();
+            self.0
+                .get_mut(self.1..end)
+                .ok_or(fmt::Error)?
+                .copy_from_slice(text.as_bytes());
+            self.1 = end;
+            Ok(())
+        }
+    }
+}
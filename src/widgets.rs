@@ -0,0 +1,287 @@
+//! Small components for e-paper status displays, enabled by the `widgets` feature.
+//!
+//! Each [Widget] draws black on white within its bounds, into a `GraphicDisplay` or any other
+//! embedded-graphics `DrawTarget`, and returns the rectangle it changed. Widgets are redrawn
+//! when their value changes, and the rectangle can be passed to
+//! `GraphicDisplay::partial_update_rect` to refresh only that part of the panel:
+//!
+//! ```
+//! use embedded_graphics::{prelude::*, primitives::Rectangle};
+//! use ssd1680::{
+//!     frame::Frame,
+//!     widgets::{ProgressBar, Widget},
+//!     Dimensions, Rotation,
+//! };
+//!
+//! let dimensions = Dimensions { rows: 16, cols: 32 };
+//! let mut frame = Frame::new([0xFFu8; 64], dimensions, Rotation::Rotate0);
+//! let mut progress = ProgressBar::new(Rectangle::new(Point::new(0, 4), Size::new(32, 8)));
+//! progress.set_progress(50);
+//! let dirty = progress.draw(&mut frame).unwrap();
+//! assert_eq!(dirty, progress.bounds());
+//! ```
+
+use crate::graphics::{BLACK, WHITE};
+use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+
+/// A component drawn within a fixed rectangle.
+pub trait Widget {
+    /// Returns the rectangle the widget is drawn in.
+    fn bounds(&self) -> Rectangle;
+
+    /// Draw the widget, returning the rectangle that was changed.
+    fn draw<D>(&self, target: &mut D) -> Result<Rectangle, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>;
+}
+
+/// A horizontal bar filled from the left in proportion to progress, e.g. of a firmware update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressBar {
+    bounds: Rectangle,
+    percent: u8,
+}
+
+impl ProgressBar {
+    /// Create an empty progress bar.
+    pub fn new(bounds: Rectangle) -> Self {
+        ProgressBar { bounds, percent: 0 }
+    }
+
+    /// Set the progress in percent, limited to 100.
+    pub fn set_progress(&mut self, percent: u8) {
+        self.percent = percent.min(100);
+    }
+
+    /// Returns the progress in percent.
+    pub fn progress(&self) -> u8 {
+        self.percent
+    }
+}
+
+impl Widget for ProgressBar {
+    fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<Rectangle, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        draw_level(target, self.bounds, self.percent)?;
+        Ok(self.bounds)
+    }
+}
+
+/// A battery outline with a terminal on the right, filled in proportion to the charge level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryGauge {
+    bounds: Rectangle,
+    percent: u8,
+}
+
+impl BatteryGauge {
+    /// Create an empty battery gauge. The terminal takes a tenth of the width, at least 2 pixels.
+    pub fn new(bounds: Rectangle) -> Self {
+        BatteryGauge { bounds, percent: 0 }
+    }
+
+    /// Set the charge level in percent, limited to 100.
+    pub fn set_level(&mut self, percent: u8) {
+        self.percent = percent.min(100);
+    }
+
+    /// Returns the charge level in percent.
+    pub fn level(&self) -> u8 {
+        self.percent
+    }
+}
+
+impl Widget for BatteryGauge {
+    fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<Rectangle, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let Size { width, height } = self.bounds.size;
+        let terminal_width = (width / 10).max(2).min(width);
+        let body = Rectangle::new(
+            self.bounds.top_left,
+            Size::new(width - terminal_width, height),
+        );
+        draw_level(target, body, self.percent)?;
+
+        let terminal = Rectangle::new(
+            self.bounds.top_left + Point::new(body.size.width as i32, 0),
+            Size::new(terminal_width, height),
+        );
+        target.fill_solid(&terminal, WHITE)?;
+        let terminal_height = height / 2;
+        target.fill_solid(
+            &Rectangle::new(
+                terminal.top_left + Point::new(0, ((height - terminal_height) / 2) as i32),
+                Size::new(terminal_width, terminal_height),
+            ),
+            BLACK,
+        )?;
+        Ok(self.bounds)
+    }
+}
+
+/// Bars of increasing height showing signal strength, e.g. of a Wi-Fi connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalBars {
+    bounds: Rectangle,
+    bars: u8,
+    strength: u8,
+}
+
+impl SignalBars {
+    /// Create `bars` bars showing no signal, spread across the width with 1 pixel gaps.
+    pub fn new(bounds: Rectangle, bars: u8) -> Self {
+        SignalBars {
+            bounds,
+            bars: bars.max(1),
+            strength: 0,
+        }
+    }
+
+    /// Set the number of filled bars, limited to the number of bars.
+    pub fn set_strength(&mut self, strength: u8) {
+        self.strength = strength.min(self.bars);
+    }
+
+    /// Returns the number of filled bars.
+    pub fn strength(&self) -> u8 {
+        self.strength
+    }
+}
+
+impl Widget for SignalBars {
+    fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<Rectangle, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        target.fill_solid(&self.bounds, WHITE)?;
+        let bars = u32::from(self.bars);
+        let Size { width, height } = self.bounds.size;
+        let bar_width = width.saturating_sub(bars - 1) / bars;
+        for bar in 0..bars {
+            let bar_height = height * (bar + 1) / bars;
+            let rect = Rectangle::new(
+                self.bounds.top_left
+                    + Point::new((bar * (bar_width + 1)) as i32, (height - bar_height) as i32),
+                Size::new(bar_width, bar_height),
+            );
+            if bar < u32::from(self.strength) {
+                target.fill_solid(&rect, BLACK)?;
+            } else {
+                draw_outline(target, rect)?;
+            }
+        }
+        Ok(self.bounds)
+    }
+}
+
+/// Draw an outlined rectangle filled from the left to `percent` of its inside.
+fn draw_level<D>(target: &mut D, rect: Rectangle, percent: u8) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    target.fill_solid(&rect, WHITE)?;
+    draw_outline(target, rect)?;
+    let inside = Rectangle::new(
+        rect.top_left + Point::new(1, 1),
+        Size::new(
+            rect.size.width.saturating_sub(2),
+            rect.size.height.saturating_sub(2),
+        ),
+    );
+    let filled = inside.size.width * u32::from(percent) / 100;
+    target.fill_solid(
+        &Rectangle::new(inside.top_left, Size::new(filled, inside.size.height)),
+        BLACK,
+    )
+}
+
+/// Draw the 1 pixel outline of a rectangle.
+fn draw_outline<D>(target: &mut D, rect: Rectangle) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    if rect.is_zero_sized() {
+        return Ok(());
+    }
+    let Size { width, height } = rect.size;
+    let top_left = rect.top_left;
+    let bottom = top_left + Point::new(0, height as i32 - 1);
+    let right = top_left + Point::new(width as i32 - 1, 0);
+    target.fill_solid(&Rectangle::new(top_left, Size::new(width, 1)), BLACK)?;
+    target.fill_solid(&Rectangle::new(bottom, Size::new(width, 1)), BLACK)?;
+    target.fill_solid(&Rectangle::new(top_left, Size::new(1, height)), BLACK)?;
+    target.fill_solid(&Rectangle::new(right, Size::new(1, height)), BLACK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        display::{Display, Rotation},
+        graphics::GraphicDisplay,
+        test_util::{builder, NullInterface},
+    };
+
+    const SIZE: usize = 32 * 16 / 8;
+
+    #[test]
+    fn widgets_draw_within_their_bounds() {
+        let config = builder(16, 32)
+            .rotation(Rotation::Rotate0)
+            .build()
+            .expect("invalid config");
+        let mut black_buffer = [0xFFu8; SIZE];
+        let mut work_buffer = [0u8; SIZE];
+        let mut display = GraphicDisplay::new(
+            Display::new(NullInterface, config),
+            &mut black_buffer,
+            &mut work_buffer,
+        );
+
+        let mut progress = ProgressBar::new(Rectangle::new(Point::zero(), Size::new(12, 4)));
+        progress.set_progress(50);
+        assert_eq!(progress.draw(&mut display), Ok(progress.bounds()));
+        assert_eq!(display.get_pixel(0, 0), Some(BLACK));
+        assert_eq!(display.get_pixel(5, 1), Some(BLACK));
+        assert_eq!(display.get_pixel(6, 1), Some(WHITE));
+        assert_eq!(display.get_pixel(11, 2), Some(BLACK));
+        assert_eq!(display.get_pixel(12, 2), Some(WHITE));
+
+        // Three bars 3 pixels wide with 1 pixel gaps, the first filled
+        let mut signal = SignalBars::new(Rectangle::new(Point::new(16, 0), Size::new(11, 8)), 3);
+        signal.set_strength(1);
+        assert_eq!(signal.draw(&mut display), Ok(signal.bounds()));
+        assert_eq!(display.get_pixel(17, 6), Some(BLACK));
+        assert_eq!(display.get_pixel(17, 5), Some(WHITE));
+        assert_eq!(display.get_pixel(20, 3), Some(BLACK));
+        assert_eq!(display.get_pixel(21, 4), Some(WHITE));
+        assert_eq!(display.get_pixel(19, 7), Some(WHITE));
+
+        // A body 8 pixels wide and a terminal 2 pixels wide, half as high as the body
+        let mut battery = BatteryGauge::new(Rectangle::new(Point::new(0, 8), Size::new(10, 5)));
+        battery.set_level(50);
+        assert_eq!(battery.draw(&mut display), Ok(battery.bounds()));
+        assert_eq!(display.get_pixel(7, 8), Some(BLACK));
+        assert_eq!(display.get_pixel(3, 10), Some(BLACK));
+        assert_eq!(display.get_pixel(4, 10), Some(WHITE));
+        assert_eq!(display.get_pixel(8, 8), Some(WHITE));
+        assert_eq!(display.get_pixel(9, 10), Some(BLACK));
+        assert_eq!(display.get_pixel(9, 11), Some(WHITE));
+    }
+}